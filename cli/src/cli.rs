@@ -1,7 +1,7 @@
 use std::path::PathBuf;
 
-use clap::{Parser, Subcommand};
-use gixor::{Gixor, Name};
+use clap::{Parser, Subcommand, ValueEnum};
+use gixor::Name;
 
 /// The build information `build.rs` writes out, shown by `--version`.
 const BUILD_LONG_VERSION: &str = include_str!(concat!(env!("OUT_DIR"), "/BUILD_LONG_VERSION.txt"));
@@ -41,6 +41,16 @@ pub(crate) enum GixorCommand {
         about = "Manage the aliases. If no command is given, list the aliases."
     )]
     Alias(AliasOpts),
+    #[command(
+        name = "check",
+        about = "Check that the .gitignore is what dumping would make of it, printing the differences"
+    )]
+    Check(CheckOpts),
+    #[command(
+        name = "diff",
+        about = "Show what dumping the boilerplates would change in the .gitignore, block by block"
+    )]
+    Diff(DiffOpts),
     #[command(name = "dump", about = "Dump the boilerplates")]
     Dump(DumpOpts),
     #[command(
@@ -52,6 +62,11 @@ pub(crate) enum GixorCommand {
     Init,
     #[command(name = "list", alias = "ls", about = "List available boilerplates")]
     List(ListOpts),
+    #[command(
+        name = "outdated",
+        about = "Show the boilerplates in the .gitignore that changed upstream since they were dumped"
+    )]
+    Outdated(OutdatedOpts),
     #[command(name = "root", about = "Show the root directory of the boilerplates")]
     Root(RootOpts),
     #[command(
@@ -60,11 +75,21 @@ pub(crate) enum GixorCommand {
         about = "Search the boilerplates from the query"
     )]
     Search(SearchOpts),
+    #[command(
+        name = "sync",
+        about = "Rebuild the .gitignore from the boilerplates recorded in its .gixor.lock"
+    )]
+    Sync(SyncOpts),
     #[command(
         name = "update",
         about = "Update the gitignore boilerplate repositories (alias of `repository update`)"
     )]
     Update,
+    #[command(
+        name = "upgrade",
+        about = "Move the boilerplates locked in .gixor.lock to their latest content, all of them if no names are given"
+    )]
+    Upgrade(UpgradeOpts),
     #[command(
         name = "repository",
         alias = "repo",
@@ -147,6 +172,31 @@ pub(crate) enum RepositoryOpts {
     List,
     #[command(name = "remove", about = "Remove a gitignore boilerplate repository")]
     Remove(RepoRemoveOpts),
+    #[command(
+        name = "rename",
+        about = "Rename a gitignore boilerplate repository, keeping its clone and aliases"
+    )]
+    Rename(RepoRenameOpts),
+    #[command(
+        name = "move",
+        about = "Change the priority of a gitignore boilerplate repository in searching boilerplates"
+    )]
+    Move(RepoMoveOpts),
+    #[command(
+        name = "enable",
+        about = "Enable a disabled gitignore boilerplate repository"
+    )]
+    Enable(RepoSwitchOpts),
+    #[command(
+        name = "disable",
+        about = "Disable a gitignore boilerplate repository, keeping its clone"
+    )]
+    Disable(RepoSwitchOpts),
+    #[command(
+        name = "set-url",
+        about = "Change the URL of a gitignore boilerplate repository without cloning it again"
+    )]
+    SetUrl(RepoSetUrlOpts),
     #[command(
         name = "update",
         about = "Run `git update` for updating a gitignore boilerplate repository"
@@ -164,10 +214,29 @@ pub(crate) struct RepoAddOpts {
     )]
     pub(crate) name: Option<String>,
 
+    #[clap(
+        short,
+        long,
+        value_name = "BRANCH",
+        help = "Specify the branch to follow. The default branch of the remote is followed if omitted"
+    )]
+    pub(crate) branch: Option<String>,
+
+    #[clap(
+        short,
+        long,
+        value_name = "REV",
+        help = "Pin the repository to a tag, a commit, or a semver range over the tags (e.g., \"^2.1\")"
+    )]
+    pub(crate) rev: Option<String>,
+
     #[clap(
         value_name = "URL|NAME",
         help = r#"Specify the URL or NAME of the gitignore boilerplate repository.
-The NAME shows the owner name of the repository, e.g., "github" means "https://github.com/github/gitignore""#
+The NAME shows the owner name of the repository, e.g., "github" means "https://github.com/github/gitignore".
+"OWNER/REPO" is on GitHub, and the prefixes "gh:", "gl:", "bb:", and "codeberg:" name the host,
+e.g., "gl:group/gitignore". The "shorthands" in the configuration file add prefixes of their own.
+A local repository is given by a path starting with "./", "../", or "/"."#
     )]
     pub(crate) url: String,
 }
@@ -189,8 +258,83 @@ pub(crate) struct RepoRemoveOpts {
     pub(crate) name: String,
 }
 
+#[derive(Parser, Debug)]
+pub(crate) struct RepoRenameOpts {
+    #[clap(
+        value_name = "OLD",
+        help = "Specify the current NAME of the gitignore boilerplate repository"
+    )]
+    pub(crate) old: String,
+
+    #[clap(
+        value_name = "NEW",
+        help = "Specify the new NAME of the gitignore boilerplate repository"
+    )]
+    pub(crate) new: String,
+}
+
+#[derive(Parser, Debug)]
+pub(crate) struct RepoMoveOpts {
+    #[clap(
+        value_name = "NAME",
+        help = "Specify the NAME of the gitignore boilerplate repository"
+    )]
+    pub(crate) name: String,
+
+    #[clap(
+        value_name = "PRIORITY",
+        allow_negative_numbers = true,
+        help = r#"Specify the priority of the repository. The repositories with the higher priority are searched first,
+and those of the same priority in the order of the configuration. The default priority is 0"#
+    )]
+    pub(crate) priority: i32,
+}
+
+#[derive(Parser, Debug)]
+pub(crate) struct RepoSwitchOpts {
+    #[clap(
+        value_name = "NAME",
+        help = "Specify the NAME of the gitignore boilerplate repository"
+    )]
+    pub(crate) name: String,
+}
+
+#[derive(Parser, Debug)]
+pub(crate) struct RepoSetUrlOpts {
+    #[clap(
+        value_name = "NAME",
+        help = "Specify the NAME of the gitignore boilerplate repository"
+    )]
+    pub(crate) name: String,
+
+    #[clap(
+        value_name = "URL",
+        help = "Specify the new URL of the repository. The shorthands of `repository add` are available"
+    )]
+    pub(crate) url: String,
+}
+
 #[derive(Parser, Debug)]
 pub(crate) struct DumpOpts {
+    #[clap(flatten)]
+    pub(crate) build: BuildOpts,
+
+    #[clap(
+        short = 'n',
+        long,
+        help = "Print the result to stdout and leave the gitignore untouched.",
+        default_value_t = false
+    )]
+    pub(crate) dry_run: bool,
+
+    /// Kept so that existing scripts keep working. Appending is the default now.
+    #[clap(short, long, hide = true, default_value_t = false)]
+    pub(crate) append: bool,
+}
+
+/// The options deciding the content a dump builds, which `diff` shares with `dump`.
+#[derive(Parser, Debug)]
+pub(crate) struct BuildOpts {
     #[clap(
         short,
         long,
@@ -209,7 +353,7 @@ pub(crate) struct DumpOpts {
 
     #[clap(
         long,
-        help = "Drop the prologue, the part of the gitignore before the first boilerplate.",
+        help = "Drop the prologue, the part of the gitignore before the first boilerplate, and the rules written after the boilerplates.",
         default_value_t = false
     )]
     pub(crate) clear_prologue: bool,
@@ -223,27 +367,64 @@ pub(crate) struct DumpOpts {
     pub(crate) clear: bool,
 
     #[clap(
-        short = 'n',
+        short,
         long,
-        help = "Print the result to stdout and leave the gitignore untouched.",
+        help = "Overwrite the boilerplates edited by hand since they were dumped.",
+        conflicts_with = "merge",
         default_value_t = false
     )]
-    pub(crate) dry_run: bool,
+    pub(crate) force: bool,
 
-    /// Kept so that existing scripts keep working. Appending is the default now.
-    #[clap(short, long, hide = true, default_value_t = false)]
-    pub(crate) append: bool,
+    #[clap(
+        long,
+        help = "Merge the edits by hand into the new content of the boilerplates, from the commits they were dumped at.",
+        default_value_t = false
+    )]
+    pub(crate) merge: bool,
+
+    #[clap(
+        short,
+        long,
+        help = "Keep the boilerplates at the commits the gitignore records, rather than upgrading them to the latest ones. A name given with a revision moves to that one.",
+        default_value_t = false
+    )]
+    pub(crate) keep_revisions: bool,
 
-    #[clap(value_name = "NAMES...", help = "The boilerplate names to dump.")]
+    #[clap(
+        value_name = "NAMES...",
+        help = "The boilerplate names to dump, each optionally at a revision, such as rust@v2.0 or rust@<commit>."
+    )]
     pub(crate) names: Vec<String>,
 }
 
-impl DumpOpts {
+impl BuildOpts {
     /// Returns true if the prologue of the destination should be dropped.
     pub fn should_clear_prologue(&self) -> bool {
         self.clear || self.clear_prologue
     }
 
+    /// Returns what to make of the boilerplates edited by hand since they were dumped.
+    pub fn edit_policy(&self) -> gixor::document::EditPolicy {
+        use gixor::document::EditPolicy;
+        if self.force {
+            EditPolicy::Overwrite
+        } else if self.merge {
+            EditPolicy::Merge
+        } else {
+            EditPolicy::Refuse
+        }
+    }
+
+    /// Returns which commits to take the boilerplates of the destination at.
+    pub fn revision_policy(&self) -> gixor::document::RevisionPolicy {
+        use gixor::document::RevisionPolicy;
+        if self.keep_revisions {
+            RevisionPolicy::Recorded
+        } else {
+            RevisionPolicy::Latest
+        }
+    }
+
     /// Returns true if the entries already listed in the destination should be dropped.
     fn drop_current_entries(&self) -> bool {
         self.clear || self.no_append
@@ -253,38 +434,22 @@ impl DumpOpts {
     /// Unless the current entries are dropped, they are read from the destination first,
     /// then the given names are added and the `-NAME` ones removed.
     /// Finally, convert `String` to `Name` by `Name::parse` and return it.
-    pub fn names(&self, gixor: &Gixor) -> gixor::Result<Vec<Name>> {
-        let current = self.resolvable_current_list(gixor)?;
+    ///
+    /// The current entries that no longer resolve are left for the dump to drop, which
+    /// reports them.
+    pub fn names(&self) -> gixor::Result<Vec<Name>> {
+        let current = self.current_list_if_append()?;
         Ok(self.names_with(current))
     }
 
     /// Merges the given names into `current` and parses the result.
-    /// Split out from [`DumpOpts::names`] so the merging can be exercised on its own.
+    /// Split out from [`BuildOpts::names`] so the merging can be exercised on its own.
     fn names_with(&self, current: Vec<String>) -> Vec<Name> {
         let v = self.merge_names_with_add_or_remove(&self.names, current);
         log::debug!("parse dumping targets: {}", v.join(", "));
         Name::parse_all(v)
     }
 
-    /// Drops the current entries that no longer resolve to a boilerplate.
-    ///
-    /// These names come from the destination file rather than from the command line, so a
-    /// boilerplate renamed or removed upstream is not the user's mistake and must not block
-    /// the update. Names given on the command line are still resolved strictly, later on.
-    fn resolvable_current_list(&self, gixor: &Gixor) -> gixor::Result<Vec<String>> {
-        let current = self.current_list_if_append()?;
-        Ok(current
-            .into_iter()
-            .filter(|name| match gixor.find(Name::parse(name)) {
-                Ok(_) => true,
-                Err(e) => {
-                    log::warn!("{name}: dropped from the gitignore ({e})");
-                    false
-                }
-            })
-            .collect())
-    }
-
     fn merge_names_with_add_or_remove(
         &self,
         names: &Vec<String>,
@@ -292,8 +457,7 @@ impl DumpOpts {
     ) -> Vec<String> {
         for name in names {
             if let Some(trunk) = name.strip_prefix("-") {
-                let t = trunk.to_lowercase();
-                v.retain(|item| item.to_lowercase() != t);
+                v.retain(|item| !names_entry(trunk, item));
             } else {
                 v.push(name.clone());
             }
@@ -314,11 +478,137 @@ impl DumpOpts {
             // There is nothing to carry over before the gitignore exists, and creating one is
             // the ordinary case now that appending is the default.
             Err(gixor::Error::FileNotFound(_)) => Ok(vec![]),
-            r => r,
+            r => r.map(|entries| entries.iter().map(ToString::to_string).collect()),
         }
     }
 }
 
+/// Returns `true` if `-{trunk}` removes the current entry `item`.
+///
+/// The entries read back are qualified with their repository, such as `team/Rust` or
+/// `default/Global/macOS`, and are named either in full or by what follows the repository. So
+/// `-Rust` drops `team/Rust`, `-team/Rust` drops it and leaves `default/Rust` alone, and
+/// `-Global/macOS` drops `default/Global/macOS`, which `-macOS` does not.
+fn names_entry(trunk: &str, item: &str) -> bool {
+    let (trunk, item) = (trunk.to_lowercase(), item.to_lowercase());
+    item == trunk
+        || item
+            .split_once('/')
+            .is_some_and(|(_, boilerplate)| boilerplate == trunk)
+}
+
+#[derive(Parser, Debug)]
+pub(crate) struct CheckOpts {
+    #[clap(
+        short,
+        long,
+        value_name = "DEST",
+        default_value = ".gitignore",
+        help = "Specify the gitignore to check, or the directory holding it."
+    )]
+    pub(crate) dest: PathBuf,
+
+    #[clap(
+        short,
+        long,
+        help = "Expect the boilerplates at the commits the gitignore records, rather than the latest ones.",
+        default_value_t = false
+    )]
+    pub(crate) recorded: bool,
+}
+
+#[derive(Parser, Debug)]
+pub(crate) struct DiffOpts {
+    #[clap(
+        long,
+        value_name = "WHEN",
+        default_value = "auto",
+        help = "Specify when to color the output. \"auto\" colors it for a terminal"
+    )]
+    pub(crate) color: clap::ColorChoice,
+
+    /// The options of `dump` that decide the content, since the changes are those `dump` would
+    /// make.
+    #[clap(flatten)]
+    pub(crate) build: BuildOpts,
+}
+
+/// How a command prints what it found.
+#[derive(Debug, Clone, Copy, PartialEq, ValueEnum)]
+pub(crate) enum Format {
+    /// Lines for someone to read.
+    Text,
+    /// JSON for another program to read.
+    Json,
+}
+
+#[derive(Parser, Debug)]
+pub(crate) struct OutdatedOpts {
+    #[clap(
+        short,
+        long,
+        value_name = "DEST",
+        default_value = ".gitignore",
+        help = "Specify the gitignore to examine, or the directory holding it."
+    )]
+    pub(crate) dest: PathBuf,
+
+    #[clap(
+        long,
+        value_name = "FORMAT",
+        default_value = "text",
+        help = "Specify the output format"
+    )]
+    pub(crate) format: Format,
+}
+
+#[derive(Parser, Debug)]
+pub(crate) struct SyncOpts {
+    #[clap(
+        short,
+        long,
+        value_name = "DEST",
+        default_value = ".gitignore",
+        help = "Specify the gitignore to rebuild, or the directory holding it and its .gixor.lock."
+    )]
+    pub(crate) dest: PathBuf,
+
+    #[clap(
+        short,
+        long,
+        help = "Overwrite the boilerplates edited by hand since they were dumped.",
+        default_value_t = false
+    )]
+    pub(crate) force: bool,
+}
+
+#[derive(Parser, Debug)]
+pub(crate) struct UpgradeOpts {
+    #[clap(
+        short,
+        long,
+        value_name = "DEST",
+        default_value = ".gitignore",
+        help = "Specify the gitignore to upgrade, or the directory holding it and its .gixor.lock."
+    )]
+    pub(crate) dest: PathBuf,
+
+    #[clap(
+        short,
+        long,
+        help = "Overwrite the boilerplates edited by hand since they were dumped.",
+        default_value_t = false
+    )]
+    pub(crate) force: bool,
+
+    #[clap(
+        index = 1,
+        value_name = "BOILERPLATES",
+        help = "The boilerplates to upgrade, such as rust or default/Rust, and rust@v1.0 to move to a revision."
+    )]
+    pub(crate) names: Vec<String>,
+}
+
 #[derive(Parser, Debug)]
 pub(crate) struct EntriesOpts {
     #[clap(
@@ -368,75 +658,120 @@ pub(crate) struct CompleteOpts {
 mod tests {
     use super::*;
 
-    fn dump_opts(dest: &str, names: Vec<&str>) -> DumpOpts {
-        DumpOpts {
+    fn build_opts(dest: &str, names: Vec<&str>) -> BuildOpts {
+        BuildOpts {
             dest: dest.into(),
             no_append: false,
             clear_prologue: false,
             clear: false,
-            dry_run: false,
-            append: false,
+            force: false,
+            merge: false,
+            keep_revisions: false,
             names: names.into_iter().map(String::from).collect(),
         }
     }
 
     #[test]
-    fn dump_opts_names_appends_to_the_current_ones() {
-        let opts = dump_opts(".gitignore", vec!["java"]);
+    fn build_opts_names_appends_to_the_current_ones() {
+        let opts = build_opts(".gitignore", vec!["java"]);
         let names = opts.names_with(vec!["Rust".into(), "Python".into()]);
         let names = names.iter().map(|n| n.to_string()).collect::<Vec<_>>();
         assert_eq!(names, vec!["Rust", "Python", "java"]);
     }
 
     #[test]
-    fn dump_opts_names_removes_the_ones_prefixed_with_a_dash() {
-        let opts = dump_opts(".gitignore", vec!["-rust", "go"]);
+    fn build_opts_names_removes_the_ones_prefixed_with_a_dash() {
+        let opts = build_opts(".gitignore", vec!["-rust", "go"]);
         let names = opts.names_with(vec!["Rust".into(), "Python".into()]);
         let names = names.iter().map(|n| n.to_string()).collect::<Vec<_>>();
         assert_eq!(names, vec!["Python", "go"]);
+
+        let opts = build_opts(".gitignore", vec!["-rust", "-Global/macOS"]);
+        let names = opts.names_with(vec![
+            "team/Rust".into(),
+            "default/Global/macOS".into(),
+            "default/Python".into(),
+        ]);
+        let names = names.iter().map(|n| n.to_string()).collect::<Vec<_>>();
+        assert_eq!(names, vec!["default/Python"]);
     }
 
     #[test]
-    fn dump_opts_current_list_is_empty_without_a_gitignore() {
+    fn build_opts_names_removes_only_the_entries_of_the_repository_named() {
+        let current = || {
+            [
+                "team/macOS",
+                "default/Global/macOS",
+                "team/Rust",
+                "default/Rust",
+            ]
+            .map(String::from)
+            .to_vec()
+        };
+        let names_after = |removed: Vec<&str>| {
+            let opts = build_opts(".gitignore", removed);
+            let names = opts.names_with(current());
+            names.iter().map(|n| n.to_string()).collect::<Vec<_>>()
+        };
+        assert_eq!(
+            names_after(vec!["-macOS"]),
+            vec!["default/Global/macOS", "team/Rust", "default/Rust"]
+        );
+        assert_eq!(
+            names_after(vec!["-default/rust", "-default/Global/macOS"]),
+            vec!["team/macOS", "team/Rust"]
+        );
+    }
+
+    #[test]
+    fn build_opts_current_list_is_empty_without_a_gitignore() {
         let dir = tempfile::tempdir().unwrap();
         let dest = dir.path().join(".gitignore");
-        let opts = dump_opts(&dest.to_string_lossy(), vec!["java"]);
+        let opts = build_opts(&dest.to_string_lossy(), vec!["java"]);
         // appending is the default, and a missing gitignore is simply an empty one
         assert_eq!(opts.current_list_if_append().unwrap(), Vec::<String>::new());
     }
 
     #[test]
-    fn dump_opts_current_list_is_read_when_appending() {
+    fn build_opts_current_list_is_read_when_appending() {
         let dir = tempfile::tempdir().unwrap();
         let dest = dir.path().join(".gitignore");
         std::fs::write(&dest, "# mine\n### Rust.gitignore\ntarget\n").unwrap();
-        let opts = dump_opts(&dest.to_string_lossy(), vec![]);
+        let opts = build_opts(&dest.to_string_lossy(), vec![]);
         assert_eq!(opts.current_list_if_append().unwrap(), vec!["Rust"]);
+
+        // the generated header tells the repository the boilerplate came from
+        std::fs::write(
+            &dest,
+            "### Generated by Gixor (https://github.com/tamada/gixor) (team/Rust)\n### Rust.gitignore\ntarget\n",
+        )
+        .unwrap();
+        assert_eq!(opts.current_list_if_append().unwrap(), vec!["team/Rust"]);
     }
 
     #[test]
-    fn dump_opts_current_list_is_dropped_by_no_append_and_clear() {
+    fn build_opts_current_list_is_dropped_by_no_append_and_clear() {
         let dir = tempfile::tempdir().unwrap();
         let dest = dir.path().join(".gitignore");
         std::fs::write(&dest, "### Rust.gitignore\ntarget\n").unwrap();
 
-        let mut opts = dump_opts(&dest.to_string_lossy(), vec![]);
+        let mut opts = build_opts(&dest.to_string_lossy(), vec![]);
         opts.no_append = true;
         assert_eq!(opts.current_list_if_append().unwrap(), Vec::<String>::new());
 
-        let mut opts = dump_opts(&dest.to_string_lossy(), vec![]);
+        let mut opts = build_opts(&dest.to_string_lossy(), vec![]);
         opts.clear = true;
         assert_eq!(opts.current_list_if_append().unwrap(), Vec::<String>::new());
     }
 
     #[test]
-    fn dump_opts_clear_implies_clearing_the_prologue() {
-        let mut opts = dump_opts(".gitignore", vec![]);
+    fn build_opts_clear_implies_clearing_the_prologue() {
+        let mut opts = build_opts(".gitignore", vec![]);
         assert!(!opts.should_clear_prologue());
         opts.clear_prologue = true;
         assert!(opts.should_clear_prologue());
 
-        let mut opts = dump_opts(".gitignore", vec![]);
+        let mut opts = build_opts(".gitignore", vec![]);
         opts.clear = true;
         assert!(opts.should_clear_prologue());
         assert!(opts.drop_current_entries());
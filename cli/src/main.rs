@@ -1,9 +1,12 @@
 use clap::{Parser, ValueEnum};
+use std::io::IsTerminal;
 use std::path::PathBuf;
+use std::sync::Arc;
 
 use gixor::{AliasManager, Error, Gixor, GixorFactory, Name, RepositoryManager, Result};
 
 mod cli;
+mod progress;
 mod terminal;
 
 /// Represents the log level.
@@ -18,7 +21,7 @@ pub enum LogLevel {
 
 fn load_gixor(config_path: Option<PathBuf>, no_network: bool) -> Result<(Gixor, bool)> {
     let mut store_flag = false;
-    let gixor = match config_path {
+    let mut gixor = match config_path {
         None => {
             log::trace!("no config path specified. use default configuration");
             GixorFactory::load_or_default()
@@ -29,6 +32,11 @@ fn load_gixor(config_path: Option<PathBuf>, no_network: bool) -> Result<(Gixor,
             g
         }
     };
+    // The bars are for someone watching; piped into a file or another command, they would be
+    // nothing but noise.
+    if std::io::stderr().is_terminal() {
+        gixor.set_observer(Arc::new(progress::Bars::new()));
+    }
     let gixor = if gixor.is_empty() {
         log::trace!("no repositories are given. add default repository");
         store_flag = true;
@@ -40,7 +48,10 @@ fn load_gixor(config_path: Option<PathBuf>, no_network: bool) -> Result<(Gixor,
     } else {
         gixor
     };
-    match gixor.prepare(no_network) {
+    // An unreachable remote should not stop the commands that only read: the clone it left
+    // behind is used as it is, with a warning. `update` asks for the real thing by itself.
+    let policy = gixor::repos::PreparePolicy::KeepStale;
+    match gixor.prepare_with(no_network, policy).into_result() {
         Err(e) => Err(e),
         _ => Ok((gixor, store_flag)),
     }
@@ -100,15 +111,85 @@ fn perform_alias(gixor: &mut Gixor, opts: cli::AliasOpts) -> Result<Option<&Gixo
     }
 }
 
-fn perform_dump(gixor: &Gixor, opts: cli::DumpOpts) -> Result<Option<&Gixor>> {
-    let (dest, clear_prologue) = (opts.dest.clone(), opts.should_clear_prologue());
-    let names = opts.names(gixor)?;
+fn perform_dump(gixor: &mut Gixor, opts: cli::DumpOpts) -> Result<Option<&Gixor>> {
+    let build = &opts.build;
+    let (dest, clear_prologue) = (build.dest.clone(), build.should_clear_prologue());
+    gixor.set_edit_policy(build.edit_policy());
+    gixor.set_revision_policy(build.revision_policy());
+    let names = build.names()?;
     if opts.dry_run {
         let content = gixor.build_gitignore(names, &dest, clear_prologue)?;
         print!("{content}");
         return Ok(None);
     }
-    gixor.dump_to(names, dest, clear_prologue).map(|_| None)
+    let report = gixor.dump_to(names, &dest, clear_prologue)?;
+    // the gitignore itself goes to stdout with `-`, and the report must stay out of it
+    if dest == "-" {
+        eprintln!("{report}");
+    } else {
+        println!("{report}");
+    }
+    Ok(None)
+}
+
+/// Rebuilds the gitignore from its lock. The repositories the lock added to the configuration
+/// are stored with it.
+fn perform_sync(gixor: &mut Gixor, opts: cli::SyncOpts) -> Result<Option<&Gixor>> {
+    if opts.force {
+        gixor.set_edit_policy(gixor::document::EditPolicy::Overwrite);
+    }
+    let before = gixor.len();
+    let report = gixor.sync(&opts.dest)?;
+    println!("{report}");
+    if gixor.len() > before {
+        Ok(Some(gixor))
+    } else {
+        Ok(None)
+    }
+}
+
+/// Moves the boilerplates of the lock forward and dumps the gitignore with them.
+fn perform_upgrade(gixor: &mut Gixor, opts: cli::UpgradeOpts) -> Result<Option<&Gixor>> {
+    if opts.force {
+        gixor.set_edit_policy(gixor::document::EditPolicy::Overwrite);
+    }
+    let names = opts.names.iter().map(Name::parse).collect();
+    let report = gixor.upgrade(names, &opts.dest)?;
+    println!("{report}");
+    Ok(None)
+}
+
+/// Prints what dumping would change in the gitignore, block by block, leaving it as it is.
+fn show_diff(gixor: &mut Gixor, opts: cli::DiffOpts) -> Result<Option<&Gixor>> {
+    let build = opts.build;
+    gixor.set_edit_policy(build.edit_policy());
+    gixor.set_revision_policy(build.revision_policy());
+    let names = build.names()?;
+    let check = gixor.diff(names, &build.dest, build.should_clear_prologue())?;
+    let color = match opts.color {
+        clap::ColorChoice::Always => true,
+        clap::ColorChoice::Never => false,
+        clap::ColorChoice::Auto => {
+            std::io::stdout().is_terminal() && std::env::var_os("NO_COLOR").is_none()
+        }
+    };
+    print!("{}", terminal::format_changes(&check.changes(), color));
+    Ok(None)
+}
+
+/// Prints how the gitignore differs from what dumping its entries would make of it, and fails
+/// when it does, so that a CI job stops there.
+fn check_gitignore(gixor: &mut Gixor, opts: cli::CheckOpts) -> Result<Option<&Gixor>> {
+    if opts.recorded {
+        gixor.set_revision_policy(gixor::document::RevisionPolicy::Recorded);
+    }
+    let names = gixor::entries(&opts.dest)?
+        .into_iter()
+        .map(|entry| entry.name)
+        .collect();
+    let check = gixor.check(names, &opts.dest)?;
+    print!("{}", check.diff());
+    check.into_result().map(|_| None)
 }
 
 fn list_each_boilerplate(
@@ -169,6 +250,7 @@ fn list_entries(_: &Gixor, opts: cli::EntriesOpts) -> Result<Option<&Gixor>> {
     match gixor::entries(opts.dir) {
         Err(e) => Err(e),
         Ok(entries) => {
+            let entries = entries.iter().map(ToString::to_string).collect();
             print_in_columns_if_needed(entries, None);
             Ok(None)
         }
@@ -189,9 +271,18 @@ fn show_root(gixor: &Gixor, opts: cli::RootOpts) -> Result<Option<&Gixor>> {
 }
 
 fn update_repositories(gixor: &Gixor) -> Result<Option<&Gixor>> {
-    match gixor.prepare(false) {
-        Ok(_) => Ok(None),
-        Err(e) => Err(e),
+    let report = gixor.update();
+    if !report.repositories.is_empty() {
+        println!("{report}");
+    }
+    let failed = report.iter().filter(|p| !p.outcome.is_success()).count();
+    if failed == 0 {
+        Ok(None)
+    } else {
+        Err(Error::Fatal(format!(
+            "{failed} of {} repositories failed to update",
+            report.repositories.len()
+        )))
     }
 }
 
@@ -210,10 +301,13 @@ fn search_boilerplates(gixor: &Gixor, opts: cli::SearchOpts) -> Result<Option<&G
 }
 
 fn add_repository(gixor: &mut Gixor, opts: cli::RepoAddOpts) -> Result<Option<&Gixor>> {
-    let repo = match opts.name {
-        Some(name) => gixor::repos::Repository::new_with(name, opts.url),
-        None => gixor::repos::Repository::new(opts.url),
+    let url = gixor.resolve_url(&opts.url)?;
+    let mut repo = match opts.name {
+        Some(name) => gixor::repos::Repository::new_with(name, url),
+        None => gixor::repos::Repository::new(url),
     };
+    repo.branch = opts.branch;
+    repo.rev = opts.rev;
     match gixor.add_repository(repo) {
         Ok(_) => Ok(Some(gixor)),
         Err(e) => Err(e),
@@ -227,40 +321,105 @@ fn remove_repository(gixor: &mut Gixor, opts: cli::RepoRemoveOpts) -> Result<Opt
     }
 }
 
+fn rename_repository(gixor: &mut Gixor, opts: cli::RepoRenameOpts) -> Result<Option<&Gixor>> {
+    gixor.rename_repository(opts.old, opts.new)?;
+    Ok(Some(gixor))
+}
+
+fn move_repository(gixor: &mut Gixor, opts: cli::RepoMoveOpts) -> Result<Option<&Gixor>> {
+    gixor.set_repository_priority(opts.name, opts.priority)?;
+    Ok(Some(gixor))
+}
+
+fn enable_repository(
+    gixor: &mut Gixor,
+    opts: cli::RepoSwitchOpts,
+    enabled: bool,
+) -> Result<Option<&Gixor>> {
+    gixor.set_repository_enabled(opts.name, enabled)?;
+    Ok(Some(gixor))
+}
+
+fn set_repository_url(gixor: &mut Gixor, opts: cli::RepoSetUrlOpts) -> Result<Option<&Gixor>> {
+    let url = gixor.resolve_url(&opts.url)?;
+    gixor.set_repository_url(opts.name, url)?;
+    Ok(Some(gixor))
+}
+
 fn list_repositories(gixor: &Gixor) -> Result<Option<&Gixor>> {
     let base_path = gixor.base_path().to_path_buf();
     for repo in gixor.repositories() {
+        let state = match (repo.enabled, repo.priority) {
+            (false, _) => " (disabled)".to_string(),
+            (true, 0) => String::new(),
+            (true, priority) => format!(" (priority: {priority})"),
+        };
         println!(
-            "{}: {}\n    {}/{}",
+            "{}{state}: {}\n    {}",
             repo.name,
             repo.url,
-            base_path.display(),
-            repo.name
+            repo.path(&base_path).display()
         );
+        match (&repo.rev, repo.resolve(&base_path)) {
+            (Some(rev), Ok(Some(revision))) => println!("    rev: {rev} -> {revision}"),
+            (Some(rev), Err(e)) => println!("    rev: {rev} -> unresolved ({e})"),
+            _ => {
+                if let Some(branch) = repo.branch_at(&base_path) {
+                    println!("    branch: {branch}");
+                }
+            }
+        }
     }
     Ok(None)
 }
 
+/// Prints how the boilerplates of the gitignore stand against their repositories, and fails
+/// when any of them changed upstream, so that a CI job stops there.
+fn show_outdated(gixor: &Gixor, opts: cli::OutdatedOpts) -> Result<Option<&Gixor>> {
+    let outdated = gixor.outdated(&opts.dest)?;
+    match opts.format {
+        cli::Format::Text if outdated.boilerplates.is_empty() => {}
+        cli::Format::Text => println!("{outdated}"),
+        cli::Format::Json => {
+            println!(
+                "{}",
+                serde_json::to_string_pretty(&outdated).map_err(Error::Json)?
+            )
+        }
+    }
+    outdated.into_result().map(|_| None)
+}
+
 fn perform_impl(gixor: &mut Gixor, subcmd: cli::GixorCommand, store_flag: bool) -> Result<bool> {
     use cli::GixorCommand::*;
     let r = match subcmd {
         Alias(opts) => perform_alias(gixor, opts),
+        Check(opts) => check_gitignore(gixor, opts),
+        Diff(opts) => show_diff(gixor, opts),
         Dump(opts) => perform_dump(gixor, opts),
         Init => Ok(Some(&*gixor)),
         Entries(opts) => list_entries(gixor, opts),
         List(opts) => list_boilerplates(gixor, opts),
+        Outdated(opts) => show_outdated(gixor, opts),
         Repository(opts) => {
             use cli::RepositoryOpts::*;
             match opts {
                 Add(opts) => add_repository(gixor, opts),
                 List => list_repositories(gixor),
                 Remove(opts) => remove_repository(gixor, opts),
+                Rename(opts) => rename_repository(gixor, opts),
+                SetUrl(opts) => set_repository_url(gixor, opts),
+                Move(opts) => move_repository(gixor, opts),
+                Enable(opts) => enable_repository(gixor, opts, true),
+                Disable(opts) => enable_repository(gixor, opts, false),
                 Update => update_repositories(gixor),
             }
         }
         Root(opts) => show_root(gixor, opts),
         Search(opts) => search_boilerplates(gixor, opts),
+        Sync(opts) => perform_sync(gixor, opts),
         Update => update_repositories(gixor),
+        Upgrade(opts) => perform_upgrade(gixor, opts),
         #[cfg(debug_assertions)]
         CompletionFiles(opts) => gencomp::generate(opts.dest),
     };
@@ -271,8 +430,18 @@ fn perform_impl(gixor: &mut Gixor, subcmd: cli::GixorCommand, store_flag: bool)
     }
 }
 
+/// Returns `true` for the commands that update every repository themselves, which have no use
+/// for the repositories being brought up to date on the way in.
+fn is_update(subcmd: &cli::GixorCommand) -> bool {
+    matches!(
+        subcmd,
+        cli::GixorCommand::Update | cli::GixorCommand::Repository(cli::RepositoryOpts::Update)
+    )
+}
+
 fn perform(opts: cli::CliOpts) -> Result<()> {
-    let (mut gixor, store_flag) = load_gixor(opts.config, opts.no_network)?;
+    let no_network = opts.no_network || is_update(&opts.subcmd);
+    let (mut gixor, store_flag) = load_gixor(opts.config, no_network)?;
     match perform_impl(&mut gixor, opts.subcmd, store_flag) {
         Ok(flag) => {
             if flag {
@@ -345,12 +514,49 @@ mod gencomp {
     }
 }
 
+/// Returns how to get past the boilerplates edited by hand when they stop `subcmd`, as the hints
+/// for [`Error::Modified`] and [`Error::Conflict`]. The library tells only what stopped it, and
+/// the options to get past it are those of the command.
+fn edits_hints(subcmd: &cli::GixorCommand) -> Option<(&'static str, &'static str)> {
+    use cli::GixorCommand::*;
+    const FORCE: &str = "run again with --force to overwrite the edits";
+    match subcmd {
+        Dump(_) | Diff(_) => Some((
+            "run again with --force to overwrite the edits, or with --merge to keep them",
+            FORCE,
+        )),
+        Sync(_) | Upgrade(_) => Some((FORCE, FORCE)),
+        _ => None,
+    }
+}
+
 fn main() {
     let opts = cli::CliOpts::parse();
     init_log(&opts.log);
+    // The first Ctrl-C lets the clones and the fetches under way stop and clean up after
+    // themselves; the second ends gixor there and then.
+    let handler = ctrlc::set_handler(|| {
+        if gixor::gitbridge::is_interrupted() {
+            std::process::exit(130);
+        }
+        gixor::gitbridge::interrupt();
+    });
+    if let Err(e) = handler {
+        log::warn!("failed to handle Ctrl-C: {e}");
+    }
+    let hints = edits_hints(&opts.subcmd);
     if let Err(e) = perform(opts) {
-        println!("Error: {e}");
-        std::process::exit(1);
+        eprintln!("Error: {e}");
+        match (&e, hints) {
+            (Error::Modified(_), Some((modified, _))) => eprintln!("Hint: {modified}"),
+            (Error::Conflict(_), Some((_, conflict))) => eprintln!("Hint: {conflict}"),
+            _ => {}
+        }
+        std::process::exit(if gixor::gitbridge::is_interrupted() {
+            130
+        } else {
+            1
+        });
     }
 }
 
@@ -431,13 +637,18 @@ mod tests {
         let mut gixor = GixorFactory::new_at(&config_path);
 
         let subcmd = GixorCommand::Dump(cli::DumpOpts {
-            dest: "-".into(),
-            no_append: false,
-            clear_prologue: false,
-            clear: false,
+            build: cli::BuildOpts {
+                dest: "-".into(),
+                no_append: false,
+                clear_prologue: false,
+                clear: false,
+                force: false,
+                merge: false,
+                keep_revisions: false,
+                names: vec![],
+            },
             dry_run: false,
             append: false,
-            names: vec![],
         });
 
         let result = perform_impl(&mut gixor, subcmd, false);
@@ -486,7 +697,7 @@ mod tests {
         let mut gixor = GixorFactory::new_at(&config_path);
 
         let subcmd = GixorCommand::Update;
-        // Since we have no repos and prepare(false) returns Ok(()), this should pass
+        // The default repository is updated, which the mirror in the test setup serves
         let result = perform_impl(&mut gixor, subcmd, false);
         assert!(result.is_ok());
     }
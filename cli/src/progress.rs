@@ -0,0 +1,86 @@
+use std::collections::HashMap;
+use std::sync::Mutex;
+
+use gixor::progress::{Event, Observer, Phase};
+use indicatif::{HumanBytes, MultiProgress, ProgressBar, ProgressStyle};
+
+/// Shows a progress bar for each repository being cloned or fetched.
+///
+/// The bars are drawn to the standard error, one line per repository, and each disappears
+/// when its repository is done, so that only the report of the outcomes is left behind.
+pub(crate) struct Bars {
+    bars: MultiProgress,
+    repositories: Mutex<HashMap<String, (ProgressBar, Option<Phase>)>>,
+}
+
+impl Bars {
+    pub(crate) fn new() -> Self {
+        Self {
+            bars: MultiProgress::new(),
+            repositories: Mutex::new(HashMap::new()),
+        }
+    }
+}
+
+impl Observer for Bars {
+    fn on_event(&self, repository: &str, event: &Event) {
+        let mut repositories = self.repositories.lock().unwrap();
+        match event {
+            Event::Started => {
+                let bar = self.bars.add(ProgressBar::new_spinner());
+                bar.set_style(spinner_style());
+                bar.set_prefix(repository.to_string());
+                bar.set_message("connecting");
+                bar.enable_steady_tick(std::time::Duration::from_millis(100));
+                repositories.insert(repository.to_string(), (bar, None));
+            }
+            Event::Progress {
+                phase,
+                done,
+                total,
+                bytes,
+            } => {
+                let Some((bar, shown)) = repositories.get_mut(repository) else {
+                    return;
+                };
+                // The phases overlap a little; a bar going back to one it left looks broken.
+                if shown.is_some_and(|shown| shown > *phase) {
+                    return;
+                }
+                *shown = Some(*phase);
+                let mut message = phase.to_string();
+                match total {
+                    Some(total) => {
+                        bar.set_style(bar_style());
+                        bar.set_position(*done);
+                        bar.set_length(*total);
+                    }
+                    None => {
+                        bar.set_style(spinner_style());
+                        message.push_str(&format!(": {done}"));
+                    }
+                }
+                if let Some(bytes) = bytes {
+                    message.push_str(&format!(", {}", HumanBytes(*bytes)));
+                }
+                bar.set_message(message);
+            }
+            Event::Finished => {
+                if let Some((bar, _)) = repositories.remove(repository) {
+                    bar.finish_and_clear();
+                    self.bars.remove(&bar);
+                }
+            }
+        }
+    }
+}
+
+fn spinner_style() -> ProgressStyle {
+    ProgressStyle::with_template("{spinner} {prefix:.bold} {msg}").expect("the template is valid")
+}
+
+fn bar_style() -> ProgressStyle {
+    ProgressStyle::with_template("{spinner} {prefix:.bold} [{bar:30}] {pos}/{len} {msg}")
+        .expect("the template is valid")
+        .progress_chars("=> ")
+}
@@ -61,6 +61,41 @@ impl Terminal {
     }
 }
 
+/// Renders the changes dumping would make to a gitignore, a heading for each block of a
+/// boilerplate, or text of the user, followed by its hunks. With `color`, the lines are painted
+/// the way `git diff` paints them.
+pub(crate) fn format_changes(changes: &[gixor::Change], color: bool) -> String {
+    let paint = |code: &str, line: &str| {
+        if color {
+            format!("\x1b[{code}m{line}\x1b[m")
+        } else {
+            line.to_string()
+        }
+    };
+    let mut out = String::new();
+    for change in changes {
+        let heading = match &change.name {
+            Some(name) => format!("### {name} ({})", change.kind),
+            None => format!("### your own rules ({})", change.kind),
+        };
+        out.push_str(&paint("1", &heading));
+        out.push('\n');
+        for hunk in &change.hunks {
+            for line in hunk.to_string().lines() {
+                let line = match line.chars().next() {
+                    Some('@') => paint("36", line),
+                    Some('-') => paint("31", line),
+                    Some('+') => paint("32", line),
+                    _ => line.to_string(),
+                };
+                out.push_str(&line);
+                out.push('\n');
+            }
+        }
+    }
+    out
+}
+
 fn padding_list(list: Vec<String>, max_length: usize) -> Vec<String> {
     let mut result = vec![];
     for item in list {
@@ -108,6 +143,22 @@ mod tests {
         assert_eq!(r2[1], "NetBeans");
     }
 
+    #[test]
+    fn test_format_changes() {
+        let check = gixor::Check {
+            path: ".gitignore".into(),
+            actual: "# mine\n".into(),
+            expected: "# mine\n### Generated by Gixor (https://github.com/tamada/gixor) (default/Rust)\n### Rust.gitignore\ntarget/\n".into(),
+        };
+        let changes = check.changes();
+        assert_eq!(
+            format_changes(&changes, false),
+            "### default/Rust (added)\n@@ -1,1 +1,4 @@\n # mine\n+### Generated by Gixor (https://github.com/tamada/gixor) (default/Rust)\n+### Rust.gitignore\n+target/\n"
+        );
+        assert!(format_changes(&changes, true)
+            .starts_with("\x1b[1m### default/Rust (added)\x1b[m\n\x1b[36m@@"));
+    }
+
     #[test]
     pub fn test_format_header() {
         let t = Terminal::new_with(80, " ");
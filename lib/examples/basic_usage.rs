@@ -9,7 +9,7 @@ fn main() -> gixor::Result<()> {
     // 2. Prepare the repositories (clone or update).
     // This requires network access unless the repositories are already cached.
     println!("Preparing repositories...");
-    gixor.prepare(false)?;
+    gixor.prepare(false).into_result()?;
 
     // 3. Define the boilerplates you want to include.
     // You can use simple names like "Rust" or "macOS".
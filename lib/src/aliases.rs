@@ -36,6 +36,26 @@ impl Aliases {
         self.aliases.iter().find(|&a| a.name == name)
     }
 
+    /// Rewrites every boilerplate name of the repository `old` to the repository `new`, for a
+    /// repository that was renamed.
+    pub(crate) fn rename_repository(&mut self, old: &str, new: &str) {
+        let boilerplates = self
+            .aliases
+            .iter_mut()
+            .flat_map(|a| a.boilerplates.iter_mut());
+        for name in boilerplates {
+            if let Some(repository_name) = name.repository_name.as_mut()
+                && repository_name.eq_ignore_ascii_case(old)
+            {
+                log::debug!(
+                    "{repository_name}/{}: renaming to {new}",
+                    name.boilerplate_name
+                );
+                *repository_name = new.to_string();
+            }
+        }
+    }
+
     /// Merge two instances of Aliases, avoiding duplicates by alias name, and returns a new instance.
     pub fn merge(&self, other: &Aliases) -> Self {
         let mut merged = self.aliases.clone();
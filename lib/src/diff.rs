@@ -0,0 +1,346 @@
+//! How two texts differ line by line, for showing how a gitignore differs from what gixor would
+//! write.
+//!
+//! The lines are compared with their line endings, so a line that only changed from `\n` to
+//! `\r\n` shows as changed, which it is as far as the file is concerned. The differences are
+//! found with the algorithm of Myers, the one `git diff` uses by default, and grouped into hunks
+//! with three lines of context, as a unified diff shows them.
+use std::fmt::Display;
+use std::ops::Range;
+
+/// The lines of context around a change.
+const CONTEXT: usize = 3;
+
+/// A line of a [`Hunk`], with its line ending, if it has one.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Line {
+    /// A line both texts have.
+    Context(String),
+    /// A line only the old text has.
+    Removed(String),
+    /// A line only the new text has.
+    Added(String),
+}
+
+/// A run of changed lines with the unchanged lines around them.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Hunk {
+    /// The number of the first line of the hunk in the old text, counted from 1, or of the
+    /// line before it when the hunk has no line of the old text.
+    pub old_start: usize,
+    /// The number of lines of the old text in the hunk.
+    pub old_len: usize,
+    /// The number of the first line of the hunk in the new text, as `old_start` is counted.
+    pub new_start: usize,
+    /// The number of lines of the new text in the hunk.
+    pub new_len: usize,
+    /// The lines of the hunk, in order.
+    pub lines: Vec<Line>,
+}
+
+/// Returns the hunks that turn `old` into `new`, none when they are the same.
+pub fn hunks(old: &str, new: &str) -> Vec<Hunk> {
+    grouped_hunks(old, new, |_| (), |_| ())
+        .into_iter()
+        .map(|(_, hunk)| hunk)
+        .collect()
+}
+
+/// Returns the hunks that turn `old` into `new`, each with the group its changes fall in.
+///
+/// A line removed from `old` falls in the group `old_group` gives its index, counted from 0,
+/// and a line added to `new` in the one `new_group` gives its index. Changes of different
+/// groups never share a hunk, however close they are. The context before the changes of a hunk
+/// may show lines of another group, while the context after them stops at the first such line.
+pub(crate) fn grouped_hunks<G: PartialEq>(
+    old: &str,
+    new: &str,
+    old_group: impl Fn(usize) -> G,
+    new_group: impl Fn(usize) -> G,
+) -> Vec<(G, Hunk)> {
+    let old = old.split_inclusive('\n').collect::<Vec<_>>();
+    let new = new.split_inclusive('\n').collect::<Vec<_>>();
+    let group_of = |edit: &Edit| match *edit {
+        Edit::Keep(i, _) | Edit::Remove(i) => old_group(i),
+        Edit::Add(j) => new_group(j),
+    };
+    let mut edits = edits(&old, &new);
+    // the search removes all it removes from a place before adding anything there; the changes
+    // of each group are gathered, so that a group replacing its lines makes a single hunk
+    let mut from = 0;
+    while let Some(first) = (from..edits.len()).find(|&i| edits[i].is_change()) {
+        let end = (first..edits.len())
+            .find(|&i| !edits[i].is_change())
+            .unwrap_or(edits.len());
+        let groups = edits[first..end].iter().map(group_of).collect::<Vec<_>>();
+        let rank = |k: usize| groups.iter().position(|g| *g == groups[k]).unwrap();
+        let mut order = (0..groups.len()).collect::<Vec<_>>();
+        order.sort_by_key(|&k| rank(k));
+        let run = order.iter().map(|&k| edits[first + k]).collect::<Vec<_>>();
+        edits.splice(first..end, run);
+        from = end;
+    }
+
+    let mut hunks = vec![];
+    let mut from = 0;
+    while let Some(first) = (from..edits.len()).find(|&i| edits[i].is_change()) {
+        let group = group_of(&edits[first]);
+        // changes closer than twice the context share their context, and so their hunk
+        let mut last = first;
+        while let Some(next) = (last + 1..edits.len()).find(|&i| edits[i].is_change()) {
+            if next - last > 2 * CONTEXT || group_of(&edits[next]) != group {
+                break;
+            }
+            last = next;
+        }
+        let start = first.saturating_sub(CONTEXT).max(from);
+        // the context after the changes stops at the lines of another group, which are left
+        // to the hunk of that group
+        let stop = (last + 1..(last + CONTEXT + 1).min(edits.len()))
+            .find(|&i| group_of(&edits[i]) != group)
+            .unwrap_or((last + CONTEXT + 1).min(edits.len()));
+        hunks.push((group, hunk(&edits, start..stop, &old, &new)));
+        from = stop;
+    }
+    hunks
+}
+
+/// Renders how `old` differs from `new` as a unified diff, such as `git diff` prints, naming
+/// them `old_label` and `new_label`. Two texts that are the same render as nothing.
+pub fn unified(old: &str, new: &str, old_label: &str, new_label: &str) -> String {
+    let hunks = hunks(old, new);
+    if hunks.is_empty() {
+        return String::new();
+    }
+    let mut out = format!("--- {old_label}\n+++ {new_label}\n");
+    for hunk in hunks {
+        out.push_str(&hunk.to_string());
+    }
+    out
+}
+
+impl Display for Hunk {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        writeln!(
+            f,
+            "@@ -{},{} +{},{} @@",
+            self.old_start, self.old_len, self.new_start, self.new_len
+        )?;
+        for line in &self.lines {
+            let (mark, text) = match line {
+                Line::Context(text) => (' ', text),
+                Line::Removed(text) => ('-', text),
+                Line::Added(text) => ('+', text),
+            };
+            write!(f, "{mark}{text}")?;
+            if !text.ends_with('\n') {
+                write!(f, "\n\\ No newline at end of file\n")?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// A step of turning the old text into the new one, with the index of the line it is about.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Edit {
+    /// The line of the old text at the first index is the line of the new one at the second.
+    Keep(usize, usize),
+    /// The line of the old text at the index is removed.
+    Remove(usize),
+    /// The line of the new text at the index is added.
+    Add(usize),
+}
+
+impl Edit {
+    fn is_change(&self) -> bool {
+        !matches!(self, Edit::Keep(..))
+    }
+}
+
+fn hunk(edits: &[Edit], range: Range<usize>, old: &[&str], new: &[&str]) -> Hunk {
+    // the edits before the hunk tell where it starts in both texts, whichever they are about
+    let old_at = edits[..range.start]
+        .iter()
+        .filter(|edit| !matches!(edit, Edit::Add(_)))
+        .count();
+    let new_at = edits[..range.start]
+        .iter()
+        .filter(|edit| !matches!(edit, Edit::Remove(_)))
+        .count();
+    let edits = &edits[range];
+    let mut hunk = Hunk {
+        old_start: old_at,
+        old_len: 0,
+        new_start: new_at,
+        new_len: 0,
+        lines: Vec::with_capacity(edits.len()),
+    };
+    for edit in edits {
+        hunk.lines.push(match *edit {
+            Edit::Keep(i, _) => {
+                hunk.old_len += 1;
+                hunk.new_len += 1;
+                Line::Context(old[i].to_string())
+            }
+            Edit::Remove(i) => {
+                hunk.old_len += 1;
+                Line::Removed(old[i].to_string())
+            }
+            Edit::Add(j) => {
+                hunk.new_len += 1;
+                Line::Added(new[j].to_string())
+            }
+        });
+    }
+    if hunk.old_len > 0 {
+        hunk.old_start += 1;
+    }
+    if hunk.new_len > 0 {
+        hunk.new_start += 1;
+    }
+    hunk
+}
+
+/// Finds the shortest way of turning `old` into `new`, after Myers, "An O(ND) Difference
+/// Algorithm and Its Variations".
+///
+/// The lines the two share at the start and at the end are kept before the search begins, since
+/// a gitignore dumped again mostly changes somewhere in the middle, if at all.
+fn edits(old: &[&str], new: &[&str]) -> Vec<Edit> {
+    let prefix = old.iter().zip(new).take_while(|(a, b)| a == b).count();
+    let suffix = old[prefix..]
+        .iter()
+        .rev()
+        .zip(new[prefix..].iter().rev())
+        .take_while(|(a, b)| a == b)
+        .count();
+    let (a, b) = (
+        &old[prefix..old.len() - suffix],
+        &new[prefix..new.len() - suffix],
+    );
+
+    let mut edits = (0..prefix).map(|i| Edit::Keep(i, i)).collect::<Vec<_>>();
+    edits.extend(shortest(a, b).into_iter().map(|edit| match edit {
+        Edit::Keep(i, j) => Edit::Keep(i + prefix, j + prefix),
+        Edit::Remove(i) => Edit::Remove(i + prefix),
+        Edit::Add(j) => Edit::Add(j + prefix),
+    }));
+    let (old_end, new_end) = (old.len() - suffix, new.len() - suffix);
+    edits.extend((0..suffix).map(|i| Edit::Keep(old_end + i, new_end + i)));
+    edits
+}
+
+fn shortest(a: &[&str], b: &[&str]) -> Vec<Edit> {
+    let (n, m) = (a.len() as isize, b.len() as isize);
+    let max = (n + m) as usize;
+    let offset = max as isize;
+    // v[k] is how far along `a` the furthest path on the diagonal k has got
+    let mut v = vec![0isize; 2 * max + 2];
+    let mut trace = vec![];
+    'search: for d in 0..=max as isize {
+        trace.push(v.clone());
+        for k in (-d..=d).step_by(2) {
+            let at = (k + offset) as usize;
+            let mut x = if k == -d || (k != d && v[at - 1] < v[at + 1]) {
+                v[at + 1]
+            } else {
+                v[at - 1] + 1
+            };
+            let mut y = x - k;
+            while x < n && y < m && a[x as usize] == b[y as usize] {
+                x += 1;
+                y += 1;
+            }
+            v[at] = x;
+            if x >= n && y >= m {
+                break 'search;
+            }
+        }
+    }
+
+    // walk the furthest paths back from the end to the start
+    let mut edits = vec![];
+    let (mut x, mut y) = (n, m);
+    for (d, v) in trace.iter().enumerate().rev() {
+        let d = d as isize;
+        let k = x - y;
+        let at = (k + offset) as usize;
+        let previous = if k == -d || (k != d && v[at - 1] < v[at + 1]) {
+            k + 1
+        } else {
+            k - 1
+        };
+        let px = v[(previous + offset) as usize];
+        let py = px - previous;
+        while x > px && y > py {
+            x -= 1;
+            y -= 1;
+            edits.push(Edit::Keep(x as usize, y as usize));
+        }
+        if d > 0 {
+            if x == px {
+                edits.push(Edit::Add(py as usize));
+            } else {
+                edits.push(Edit::Remove(px as usize));
+            }
+        }
+        (x, y) = (px, py);
+    }
+    edits.reverse();
+    edits
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_hunks() {
+        let old = "1\n2\n3\n4\n5\n6\n7\n8\n9\n10\n11\n12\n";
+        let new = "1\n2\nthree\n4\n5\n6\n7\n8\n9\n10\n11\n12\n13\n";
+        let hunks = hunks(old, new);
+        assert_eq!(hunks.len(), 2);
+        assert_eq!(
+            hunks[0].to_string(),
+            "@@ -1,6 +1,6 @@\n 1\n 2\n-3\n+three\n 4\n 5\n 6\n"
+        );
+        assert_eq!(
+            hunks[1].to_string(),
+            "@@ -10,3 +10,4 @@\n 10\n 11\n 12\n+13\n"
+        );
+
+        assert!(super::hunks(old, old).is_empty());
+    }
+
+    #[test]
+    fn test_grouped_hunks() {
+        let old = "a\nb\nc\n";
+        let new = "a\nB\nc\nC\n";
+        // the lines after the second one are of another group
+        let hunks = grouped_hunks(old, new, |i| i < 2, |j| j < 2);
+        assert_eq!(hunks.len(), 2);
+        assert!(hunks[0].0);
+        assert_eq!(hunks[0].1.to_string(), "@@ -1,2 +1,2 @@\n a\n-b\n+B\n");
+        assert!(!hunks[1].0);
+        assert_eq!(hunks[1].1.to_string(), "@@ -3,1 +3,2 @@\n c\n+C\n");
+    }
+
+    #[test]
+    fn test_unified() {
+        assert_eq!(unified("a\n", "a\n", "old", "new"), "");
+        assert_eq!(
+            unified("", "a\nb", "old", "new"),
+            "--- old\n+++ new\n@@ -0,0 +1,2 @@\n+a\n+b\n\\ No newline at end of file\n"
+        );
+        assert_eq!(
+            unified("a\nb\nc\n", "a\nc\n", "old", "new"),
+            "--- old\n+++ new\n@@ -1,3 +1,2 @@\n a\n-b\n c\n"
+        );
+        // the line endings are part of the lines
+        assert_eq!(
+            unified("a\r\n", "a\n", "old", "new"),
+            "--- old\n+++ new\n@@ -1,1 +1,1 @@\n-a\r\n+a\n"
+        );
+    }
+}
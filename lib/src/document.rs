@@ -0,0 +1,650 @@
+//! The structure of a `.gitignore` that gixor writes into.
+//!
+//! A `.gitignore` dumped by gixor reads as follows.
+//!
+//! ```text
+//! # rules of my own           <- the prologue
+//! *.local
+//! ### Generated by Gixor (https://github.com/tamada/gixor) (default/Rust)   <- a block
+//! ### https://raw.github.com/github/gitignore/0B2F.../Rust.gitignore
+//! ### Checksum: sha256:5e8c...
+//! target/
+//! ### End of Gixor (default/Rust)
+//!
+//! # more rules of my own      <- a section of the user
+//! ### Generated by Gixor (https://github.com/tamada/gixor) (default/Global/macOS)
+//! ...
+//! ```
+//!
+//! [`GitignoreDocument::parse`] takes a `.gitignore` apart into the prologue, the text before the
+//! first block, and the sections after it, and [`GitignoreDocument`] writes the very same bytes
+//! back, line endings and all. Everything reading or rewriting a `.gitignore` goes through it,
+//! so that there is one idea of where a block starts and ends.
+//!
+//! A block starts at its `### Generated by Gixor` header, followed by the line of its source: the
+//! permalink of the boilerplate, or its path when the host has no permalink. A `.gitignore`
+//! written before the header existed has the line of the source alone, which still starts a
+//! block when it names a `.gitignore` file.
+//!
+//! The checksum is that of the boilerplate as gixor wrote it, so a block whose content no
+//! longer matches it was edited by hand since, and [`EditPolicy`] decides what the next dump
+//! makes of the edit. A block written before the checksum existed cannot tell, and is taken as
+//! it stands. A block the edits were merged into records the checksum of the merge on a line of
+//! its own, `### Merged: sha256:...`, below that of the boilerplate, so that it counts as edited
+//! only once edited again.
+//!
+//! A block ends at its `### End of Gixor` marker, together with the blank line gixor puts after
+//! it, and the text from there up to the next block is the user's. A block written before the
+//! markers existed has none, so it runs up to the next block, and text the user wrote after it
+//! cannot be told apart from the boilerplate.
+use std::fmt::Display;
+
+use sha2::{Digest, Sha256};
+
+use crate::Name;
+
+const GENERATED: &str = "### Generated by Gixor";
+
+/// The start of the line that records the checksum of a block.
+pub(crate) const CHECKSUM: &str = "### Checksum: ";
+
+/// The start of the line that records the checksum of a block the edits were merged into.
+pub(crate) const MERGED: &str = "### Merged: ";
+
+/// The start of the line that ends a block.
+pub(crate) const END: &str = "### End of Gixor";
+
+/// A `.gitignore` taken apart, which displays as the exact text it was parsed from.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct GitignoreDocument {
+    /// The text before the first block, the rules the user wrote, with its line endings.
+    pub prologue: String,
+    /// The blocks and the text of the user after the prologue, in the order of the file.
+    pub sections: Vec<Section>,
+}
+
+/// A part of a [`GitignoreDocument`] after the prologue.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Section {
+    /// A boilerplate written by gixor.
+    Block(Block),
+    /// Text of the user's own.
+    User(String),
+}
+
+/// A boilerplate as gixor wrote it into a `.gitignore`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Block {
+    /// The repository the boilerplate came from, as the header names it, or `None` for a block
+    /// written before the header named it.
+    pub repository: Option<String>,
+    /// The name of the boilerplate, its path without the extension, such as `Global/macOS`.
+    pub name: String,
+    /// The path of the boilerplate from the repository root, such as `Global/macOS.gitignore`,
+    /// when the line of the source tells it.
+    pub path: Option<String>,
+    /// The commit the boilerplate was dumped at, in lowercase hex, when the permalink names it.
+    pub commit: Option<String>,
+    /// The permalink of the boilerplate, when the host has one.
+    pub permalink: Option<String>,
+    /// The checksum of the content gixor wrote, such as `sha256:5e8c...`, or `None` for a block
+    /// written before blocks had one.
+    pub checksum: Option<String>,
+    /// The lines gixor wrote above the boilerplate, exactly as they are in the file.
+    pub header: String,
+    /// The content of the boilerplate, exactly as it is in the file. A block with no end
+    /// marker has whatever follows it up to the next block, too.
+    pub body: String,
+    /// The end marker and the blank line after it, exactly as they are in the file, or empty
+    /// for a block written before blocks had one.
+    pub footer: String,
+}
+
+/// What a dump makes of a block that was edited by hand since gixor wrote it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum EditPolicy {
+    /// The dump fails with [`crate::Error::Modified`], naming the edited blocks, and nothing
+    /// is written.
+    #[default]
+    Refuse,
+    /// The edits are overwritten with the boilerplate, as if they had never been made.
+    Overwrite,
+    /// The edits are merged into the new content of the boilerplate, with the content at the
+    /// commit the block records as the common ancestor, read from the history of the clone.
+    /// A conflict fails the dump with [`crate::Error::Conflict`].
+    ///
+    /// The merged block records the checksum of the merge beside that of the boilerplate, so
+    /// the dumps after take it as it was merged: they merge its edits again into the content
+    /// of the boilerplate, unless it was edited once more, and [`EditPolicy::Overwrite`] drops
+    /// them.
+    Merge,
+}
+
+/// Which revision of a boilerplate a dump writes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum RevisionPolicy {
+    /// The boilerplate as the clone has it checked out.
+    #[default]
+    Latest,
+    /// The boilerplate at the path and the commit its block of the destination records, read
+    /// from the history of the clone, so that the blocks stay as they were dumped however far
+    /// upstream moved since, even a boilerplate removed upstream. A boilerplate with no block
+    /// recording a commit is the latest, and one named with a revision, such as `Rust@v2.0`,
+    /// is at that revision.
+    Recorded,
+}
+
+impl GitignoreDocument {
+    /// Parses the content of a `.gitignore`. Any text is a document: one without a block is
+    /// all prologue.
+    pub fn parse(text: &str) -> Self {
+        let mut document = GitignoreDocument::default();
+        let mut lines = text.split_inclusive('\n').peekable();
+        while let Some(line) = lines.next_if(|line| !starts_block(line)) {
+            document.prologue.push_str(line);
+        }
+        while let Some(first) = lines.next() {
+            let mut header = first.to_string();
+            let generated = first.starts_with(GENERATED);
+            let source = if generated {
+                lines
+                    .next_if(|line| line.starts_with("### ") && !line.starts_with(GENERATED))
+                    .inspect(|line| header.push_str(line))
+            } else {
+                Some(first)
+            };
+            let checksum = if generated {
+                lines
+                    .next_if(|line| line.starts_with(CHECKSUM))
+                    .inspect(|line| header.push_str(line))
+                    .map(|line| trim_line_ending(&line[CHECKSUM.len()..]).to_string())
+            } else {
+                None
+            };
+            if checksum.is_some()
+                && let Some(line) = lines.next_if(|line| line.starts_with(MERGED))
+            {
+                header.push_str(line);
+            }
+            let mut body = String::new();
+            while let Some(line) = lines.next_if(|line| !starts_block(line) && !is_end(line)) {
+                body.push_str(line);
+            }
+            let mut footer = String::new();
+            if let Some(end) = lines.next_if(|line| is_end(line)) {
+                footer.push_str(end);
+                if let Some(blank) = lines.next_if(|line| trim_line_ending(line).is_empty()) {
+                    footer.push_str(blank);
+                }
+            }
+            let mut block = Block::from_source(source.map(trim_line_ending));
+            if generated && let Some(name) = generated_name(trim_line_ending(first)) {
+                block.repository = name.repository_name;
+                block.name = name.boilerplate_name;
+            }
+            block.checksum = checksum;
+            block.header = header;
+            block.body = body;
+            block.footer = footer;
+            document.sections.push(Section::Block(block));
+
+            let mut user = String::new();
+            while let Some(line) = lines.next_if(|line| !starts_block(line)) {
+                user.push_str(line);
+            }
+            if !user.is_empty() {
+                document.sections.push(Section::User(user));
+            }
+        }
+        document
+    }
+
+    /// Replaces the blocks of this document with `fresh` ones, keeping the text of the user
+    /// where it is.
+    ///
+    /// A block stays in its place, rewritten, when one of `fresh` is for it, as `is_for` tells,
+    /// and is dropped otherwise. The rest of `fresh` goes after the last block that stayed, so
+    /// that it comes before the text the user wrote after all the blocks; in place of the first
+    /// block dropped when none stayed; and at the end when the document has no block at all.
+    pub(crate) fn rewrite<T, F>(self, fresh: Vec<(T, Block)>, is_for: F) -> Self
+    where
+        F: Fn(&T, &Block) -> bool,
+    {
+        let mut fresh = fresh.into_iter().map(Some).collect::<Vec<_>>();
+        let mut sections = vec![];
+        let mut insert_at = None;
+        let mut dropped_at = None;
+        for section in self.sections {
+            match section {
+                Section::Block(block) => {
+                    let found = fresh.iter_mut().find(|candidate| {
+                        candidate
+                            .as_ref()
+                            .is_some_and(|(key, _)| is_for(key, &block))
+                    });
+                    match found.and_then(Option::take) {
+                        Some((_, rewritten)) => {
+                            sections.push(Section::Block(rewritten));
+                            insert_at = Some(sections.len());
+                        }
+                        None => {
+                            log::info!("{}: dropped from the gitignore", block.name());
+                            dropped_at.get_or_insert(sections.len());
+                        }
+                    }
+                }
+                user => sections.push(user),
+            }
+        }
+        let at = insert_at.or(dropped_at).unwrap_or(sections.len());
+        let rest = fresh
+            .into_iter()
+            .flatten()
+            .map(|(_, block)| Section::Block(block));
+        sections.splice(at..at, rest);
+        // the text of the user may end without a newline where a block now follows it, and a
+        // file gixor writes ends with one anyway
+        let mut prologue = self.prologue;
+        end_line(&mut prologue);
+        for section in &mut sections {
+            if let Section::User(text) = section {
+                end_line(text);
+            }
+        }
+        GitignoreDocument { prologue, sections }
+    }
+
+    /// Forgets the edits by hand since gixor wrote the blocks, so that a build rewrites such a
+    /// block with the boilerplate, where it would refuse or merge them. A block whose edits
+    /// were merged and left as they were keeps them.
+    pub(crate) fn forget_edits(mut self) -> Self {
+        for section in &mut self.sections {
+            if let Section::Block(block) = section
+                && block.is_modified()
+            {
+                block.header = block
+                    .header
+                    .split_inclusive('\n')
+                    .filter(|line| !line.starts_with(MERGED))
+                    .collect();
+                block.checksum = None;
+            }
+        }
+        self
+    }
+
+    /// Returns the boilerplate each line of the document is in, in the order of the lines, or
+    /// `None` for a line of the user's own. The lines end at `\n`, as [`crate::diff`] splits
+    /// them.
+    pub(crate) fn owners(&self) -> Vec<Option<Name>> {
+        let lines = |text: &str| text.split_inclusive('\n').count();
+        let mut owners = vec![None; lines(&self.prologue)];
+        for section in &self.sections {
+            match section {
+                Section::Block(block) => {
+                    let text = format!("{}{}{}", block.header, block.body, block.footer);
+                    owners.extend(std::iter::repeat_n(Some(block.name()), lines(&text)));
+                }
+                Section::User(text) => owners.extend(std::iter::repeat_n(None, lines(text))),
+            }
+        }
+        owners
+    }
+
+    /// Iterates the blocks of the document.
+    pub fn blocks(&self) -> impl Iterator<Item = &Block> {
+        self.sections.iter().filter_map(|section| match section {
+            Section::Block(block) => Some(block),
+            Section::User(_) => None,
+        })
+    }
+}
+
+impl Display for GitignoreDocument {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.prologue)?;
+        for section in &self.sections {
+            match section {
+                Section::Block(block) => {
+                    write!(f, "{}{}{}", block.header, block.body, block.footer)?
+                }
+                Section::User(text) => write!(f, "{text}")?,
+            }
+        }
+        Ok(())
+    }
+}
+
+impl Block {
+    /// Takes the block [`crate::repos::Boilerplate::dump`] renders, followed by the blank line
+    /// that sets it apart from whatever comes next.
+    pub(crate) fn rendered(text: &str) -> Option<Self> {
+        let document = GitignoreDocument::parse(&format!("{text}\n"));
+        document
+            .sections
+            .into_iter()
+            .find_map(|section| match section {
+                Section::Block(block) => Some(block),
+                Section::User(_) => None,
+            })
+    }
+
+    /// Returns the name of the boilerplate as [Name], qualified with the repository when the
+    /// header tells it.
+    pub fn name(&self) -> Name {
+        match &self.repository {
+            Some(repository) => Name::new(repository.as_str(), self.name.as_str()),
+            None => Name::parse(&self.name),
+        }
+    }
+
+    /// Returns `true` if the content of the block differs from the checksum it records, that
+    /// of the merge for a merged block, which is to say that it was edited by hand since gixor
+    /// wrote it.
+    pub fn is_modified(&self) -> bool {
+        self.merged()
+            .or(self.checksum.as_deref())
+            .is_some_and(|recorded| recorded != checksum(&self.body))
+    }
+
+    /// Returns the checksum of the content [`EditPolicy::Merge`] wrote, when the edits by hand
+    /// were merged into the block, or `None` for a block as the boilerplate has it.
+    pub fn merged(&self) -> Option<&str> {
+        self.header
+            .lines()
+            .find_map(|line| line.strip_prefix(MERGED))
+            .map(trim_line_ending)
+    }
+
+    /// Replaces the content of the block with `body`, the edits merged into it, and records the
+    /// checksum of the merge in the header. A merge that comes out as the boilerplate itself
+    /// leaves the block as it is.
+    pub(crate) fn merge_into(&mut self, body: String) {
+        if body == self.body {
+            return;
+        }
+        let merged = checksum(&body);
+        let mut header = String::new();
+        for line in self.header.split_inclusive('\n') {
+            if !line.starts_with(MERGED) {
+                header.push_str(line);
+            }
+            if line.starts_with(CHECKSUM) {
+                header.push_str(&format!("{MERGED}{merged}\n"));
+            }
+        }
+        self.header = header;
+        self.body = body;
+    }
+
+    /// Reads what the line of the source tells: the permalink of the boilerplate, or its path
+    /// when the host has no permalink.
+    ///
+    /// Every permalink names the commit right before the path, so what follows the commit is
+    /// the path; a URL with no commit in it gives its last segment, the stem, as ever.
+    fn from_source(source: Option<&str>) -> Self {
+        let source = source.map(|line| line.strip_prefix("### ").unwrap_or(line));
+        let (permalink, path) = match source {
+            Some(url) if url.contains("://") => (Some(url.to_string()), path_of(url)),
+            Some(path) => (None, Some(path.to_string())),
+            None => (None, None),
+        };
+        let name = match (&path, &permalink) {
+            (Some(path), _) => path.strip_suffix(".gitignore").unwrap_or(path),
+            (None, Some(url)) => url.rsplit('/').next().unwrap_or(url),
+            (None, None) => "",
+        };
+        Block {
+            repository: None,
+            name: name.strip_suffix(".gitignore").unwrap_or(name).to_string(),
+            commit: permalink.as_deref().and_then(commit_of),
+            path,
+            permalink,
+            checksum: None,
+            header: String::new(),
+            body: String::new(),
+            footer: String::new(),
+        }
+    }
+}
+
+/// Returns the checksum of the content of a block, as the header records it.
+///
+/// Line endings are taken as `\n`, so that a checkout converting them to `\r\n` does not make
+/// every block look edited.
+pub(crate) fn checksum(content: &str) -> String {
+    let digest = Sha256::digest(content.replace("\r\n", "\n"));
+    format!("sha256:{}", hex::encode(digest))
+}
+
+/// Returns `true` if `line` is the first line of a block.
+fn starts_block(line: &str) -> bool {
+    let line = trim_line_ending(line);
+    line.starts_with(GENERATED) || (line.starts_with("### ") && line.ends_with(".gitignore"))
+}
+
+fn end_line(text: &mut String) {
+    if !text.is_empty() && !text.ends_with('\n') {
+        text.push('\n');
+    }
+}
+
+/// Returns `true` if `line` is the end marker of a block.
+fn is_end(line: &str) -> bool {
+    line.starts_with(END)
+}
+
+fn trim_line_ending(line: &str) -> &str {
+    line.trim_end_matches(['\n', '\r'])
+}
+
+/// Takes the name from the header `### Generated by Gixor (https://github.com/tamada/gixor) (team/Rust)`.
+fn generated_name(line: &str) -> Option<Name> {
+    let (_, name) = line
+        .strip_prefix(GENERATED)?
+        .strip_suffix(')')?
+        .rsplit_once('(')?;
+    (!name.is_empty()).then(|| Name::parse(name))
+}
+
+/// Takes the path of the boilerplate from its permalink: what follows the commit, or the
+/// `path` query of Azure DevOps.
+fn path_of(url: &str) -> Option<String> {
+    if let Some((_, query)) = url.split_once("path=/") {
+        return query.split('&').next().map(String::from);
+    }
+    let segments = url.split('/').collect::<Vec<_>>();
+    let commit = segments.iter().rposition(|s| is_commit(s))?;
+    Some(segments[commit + 1..].join("/")).filter(|path| !path.is_empty())
+}
+
+/// Takes the commit from the permalink, if it names one.
+fn commit_of(url: &str) -> Option<String> {
+    url.split(['/', '=', '&', '?'])
+        .find(|s| is_commit(s))
+        .map(str::to_lowercase)
+}
+
+/// Returns `true` if `s` looks like a commit of SHA-1 or SHA-256.
+fn is_commit(s: &str) -> bool {
+    matches!(s.len(), 40 | 64) && s.chars().all(|c| c.is_ascii_hexdigit())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const DUMPED: &str = "# mine\r\n*.local\n### notes of my own\n\
+        ### Generated by Gixor (https://github.com/tamada/gixor) (team/Rust)\n\
+        ### https://raw.github.com/tamada/gitignore/0B2F17FDA63970B5B3F2E9C5310CEFA23D2BB198/Rust.gitignore\n\
+        target\n\
+        \n\
+        ### Generated by Gixor (https://github.com/tamada/gixor) (corp/Global/macOS)\n\
+        ### Global/macOS.gitignore\n\
+        .DS_Store\n\
+        \n\
+        ### https://raw.github.com/github/gitignore/4488915EEC0B3A45B5C63EAD28F286819C0917DE/Python.gitignore\n\
+        __pycache__/";
+
+    #[test]
+    fn test_parse() {
+        let document = GitignoreDocument::parse(DUMPED);
+        assert_eq!(
+            document.prologue,
+            "# mine\r\n*.local\n### notes of my own\n"
+        );
+        let blocks = document.blocks().collect::<Vec<_>>();
+        assert_eq!(blocks.len(), 3);
+
+        assert_eq!(blocks[0].name().to_string(), "team/Rust");
+        assert_eq!(blocks[0].path.as_deref(), Some("Rust.gitignore"));
+        assert_eq!(
+            blocks[0].commit.as_deref(),
+            Some("0b2f17fda63970b5b3f2e9c5310cefa23d2bb198")
+        );
+        assert!(blocks[0].permalink.is_some());
+        assert_eq!(blocks[0].body, "target\n\n");
+
+        assert_eq!(blocks[1].name().to_string(), "corp/Global/macOS");
+        assert_eq!(blocks[1].path.as_deref(), Some("Global/macOS.gitignore"));
+        assert_eq!(blocks[1].commit, None);
+        assert_eq!(blocks[1].permalink, None);
+
+        // written before the header named the repository
+        assert_eq!(blocks[2].repository, None);
+        assert_eq!(blocks[2].name().to_string(), "Python");
+        assert_eq!(blocks[2].body, "__pycache__/");
+    }
+
+    #[test]
+    fn test_serializes_back_byte_for_byte() {
+        for text in [
+            DUMPED,
+            "",
+            "\n",
+            "no newline at the end",
+            "# mine\r\n### Rust.gitignore\r\ntarget\r\n",
+            "### Generated by Gixor (https://github.com/tamada/gixor) (default/Rust)\n",
+        ] {
+            assert_eq!(GitignoreDocument::parse(text).to_string(), text, "{text:?}");
+        }
+    }
+
+    #[test]
+    fn test_user_text_stays_in_place() {
+        let text = "# mine\n\
+            ### Generated by Gixor (https://github.com/tamada/gixor) (default/Rust)\n\
+            ### Rust.gitignore\n\
+            target\n\
+            ### End of Gixor (default/Rust)\n\
+            \n\
+            # between\n\
+            ### Generated by Gixor (https://github.com/tamada/gixor) (default/Go)\n\
+            ### Go.gitignore\n\
+            go.work\n\
+            ### End of Gixor (default/Go)\n\
+            \n\
+            # after all\n\
+            *.bak";
+        let document = GitignoreDocument::parse(text);
+        assert_eq!(document.to_string(), text);
+        let kinds = document
+            .sections
+            .iter()
+            .map(|section| match section {
+                Section::Block(block) => block.name().to_string(),
+                Section::User(text) => text.clone(),
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(
+            kinds,
+            vec![
+                "default/Rust",
+                "# between\n",
+                "default/Go",
+                "# after all\n*.bak"
+            ]
+        );
+
+        let fresh = |name: &str| {
+            let block = Block::rendered(&format!(
+                "### Generated by Gixor (https://github.com/tamada/gixor) (default/{name})\n### {name}.gitignore\nnew\n{END} (default/{name})\n"
+            ))
+            .unwrap();
+            (name.to_string(), block)
+        };
+        // Rust is rewritten in place, Go is dropped, and Python goes after the last block
+        let rewritten = document.rewrite(vec![fresh("Python"), fresh("Rust")], |name, block| {
+            block.name().boilerplate_name == *name
+        });
+        assert_eq!(
+            rewritten.to_string(),
+            "# mine\n\
+            ### Generated by Gixor (https://github.com/tamada/gixor) (default/Rust)\n\
+            ### Rust.gitignore\n\
+            new\n\
+            ### End of Gixor (default/Rust)\n\
+            \n\
+            ### Generated by Gixor (https://github.com/tamada/gixor) (default/Python)\n\
+            ### Python.gitignore\n\
+            new\n\
+            ### End of Gixor (default/Python)\n\
+            \n\
+            # between\n\
+            # after all\n\
+            *.bak\n"
+        );
+    }
+
+    #[test]
+    fn test_is_modified() {
+        let block = |checksum: &str, body: &str| {
+            let text = format!(
+                "### Generated by Gixor (https://github.com/tamada/gixor) (default/Rust)\n\
+                ### Rust.gitignore\n\
+                {checksum}{body}{END} (default/Rust)\n"
+            );
+            let document = GitignoreDocument::parse(&text);
+            assert_eq!(document.to_string(), text);
+            document.blocks().next().unwrap().clone()
+        };
+        let recorded = format!("{CHECKSUM}{}\n", checksum("target\n"));
+
+        let untouched = block(&recorded, "target\n");
+        assert_eq!(untouched.checksum, Some(checksum("target\n")));
+        assert_eq!(untouched.body, "target\n");
+        assert!(!untouched.is_modified());
+        assert!(block(&recorded, "target/\n").is_modified());
+        // a checkout converting the line endings edits nothing
+        assert!(!block(&recorded, "target\r\n").is_modified());
+        // written before blocks had a checksum, so there is no telling
+        assert!(!block("", "target/\n").is_modified());
+
+        let mut rendered = untouched.clone();
+        rendered.merge_into("target\n!target/keep\n".into());
+        let merged = format!("{recorded}{MERGED}{}\n", checksum("target\n!target/keep\n"));
+        let reparsed = block(&merged, "target\n!target/keep\n");
+        assert_eq!(reparsed, rendered);
+        assert_eq!(reparsed.checksum, untouched.checksum);
+        assert!(!reparsed.is_modified());
+        assert!(block(&merged, "target\n").is_modified());
+    }
+
+    #[test]
+    fn test_path_of_permalinks() {
+        let cases = [
+            (
+                "https://gitlab.com/group/sub/gitignore/-/raw/0B2F17FDA63970B5B3F2E9C5310CEFA23D2BB198/Global/macOS.gitignore",
+                Some("Global/macOS.gitignore"),
+            ),
+            (
+                "https://dev.azure.com/org/project/_apis/git/repositories/gitignore/items?path=/Global/macOS.gitignore&versionDescriptor.version=0b2f17fda63970b5b3f2e9c5310cefa23d2bb198&versionDescriptor.versionType=commit",
+                Some("Global/macOS.gitignore"),
+            ),
+            ("https://example.com/raw/main/Global/macOS.gitignore", None),
+        ];
+        for (url, expected) in cases {
+            assert_eq!(path_of(url).as_deref(), expected, "{url}");
+        }
+    }
+}
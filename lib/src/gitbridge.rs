@@ -2,9 +2,10 @@
 //! Provides functions to interact with Git repositories
 //! using either the `gix` crate or system Git commands
 //! based on feature flags.
-use crate::repos::Boilerplate;
-use crate::Result;
-use std::path::Path;
+use crate::progress::Reporter;
+use crate::repos::{Boilerplate, Commit, Revision};
+use crate::{Error, Result};
+use std::path::{Path, PathBuf};
 
 #[cfg(feature = "usegix")]
 #[path = "gitbridge/gix.rs"]
@@ -14,17 +15,543 @@ mod gitctrl;
 #[path = "gitbridge/systemgit.rs"]
 mod gitctrl;
 
-/// Returns the latest commit hash (as bytes) of the repository at the given path.
-pub fn pull(repository_path: &Path, remote_name: &str, branch_name: &str) -> Result<()> {
-    gitctrl::pull(repository_path, remote_name, branch_name)
+/// Fetches `remote_name` and brings the branch `branch_name` of the repository at the given
+/// path up to date with it, checking the branch out first if another one is checked out.
+/// How the fetch goes is reported to `progress`.
+pub fn pull(
+    repository_path: &Path,
+    remote_name: &str,
+    branch_name: &str,
+    progress: &Reporter,
+) -> Result<()> {
+    gitctrl::pull(repository_path, remote_name, branch_name, progress)
 }
 
-/// Clones the repository from the given URL to the specified destination path.
-pub fn clone<S: AsRef<str>, P: AsRef<Path>>(url: S, dest_path: P) -> Result<()> {
-    gitctrl::clone(url, dest_path)
+/// Clones the repository from the given URL to the specified destination path, naming the
+/// remote `remote_name`.
+///
+/// `branch_name` is the branch to check out; `None` checks out the one the remote `HEAD`
+/// points at. Either way, the name of the branch checked out is returned. How the clone goes
+/// is reported to `progress`.
+///
+/// The clone is made in a sibling of `dest_path` and renamed to it only once it is complete,
+/// and removed when it is not. A directory at `dest_path` is therefore always a whole clone,
+/// which is what everything else takes it for, whether the clone failed, was interrupted, or
+/// gixor was killed outright.
+pub fn clone<S: AsRef<str>, P: AsRef<Path>>(
+    url: S,
+    dest_path: P,
+    remote_name: &str,
+    branch_name: Option<&str>,
+    progress: &Reporter,
+) -> Result<String> {
+    let dest_path = dest_path.as_ref();
+    let partial = partial_path(dest_path);
+    // left behind by a clone that was killed before it could clean up
+    remove_dir(&partial)?;
+    if let Some(parent) = dest_path.parent() {
+        std::fs::create_dir_all(parent).map_err(Error::IO)?;
+    }
+    let result =
+        gitctrl::clone(url, &partial, remote_name, branch_name, progress).and_then(|branch| {
+            // An empty directory in the way is taken over; anything else makes the rename fail.
+            let _ = std::fs::remove_dir(dest_path);
+            std::fs::rename(&partial, dest_path)
+                .map(|_| branch)
+                .map_err(Error::IO)
+        });
+    if result.is_err()
+        && let Err(e) = remove_dir(&partial)
+    {
+        log::warn!("{}: failed to remove: {e}", partial.display());
+    }
+    result
+}
+
+/// Returns the sibling of `dest_path` that [`clone`] clones into.
+fn partial_path(dest_path: &Path) -> PathBuf {
+    let name = dest_path.file_name().unwrap_or_default().to_string_lossy();
+    dest_path.with_file_name(format!(".{name}.partial"))
+}
+
+/// Removes the directory at `path` with everything in it, if there is one.
+pub(crate) fn remove_dir(path: &Path) -> Result<()> {
+    match std::fs::remove_dir_all(path) {
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(()),
+        Err(e) => Err(Error::IO(e)),
+        Ok(_) => Ok(()),
+    }
+}
+
+/// Asks the Git operations under way to stop where they are, and the ones to come not to
+/// start, which is what a Ctrl-C is to mean. They fail with an error, cleaning up after
+/// themselves as they would after any other failure.
+pub fn interrupt() {
+    gitctrl::interrupt()
+}
+
+/// Returns `true` once [`interrupt`] was called.
+pub fn is_interrupted() -> bool {
+    gitctrl::is_interrupted()
+}
+
+/// Returns the name of the branch checked out in the repository at the given path.
+///
+/// A `HEAD` detached by [`checkout`] answers with the only local branch, which is the one the
+/// clone was made with. A detached `HEAD` with no such branch to fall back on is an error.
+pub fn current_branch(repository_path: &Path) -> Result<String> {
+    gitctrl::current_branch(repository_path)
+}
+
+/// Fetches `remote_name` into the repository at the given path, tags included, leaving what
+/// is checked out alone. How the fetch goes is reported to `progress`.
+pub fn fetch(repository_path: &Path, remote_name: &str, progress: &Reporter) -> Result<()> {
+    gitctrl::fetch(repository_path, remote_name, progress)
+}
+
+/// Points the remote `remote_name` of the repository at the given path to `url`, the
+/// equivalent of `git remote set-url {remote_name} {url}`. What was fetched from the old URL
+/// stays, so the next fetch brings only what the new one has on top of it.
+pub fn set_remote_url(repository_path: &Path, remote_name: &str, url: &str) -> Result<()> {
+    gitctrl::set_remote_url(repository_path, remote_name, url)
+}
+
+/// Checks out the given commit with a detached `HEAD`, the equivalent of
+/// `git checkout --detach {commit}`.
+pub fn checkout(repository_path: &Path, commit: &[u8]) -> Result<()> {
+    gitctrl::checkout(repository_path, commit)
+}
+
+/// Returns the commit hash (as bytes) that `HEAD` of the repository at the given path is at.
+pub fn head(repository_path: &Path) -> Result<Vec<u8>> {
+    gitctrl::head(repository_path)
+}
+
+/// Returns the commit that `rev` names in the repository at the given path, or `None` when
+/// it names none. `rev` is anything `git rev-parse` takes, such as an abbreviated commit or
+/// `refs/remotes/origin/main`.
+pub fn find_commit(repository_path: &Path, rev: &str) -> Result<Option<Vec<u8>>> {
+    gitctrl::find_commit(repository_path, rev)
+}
+
+/// Returns `true` if `ancestor` is reachable from `descendant`, the equivalent of
+/// `git merge-base --is-ancestor {ancestor} {descendant}`.
+pub fn is_ancestor(repository_path: &Path, ancestor: &[u8], descendant: &[u8]) -> Result<bool> {
+    gitctrl::is_ancestor(repository_path, ancestor, descendant)
+}
+
+/// Returns the content of `path` at the given commit of the repository at the given path, the
+/// equivalent of `git show {commit}:{path}`, or `None` when the commit has no such path.
+pub fn read_at(repository_path: &Path, commit: &[u8], path: &Path) -> Result<Option<Vec<u8>>> {
+    gitctrl::read_at(repository_path, commit, path)
+}
+
+/// Returns the commits reachable from `HEAD` but not from `since` that changed `path`, the
+/// newest first, the equivalent of `git log --format='%H %s' {since}..HEAD -- {path}`.
+///
+/// The history is simplified the way `git log` does: a merge that took `path` from one of its
+/// parents as it was is followed down that parent alone, so a change that the merge dropped is
+/// not reported.
+pub fn log(repository_path: &Path, since: &[u8], path: &Path) -> Result<Vec<Commit>> {
+    gitctrl::log(repository_path, since, path)
+}
+
+/// Returns the path that `path` was renamed to after `since`, or `None` when it was not.
+///
+/// Only a rename that kept the content as it was is found: the commit that removed `path`
+/// has to have the very same content at another path, which it did not have there before,
+/// the equivalent of `git diff-tree -M100% --diff-filter=R`.
+pub fn renamed(repository_path: &Path, since: &[u8], path: &Path) -> Result<Option<PathBuf>> {
+    gitctrl::renamed(repository_path, since, path)
+}
+
+/// Merges the changes `current` and `other` made to `ancestor` line by line, the equivalent of
+/// `git merge-file -p {current} {ancestor} {other}`. Returns `None` when they conflict.
+pub fn merge(ancestor: &str, current: &str, other: &str) -> Result<Option<String>> {
+    gitctrl::merge(ancestor, current, other)
+}
+
+/// Resolves `rev` to a commit of the repository at the given path.
+///
+/// `rev` is tried as a tag name first, then as a commit id, abbreviated or not, and last as a
+/// semver range over the tags, such as `^2.1`, which picks the highest version it matches. A
+/// leading `v` of a tag is not part of its version, so `v2.1.3` matches `^2.1`.
+pub fn resolve(repository_path: &Path, rev: &str) -> Result<Revision> {
+    let tags = gitctrl::tags(repository_path)?;
+    if let Some((tag, commit)) = tags.iter().find(|(name, _)| name == rev) {
+        return Ok(Revision {
+            tag: Some(tag.clone()),
+            commit: commit.clone(),
+        });
+    }
+    if is_commit_id(rev)
+        && let Some(commit) = gitctrl::find_commit(repository_path, rev)?
+    {
+        return Ok(Revision { tag: None, commit });
+    }
+    if let Ok(range) = semver::VersionReq::parse(rev) {
+        let newest = tags
+            .iter()
+            .filter_map(|(name, commit)| tag_version(name).map(|v| (v, name, commit)))
+            .filter(|(version, _, _)| range.matches(version))
+            .max_by(|a, b| a.0.cmp(&b.0));
+        if let Some((_, tag, commit)) = newest {
+            return Ok(Revision {
+                tag: Some(tag.clone()),
+                commit: commit.clone(),
+            });
+        }
+    }
+    Err(Error::Git(format!(
+        "{rev}: neither a tag, a commit, nor a range matching any tag"
+    )))
+}
+
+/// Returns `true` if `rev` reads as a commit id, abbreviated or in full, of either SHA-1 or
+/// SHA-256, which takes 64 digits.
+fn is_commit_id(rev: &str) -> bool {
+    ((4..=40).contains(&rev.len()) || rev.len() == 64) && rev.chars().all(|c| c.is_ascii_hexdigit())
+}
+
+/// The version a tag names, which is `2.1.0` for both `2.1.0` and `v2.1.0`.
+fn tag_version(tag: &str) -> Option<semver::Version> {
+    semver::Version::parse(tag.strip_prefix(['v', 'V']).unwrap_or(tag)).ok()
 }
 
 /// Returns the latest commit hash (as bytes) of the given boilerplate in the repository located at base_path.
 pub fn hash<P: AsRef<Path>>(boilerplate: &Boilerplate, base_path: P) -> Result<Vec<u8>> {
     gitctrl::hash(boilerplate, base_path)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tests::{commit, git, init_with};
+
+    #[test]
+    fn test_is_commit_id_takes_sha1_and_sha256() {
+        assert!(is_commit_id("0b2f"));
+        assert!(is_commit_id(&"0b2f17fd".repeat(5)));
+        assert!(is_commit_id(&"0b2f17fd".repeat(8)));
+        assert!(!is_commit_id("0b2"));
+        assert!(!is_commit_id(&"0b2f17fd".repeat(6)));
+        assert!(!is_commit_id("v1.0.0"));
+    }
+
+    /// `pull` must leave the working tree at the fetched revision, since gixor reads the
+    /// boilerplates from disk rather than from the object database.
+    #[test]
+    fn test_pull_updates_the_working_tree() {
+        let base = tempfile::tempdir().unwrap();
+        let remote = base.path().join("remote");
+        let work = base.path().join("work");
+        init_with(
+            &remote,
+            &[("Foo.gitignore", "v1\n"), ("Global/Bar.gitignore", "bar\n")],
+        );
+
+        clone(
+            remote.to_string_lossy(),
+            &work,
+            "origin",
+            None,
+            &Reporter::silent(),
+        )
+        .unwrap();
+        assert_eq!(
+            std::fs::read_to_string(work.join("Foo.gitignore")).unwrap(),
+            "v1\n"
+        );
+
+        // modify one boilerplate, drop another one, and add a third
+        std::fs::write(remote.join("Foo.gitignore"), "v2\n").unwrap();
+        std::fs::remove_file(remote.join("Global/Bar.gitignore")).unwrap();
+        std::fs::write(remote.join("Baz.gitignore"), "baz\n").unwrap();
+        commit(&remote, "v2", "2024-02-01T00:00:00+0000");
+
+        pull(&work, "origin", "main", &Reporter::silent()).unwrap();
+
+        assert_eq!(
+            std::fs::read_to_string(work.join("Foo.gitignore")).unwrap(),
+            "v2\n"
+        );
+        assert_eq!(
+            std::fs::read_to_string(work.join("Baz.gitignore")).unwrap(),
+            "baz\n"
+        );
+        assert!(!work.join("Global/Bar.gitignore").exists());
+        assert!(
+            !work.join("Global").exists(),
+            "emptied directory is left behind"
+        );
+        // the index has to follow along, or the repository looks dirty to the developer
+        assert_eq!(git(&work, &["status", "--short"]), "");
+    }
+
+    /// A remote whose default branch is not `main` has to be cloned and pulled all the same,
+    /// and a branch that is asked for has to win over the one checked out.
+    #[test]
+    fn test_clone_detects_the_default_branch_and_pull_follows_the_given_one() {
+        let base = tempfile::tempdir().unwrap();
+        let remote = base.path().join("remote");
+        let work = base.path().join("work");
+        init_with(&remote, &[("Foo.gitignore", "master\n")]);
+        git(&remote, &["branch", "-m", "master"]);
+        git(&remote, &["switch", "-c", "develop"]);
+        std::fs::write(remote.join("Foo.gitignore"), "develop\n").unwrap();
+        commit(&remote, "develop", "2024-02-01T00:00:00+0000");
+        git(&remote, &["switch", "master"]);
+
+        let branch = clone(
+            remote.to_string_lossy(),
+            &work,
+            "origin",
+            None,
+            &Reporter::silent(),
+        )
+        .unwrap();
+        assert_eq!(branch, "master");
+        assert_eq!(current_branch(&work).unwrap(), "master");
+
+        std::fs::write(remote.join("Foo.gitignore"), "master v2\n").unwrap();
+        commit(&remote, "master v2", "2024-03-01T00:00:00+0000");
+        pull(&work, "origin", "master", &Reporter::silent()).unwrap();
+        assert_eq!(
+            std::fs::read_to_string(work.join("Foo.gitignore")).unwrap(),
+            "master v2\n"
+        );
+
+        pull(&work, "origin", "develop", &Reporter::silent()).unwrap();
+        assert_eq!(current_branch(&work).unwrap(), "develop");
+        assert_eq!(
+            std::fs::read_to_string(work.join("Foo.gitignore")).unwrap(),
+            "develop\n"
+        );
+        assert_eq!(git(&work, &["status", "--short"]), "");
+
+        // back on a branch the clone already has, which is switched to rather than made again
+        pull(&work, "origin", "master", &Reporter::silent()).unwrap();
+        assert_eq!(current_branch(&work).unwrap(), "master");
+        assert_eq!(
+            std::fs::read_to_string(work.join("Foo.gitignore")).unwrap(),
+            "master v2\n"
+        );
+    }
+
+    /// The branch can be picked up front rather than taken from the remote `HEAD`, and the
+    /// remote does not have to be called origin.
+    #[test]
+    fn test_clone_checks_out_the_given_branch_from_the_given_remote() {
+        let base = tempfile::tempdir().unwrap();
+        let remote = base.path().join("remote");
+        let work = base.path().join("work");
+        init_with(&remote, &[("Foo.gitignore", "main\n")]);
+        git(&remote, &["switch", "-q", "-c", "develop"]);
+        std::fs::write(remote.join("Foo.gitignore"), "develop\n").unwrap();
+        commit(&remote, "develop", "2024-02-01T00:00:00+0000");
+        git(&remote, &["switch", "-q", "main"]);
+
+        let branch = clone(
+            remote.to_string_lossy(),
+            &work,
+            "upstream",
+            Some("develop"),
+            &Reporter::silent(),
+        )
+        .unwrap();
+        assert_eq!(branch, "develop");
+        assert_eq!(current_branch(&work).unwrap(), "develop");
+        assert_eq!(
+            std::fs::read_to_string(work.join("Foo.gitignore")).unwrap(),
+            "develop\n"
+        );
+        assert_eq!(git(&work, &["remote"]), "upstream");
+        pull(&work, "upstream", "develop", &Reporter::silent()).unwrap();
+
+        let missing = base.path().join("missing");
+        assert!(
+            clone(
+                remote.to_string_lossy(),
+                &missing,
+                "origin",
+                Some("absent"),
+                &Reporter::silent(),
+            )
+            .is_err()
+        );
+        assert!(!missing.exists());
+    }
+
+    /// A pinned repository is checked out at what its revision resolves to, whether that is a
+    /// tag, a commit, or the highest tag in a semver range, and a range moves on as tags appear.
+    #[test]
+    fn test_prepare_pins_to_a_tag_a_commit_or_a_range() {
+        use crate::repos::{Outcome, Repository};
+
+        let base = tempfile::tempdir().unwrap();
+        let remote = base.path().join("remote");
+        let v100 = init_with(&remote, &[("Foo.gitignore", "1.0.0\n")]);
+        git(&remote, &["tag", "v1.0.0"]);
+        std::fs::write(remote.join("Foo.gitignore"), "2.1.0\n").unwrap();
+        commit(&remote, "2.1.0", "2024-02-01T00:00:00+0000");
+        git(&remote, &["tag", "-a", "-m", "2.1.0", "v2.1.0"]);
+        std::fs::write(remote.join("Foo.gitignore"), "2.1.4\n").unwrap();
+        let v214 = commit(&remote, "2.1.4", "2024-03-01T00:00:00+0000");
+        git(&remote, &["tag", "v2.1.4"]);
+        std::fs::write(remote.join("Foo.gitignore"), "3.0.0\n").unwrap();
+        commit(&remote, "3.0.0", "2024-04-01T00:00:00+0000");
+        git(&remote, &["tag", "v3.0.0"]);
+
+        let mut repo = Repository::new_with("work", &remote.to_string_lossy());
+        let work = base.path().join("work");
+        let content = || std::fs::read_to_string(work.join("Foo.gitignore")).unwrap();
+
+        repo.rev = Some("^2.1".into());
+        assert!(matches!(
+            repo.prepare(base.path()).unwrap(),
+            Outcome::Cloned { .. }
+        ));
+        assert_eq!(content(), "2.1.4\n");
+        let revision = repo.resolve(base.path()).unwrap().unwrap();
+        assert_eq!(revision.tag.as_deref(), Some("v2.1.4"));
+        assert_eq!(hex::encode(&revision.commit), v214);
+        // the clone still knows the branch it was made with
+        assert_eq!(current_branch(&work).unwrap(), "main");
+
+        repo.rev = Some("v2.1.0".into());
+        assert!(matches!(
+            repo.prepare(base.path()).unwrap(),
+            Outcome::CheckedOut { .. }
+        ));
+        assert_eq!(content(), "2.1.0\n");
+
+        repo.rev = Some(v100[..10].to_string());
+        repo.prepare(base.path()).unwrap();
+        assert_eq!(content(), "1.0.0\n");
+        assert_eq!(repo.resolve(base.path()).unwrap().unwrap().tag, None);
+
+        std::fs::write(remote.join("Foo.gitignore"), "2.2.0\n").unwrap();
+        commit(&remote, "2.2.0", "2024-05-01T00:00:00+0000");
+        git(&remote, &["tag", "v2.2.0"]);
+        repo.rev = Some("^2.1".into());
+        repo.prepare(base.path()).unwrap();
+        assert_eq!(content(), "2.2.0\n");
+        assert_eq!(git(&work, &["status", "--short"]), "");
+
+        repo.rev = Some("^4".into());
+        assert!(repo.prepare(base.path()).is_err());
+
+        // dropping the pin goes back to following the branch
+        repo.rev = None;
+        repo.prepare(base.path()).unwrap();
+        assert_eq!(content(), "2.2.0\n");
+        assert_eq!(git(&work, &["symbolic-ref", "--short", "HEAD"]), "main");
+    }
+
+    /// The tags are listed with the commits they point at, annotated ones peeled, a commit is
+    /// found by a prefix of its id, and checking one out detaches `HEAD` at it.
+    #[test]
+    fn test_tags_find_commit_and_checkout() {
+        let base = tempfile::tempdir().unwrap();
+        let repo = base.path().join("repo");
+        let first = init_with(&repo, &[("Foo.gitignore", "1\n")]);
+        git(&repo, &["tag", "v1.0.0"]);
+        std::fs::write(repo.join("Foo.gitignore"), "2\n").unwrap();
+        let second = commit(&repo, "second", "2024-02-01T00:00:00+0000");
+        git(&repo, &["tag", "-a", "-m", "2.0.0", "v2.0.0"]);
+        let (first_id, second_id) = (hex::decode(&first).unwrap(), hex::decode(&second).unwrap());
+
+        let mut tags = gitctrl::tags(&repo).unwrap();
+        tags.sort();
+        assert_eq!(
+            tags,
+            vec![
+                ("v1.0.0".to_string(), first_id.clone()),
+                ("v2.0.0".to_string(), second_id.clone())
+            ]
+        );
+        assert_eq!(
+            find_commit(&repo, &first[..7]).unwrap(),
+            Some(first_id.clone())
+        );
+        assert_eq!(
+            find_commit(&repo, "v2.0.0").unwrap(),
+            Some(second_id.clone())
+        );
+        assert_eq!(find_commit(&repo, "0000000").unwrap(), None);
+
+        checkout(&repo, &first_id).unwrap();
+        assert_eq!(head(&repo).unwrap(), first_id);
+        assert_eq!(
+            std::fs::read_to_string(repo.join("Foo.gitignore")).unwrap(),
+            "1\n"
+        );
+        assert_eq!(git(&repo, &["status", "--short"]), "");
+        // a detached HEAD still tells the branch the clone was made with
+        assert_eq!(current_branch(&repo).unwrap(), "main");
+        checkout(&repo, &second_id).unwrap();
+        assert_eq!(head(&repo).unwrap(), second_id);
+
+        let tag = resolve(&repo, "^1").unwrap();
+        assert_eq!((tag.tag.as_deref(), tag.commit), (Some("v1.0.0"), first_id));
+        let commit = resolve(&repo, &second[..10]).unwrap();
+        assert_eq!((commit.tag, commit.commit), (None, second_id));
+        assert!(resolve(&repo, "v9.9.9").is_err());
+    }
+
+    /// A clone that fails leaves nothing behind, neither where the repository goes, where a
+    /// later run would take it for a clone, nor beside it.
+    #[test]
+    fn test_failed_clones_are_rolled_back() {
+        use crate::repos::Repository;
+
+        let base = tempfile::tempdir().unwrap();
+        let remote = base.path().join("remote");
+        init_with(&remote, &[("Foo.gitignore", "foo\n")]);
+        let entries = || {
+            std::fs::read_dir(base.path())
+                .unwrap()
+                .map(|e| e.unwrap().file_name().to_string_lossy().to_string())
+                .collect::<Vec<_>>()
+        };
+
+        let missing = base.path().join("missing");
+        let work = base.path().join("work");
+        assert!(
+            clone(
+                missing.to_string_lossy(),
+                &work,
+                "origin",
+                None,
+                &Reporter::silent()
+            )
+            .is_err()
+        );
+        assert_eq!(entries(), vec!["remote"]);
+
+        // the clone itself goes well here, and the revision it is pinned to does not exist
+        let mut repo = Repository::new_with("work", &remote.to_string_lossy());
+        repo.rev = Some("v9.9.9".into());
+        assert!(repo.prepare(base.path()).is_err());
+        assert_eq!(entries(), vec!["remote"]);
+
+        // a clone that was there before is kept, whatever became of the update
+        repo.rev = None;
+        repo.prepare(base.path()).unwrap();
+        repo.rev = Some("v9.9.9".into());
+        assert!(repo.prepare(base.path()).is_err());
+        assert!(work.join(".git").exists());
+        // and the directory a clone left when it was killed is cleared by the next one
+        std::fs::create_dir_all(base.path().join(".other.partial/.git")).unwrap();
+        clone(
+            remote.to_string_lossy(),
+            base.path().join("other"),
+            "origin",
+            None,
+            &Reporter::silent(),
+        )
+        .unwrap();
+        assert!(!base.path().join(".other.partial").exists());
+        assert!(base.path().join("other/Foo.gitignore").exists());
+    }
+}
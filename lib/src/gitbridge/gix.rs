@@ -2,28 +2,49 @@
 //! This module provides functions to interact with Git repositories
 //! using the `gix` crate.
 //! It includes functions to clone repositories, pull updates, and retrieve commit hashes.
-use std::collections::HashSet;
+use std::collections::{HashMap, HashSet};
 use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::mpsc::{RecvTimeoutError, channel};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
 
 use gix::{
-    remote::{fetch::Outcome, ref_map::Options},
     ObjectId, Repository, Tree,
+    progress::{Count, Id, MessageLevel, NestedProgress, Progress, Step, StepShared, Unit},
+    remote::{fetch::Outcome, ref_map::Options},
 };
 
-use crate::{repos::Boilerplate, Error, Result};
+use crate::progress::{Event, Phase, Reporter};
+use crate::{
+    Error, Result,
+    repos::{Boilerplate, Commit},
+};
 
-pub fn clone<S: AsRef<str>, P: AsRef<Path>>(url: S, path: P) -> crate::Result<()> {
+pub fn clone<S: AsRef<str>, P: AsRef<Path>>(
+    url: S,
+    path: P,
+    remote: &str,
+    branch: Option<&str>,
+    progress: &Reporter,
+) -> crate::Result<String> {
     let url = url.as_ref();
     let path = path.as_ref();
     std::fs::create_dir_all(path).map_err(crate::Error::IO)?;
     let url = gix::url::parse(url.as_ref())
         .map_err(|e| crate::Error::Git(format!("Failed to parse URL: {e}")))?;
     let mut prepare_clone = gix::prepare_clone(url.clone(), path)
-        .map_err(|e| crate::Error::Git(format!("Failed to prepare clone: {e}")))?;
+        .map_err(|e| crate::Error::Git(format!("Failed to prepare clone: {e}")))?
+        .with_remote_name(remote)
+        .map_err(|e| crate::Error::Git(format!("{remote}: invalid remote name: {e}")))?
+        .with_ref_name(branch)
+        .map_err(|e| crate::Error::Git(format!("invalid branch name: {e}")))?;
     log::info!("Cloning {:?} into {path:?}...", url.to_string());
-    let (mut prepare_checkout, _) = prepare_clone
-        .fetch_then_checkout(gix::progress::Discard, &gix::interrupt::IS_INTERRUPTED)
-        .map_err(|e| crate::Error::Git(format!("Failed to fetch and checkout: {e}")))?;
+    let (mut prepare_checkout, _) = watched(progress, |tracker| {
+        prepare_clone
+            .fetch_then_checkout(tracker, &gix::interrupt::IS_INTERRUPTED)
+            .map_err(|e| crate::Error::Git(format!("Failed to fetch and checkout: {e}")))
+    })?;
     log::info!(
         "Checking out into {} ...",
         prepare_checkout
@@ -32,9 +53,11 @@ pub fn clone<S: AsRef<str>, P: AsRef<Path>>(url: S, path: P) -> crate::Result<()
             .expect("should be there")
             .display()
     );
-    let (repo, _) = prepare_checkout
-        .main_worktree(gix::progress::Discard, &gix::interrupt::IS_INTERRUPTED)
-        .map_err(|e| crate::Error::Git(format!("Failed to checkout main worktree: {e}")))?;
+    let (repo, _) = watched(progress, |tracker| {
+        prepare_checkout
+            .main_worktree(tracker, &gix::interrupt::IS_INTERRUPTED)
+            .map_err(|e| crate::Error::Git(format!("Failed to checkout main worktree: {e}")))
+    })?;
     log::info!(
         "Repo cloned into {}",
         repo.workdir().expect("directory pre-created").display()
@@ -55,9 +78,303 @@ pub fn clone<S: AsRef<str>, P: AsRef<Path>>(url: S, path: P) -> crate::Result<()
             .expect("should be the remote URL")
             .to_bstring(),
     );
+    // Without a branch given, the clone checks out the one the remote HEAD points at, so the
+    // branch checked out now is the default branch of the remote.
+    head_branch(&repo)
+}
+
+pub fn interrupt() {
+    gix::interrupt::trigger();
+}
+
+pub fn is_interrupted() -> bool {
+    gix::interrupt::is_triggered()
+}
+
+fn open(path: &Path) -> Result<Repository> {
+    gix::open(path).map_err(|e| Error::Git(format!("Failed to open repository: {e}")))
+}
+
+pub fn current_branch(path: &Path) -> Result<String> {
+    let repo = open(path)?;
+    if repo.head_name().ok().flatten().is_some() {
+        return head_branch(&repo);
+    }
+    // A clone pinned to a revision has its HEAD detached, and still holds the single branch
+    // it was cloned with.
+    let branches = repo
+        .references()
+        .map_err(|e| Error::Git(format!("Failed to read the references: {e}")))?
+        .local_branches()
+        .map_err(|e| Error::Git(format!("Failed to read the branches: {e}")))?
+        .flatten()
+        .map(|r| r.name().shorten().to_string())
+        .collect::<Vec<_>>();
+    match branches.as_slice() {
+        [branch] => Ok(branch.clone()),
+        _ => head_branch(&repo),
+    }
+}
+
+/// Rewrites `remote.{remote}.url` in the configuration file of the repository.
+///
+/// The file is edited as a file, rather than through the configuration of the open repository,
+/// which holds the values of every level merged and would write them all back into it. Like
+/// Git, the new content goes to `config.lock` first and replaces the file only once written.
+pub fn set_remote_url(path: &Path, remote: &str, url: &str) -> Result<()> {
+    let repo = open(path)?;
+    repo.find_remote(remote)
+        .map_err(|e| Error::Git(format!("{remote}: remote not found: {e}")))?;
+    let config_path = repo.git_dir().join("config");
+    let mut config =
+        gix::config::File::from_path_no_includes(config_path.clone(), gix::config::Source::Local)
+            .map_err(|e| Error::Git(format!("{}: failed to read: {e}", config_path.display())))?;
+    config
+        .set_raw_value_by("remote", Some(remote.into()), "url", url)
+        .map_err(|e| Error::Git(format!("remote.{remote}.url: failed to set: {e}")))?;
+    let lock_path = config_path.with_extension("lock");
+    let mut content = Vec::new();
+    config.write_to(&mut content).map_err(Error::IO)?;
+    std::fs::write(&lock_path, content).map_err(Error::IO)?;
+    std::fs::rename(&lock_path, &config_path).map_err(Error::IO)
+}
+
+/// Returns the tags of the repository with the commits they point at, annotated tags peeled.
+pub fn tags(path: &Path) -> Result<Vec<(String, Vec<u8>)>> {
+    let repo = open(path)?;
+    let references = repo
+        .references()
+        .map_err(|e| Error::Git(format!("Failed to read the references: {e}")))?;
+    let mut tags = vec![];
+    for tag in references
+        .tags()
+        .map_err(|e| Error::Git(format!("Failed to read the tags: {e}")))?
+    {
+        let mut tag = tag.map_err(|e| Error::Git(format!("Failed to read a tag: {e}")))?;
+        let name = tag.name().shorten().to_string();
+        match tag.peel_to_id() {
+            Ok(id) => tags.push((name, id.as_bytes().to_vec())),
+            Err(e) => log::warn!("{name}: failed to peel the tag: {e}"),
+        }
+    }
+    Ok(tags)
+}
+
+/// Returns the commit that `rev` abbreviates, or `None` if no commit matches it.
+pub fn find_commit(path: &Path, rev: &str) -> Result<Option<Vec<u8>>> {
+    let repo = open(path)?;
+    Ok(repo
+        .rev_parse_single(format!("{rev}^{{commit}}").as_str())
+        .ok()
+        .map(|id| id.as_bytes().to_vec()))
+}
+
+pub fn head(path: &Path) -> Result<Vec<u8>> {
+    let repo = open(path)?;
+    repo.head_id()
+        .map(|id| id.as_bytes().to_vec())
+        .map_err(|e| Error::Git(format!("Failed to get the HEAD: {e}")))
+}
+
+fn object_id(commit: &[u8]) -> Result<ObjectId> {
+    ObjectId::try_from(commit)
+        .map_err(|e| Error::Git(format!("{}: not a commit hash: {e}", hex::encode(commit))))
+}
+
+pub fn is_ancestor(path: &Path, ancestor: &[u8], descendant: &[u8]) -> Result<bool> {
+    let repo = open(path)?;
+    let (ancestor, descendant) = (object_id(ancestor)?, object_id(descendant)?);
+    if ancestor == descendant {
+        return Ok(true);
+    }
+    // Unrelated histories have no merge base at all, which is a plain no.
+    Ok(repo
+        .merge_base(ancestor, descendant)
+        .is_ok_and(|base| base.detach() == ancestor))
+}
+
+/// Detaches `HEAD` at `commit`, updating the working tree unless it is there already.
+pub fn checkout(path: &Path, commit: &[u8]) -> Result<()> {
+    let mut repo = open(path)?;
+    name_the_committer(&mut repo);
+    let id = object_id(commit)?;
+    let head_id = repo.head_id().ok().map(|id| id.detach());
+    let detached = repo.head_name().ok().flatten().is_none();
+    if head_id == Some(id) && detached {
+        return Ok(());
+    }
+    log::info!("Checking out {id}");
+    if head_id != Some(id) {
+        reset_worktree(&repo, id)?;
+    }
+    let head = gix::refs::transaction::RefEdit {
+        change: gix::refs::transaction::Change::Update {
+            log: gix::refs::transaction::LogChange {
+                message: format!("checkout: moving to {id}").into(),
+                ..Default::default()
+            },
+            expected: gix::refs::transaction::PreviousValue::Any,
+            new: gix::refs::Target::Object(id),
+        },
+        name: "HEAD".try_into().expect("HEAD is a valid reference name"),
+        deref: false,
+    };
+    repo.edit_reference(head)
+        .map_err(|e| Error::Git(format!("Failed to check out {id}: {e}")))?;
     Ok(())
 }
 
+pub fn read_at(path: &Path, commit: &[u8], target: &Path) -> Result<Option<Vec<u8>>> {
+    let repo = open(path)?;
+    let id = object_id(commit)?;
+    let tree = commit_tree(&repo, id)?;
+    let Some(entry) = entry_id(&tree, target)? else {
+        return Ok(None);
+    };
+    repo.find_object(entry)
+        .map(|object| Some(object.detach().data))
+        .map_err(|e| Error::Git(format!("{entry}: failed to find the object: {e}")))
+}
+
+pub fn merge(ancestor: &str, current: &str, other: &str) -> Result<Option<String>> {
+    use gix::merge::blob::{Resolution, builtin_driver::text};
+    let mut out = vec![];
+    let mut input = gix::diff::blob::InternedInput::default();
+    let resolution = text(
+        &mut out,
+        &mut input,
+        text::Labels::default(),
+        current.as_bytes(),
+        ancestor.as_bytes(),
+        other.as_bytes(),
+        text::Options::default(),
+    );
+    Ok(match resolution {
+        Resolution::Conflict => None,
+        _ => Some(String::from_utf8_lossy(&out).to_string()),
+    })
+}
+
+pub fn log(path: &Path, since: &[u8], target: &Path) -> Result<Vec<Commit>> {
+    let repo = open(path)?;
+    history(&repo, object_id(since)?, target)?
+        .into_iter()
+        .map(|id| {
+            let commit = repo
+                .find_commit(id)
+                .map_err(|e| Error::Git(format!("{id}: failed to find the commit: {e}")))?;
+            let message = commit
+                .message()
+                .map_err(|e| Error::Git(format!("{id}: failed to read the message: {e}")))?;
+            Ok(Commit {
+                id: id.as_bytes().to_vec(),
+                summary: message.summary().to_string(),
+            })
+        })
+        .collect()
+}
+
+pub fn renamed(path: &Path, since: &[u8], target: &Path) -> Result<Option<PathBuf>> {
+    let repo = open(path)?;
+    // the newest change to a path that is gone is the commit that removed it
+    let Some(removal) = history(&repo, object_id(since)?, target)?
+        .into_iter()
+        .next()
+    else {
+        return Ok(None);
+    };
+    let tree = commit_tree(&repo, removal)?;
+    if entry_id(&tree, target)?.is_some() {
+        return Ok(None);
+    }
+    let commit = repo
+        .find_commit(removal)
+        .map_err(|e| Error::Git(format!("{removal}: failed to find the commit: {e}")))?;
+    for parent in commit.parent_ids() {
+        let parent = commit_tree(&repo, parent.detach())?;
+        let Some(removed) = entry_id(&parent, target)? else {
+            continue;
+        };
+        let files = tree
+            .traverse()
+            .breadthfirst
+            .files()
+            .map_err(|e| Error::Git(format!("{removal}: failed to list the files: {e}")))?;
+        for file in files.into_iter().filter(|file| file.oid == removed) {
+            let candidate = PathBuf::from(file.filepath.to_string());
+            if entry_id(&parent, &candidate)? != Some(removed) {
+                return Ok(Some(candidate));
+            }
+        }
+    }
+    Ok(None)
+}
+
+/// Returns the commits reachable from `HEAD` but not from `since` that changed `target`, the
+/// newest first.
+///
+/// The walk goes down the history the way [`hash`] does, following a parent that has the very
+/// same object at `target`, and the first parent past a commit that changed it.
+fn history(repo: &Repository, since: ObjectId, target: &Path) -> Result<Vec<ObjectId>> {
+    let head = repo
+        .head_id()
+        .map_err(|e| Error::Git(format!("Failed to get the HEAD: {e}")))?
+        .detach();
+    let unseen = repo
+        .rev_walk([head])
+        .with_hidden([since])
+        .all()
+        .map_err(|e| Error::Git(format!("{since}: failed to walk the history: {e}")))?
+        .map(|info| info.map(|info| info.id))
+        .collect::<std::result::Result<HashSet<_>, _>>()
+        .map_err(|e| Error::Git(format!("{since}: failed to walk the history: {e}")))?;
+
+    let mut changes = vec![];
+    let mut current = head;
+    while unseen.contains(&current) {
+        let commit = repo
+            .find_commit(current)
+            .map_err(|e| Error::Git(format!("{current}: failed to find the commit: {e}")))?;
+        let entry = entry_id(&commit_tree(repo, current)?, target)?;
+        let parents = commit
+            .parent_ids()
+            .map(|id| id.detach())
+            .collect::<Vec<_>>();
+        let mut treesame = None;
+        for parent in &parents {
+            if entry_id(&commit_tree(repo, *parent)?, target)? == entry {
+                treesame = Some(*parent);
+                break;
+            }
+        }
+        match (treesame, parents.first()) {
+            (Some(parent), _) => current = parent,
+            (None, Some(first)) => {
+                changes.push(current);
+                current = *first;
+            }
+            // a root commit changed `target` only if it introduced it
+            (None, None) => {
+                if entry.is_some() {
+                    changes.push(current);
+                }
+                break;
+            }
+        }
+    }
+    Ok(changes)
+}
+
+/// Returns the short name of the branch `HEAD` points at.
+fn head_branch(repo: &Repository) -> Result<String> {
+    let name = repo
+        .head_name()
+        .map_err(|e| Error::Git(format!("Failed to read the HEAD: {e}")))?
+        .ok_or_else(|| Error::Git("HEAD is detached, so no branch is checked out".into()))?;
+    let name = name.as_bstr().to_string();
+    Ok(name.trim_start_matches("refs/heads/").to_string())
+}
+
 /// Returns the id of the object that `tree` holds at `path`, or `None` if `path` is absent.
 fn entry_id(tree: &Tree<'_>, path: &Path) -> Result<Option<ObjectId>> {
     tree.lookup_entry_by_path(path)
@@ -136,6 +453,201 @@ pub fn hash<P: AsRef<Path>>(boilerplate: &Boilerplate, base_path: P) -> Result<V
     }
 }
 
+/// How often [`watched`] looks at the progress of gix.
+const SAMPLING_INTERVAL: Duration = Duration::from_millis(100);
+
+/// Runs `work` with a [`Tracker`] for gix to report to, and tells `progress` what becomes of
+/// it.
+///
+/// gix reports its progress by bumping counters, which a renderer is expected to look at now
+/// and then, rather than by calling back. So a thread of its own samples the counters until
+/// `work` is over, once more at the end so that the last counts are not lost, and turns what
+/// changed into [`Event`]s.
+fn watched<T>(progress: &Reporter, work: impl FnOnce(Tracker) -> T) -> T {
+    let tracker = Tracker::default();
+    let (done, finished) = channel::<()>();
+    std::thread::scope(|scope| {
+        let tasks = &tracker.tasks;
+        let sampler = scope.spawn(move || {
+            let mut sampler = Sampler::default();
+            while let Err(RecvTimeoutError::Timeout) = finished.recv_timeout(SAMPLING_INTERVAL) {
+                sampler.sample(tasks, progress);
+            }
+            sampler.sample(tasks, progress);
+        });
+        let result = work(tracker.clone());
+        drop(done);
+        if sampler.join().is_err() {
+            log::warn!("{}: the progress sampler panicked", progress.repository());
+        }
+        result
+    })
+}
+
+/// A counter gix bumps, named and identified the way gix does.
+struct Task {
+    id: Id,
+    name: Mutex<String>,
+    step: StepShared,
+    max: Mutex<Option<Step>>,
+}
+
+impl Task {
+    fn new(name: String, id: Id) -> Self {
+        Task {
+            id,
+            name: Mutex::new(name),
+            step: Arc::new(AtomicUsize::new(0)),
+            max: Mutex::new(None),
+        }
+    }
+}
+
+/// The progress handed to gix: one task of a list that every task it spawns is added to.
+///
+/// This is what the tree of `prodash`, which gix otherwise renders with, does too, except that
+/// a task stays on the list once gix is done with it. A clone of a small repository is over
+/// before anyone looks, and the final counts are the ones that matter.
+#[derive(Clone)]
+struct Tracker {
+    tasks: Arc<Mutex<Vec<Arc<Task>>>>,
+    task: Arc<Task>,
+}
+
+impl Default for Tracker {
+    fn default() -> Self {
+        let task = Arc::new(Task::new(String::new(), gix::progress::UNKNOWN));
+        Tracker {
+            tasks: Arc::new(Mutex::new(vec![task.clone()])),
+            task,
+        }
+    }
+}
+
+impl Count for Tracker {
+    fn set(&self, step: Step) {
+        self.task.step.store(step, Ordering::Relaxed);
+    }
+
+    fn step(&self) -> Step {
+        self.task.step.load(Ordering::Relaxed)
+    }
+
+    fn inc_by(&self, step: Step) {
+        self.task.step.fetch_add(step, Ordering::Relaxed);
+    }
+
+    fn counter(&self) -> StepShared {
+        self.task.step.clone()
+    }
+}
+
+impl Progress for Tracker {
+    fn init(&mut self, max: Option<Step>, _unit: Option<Unit>) {
+        *self.task.max.lock().unwrap() = max;
+        self.set(0);
+    }
+
+    fn max(&self) -> Option<Step> {
+        *self.task.max.lock().unwrap()
+    }
+
+    fn set_max(&mut self, max: Option<Step>) -> Option<Step> {
+        std::mem::replace(&mut *self.task.max.lock().unwrap(), max)
+    }
+
+    fn set_name(&mut self, name: String) {
+        *self.task.name.lock().unwrap() = name;
+    }
+
+    fn name(&self) -> Option<String> {
+        Some(self.task.name.lock().unwrap().clone())
+    }
+
+    fn id(&self) -> Id {
+        self.task.id
+    }
+
+    fn message(&self, _level: MessageLevel, message: String) {
+        log::debug!("{}: {message}", self.task.name.lock().unwrap());
+    }
+}
+
+impl NestedProgress for Tracker {
+    type SubProgress = Tracker;
+
+    fn add_child(&mut self, name: impl Into<String>) -> Tracker {
+        self.add_child_with_id(name, gix::progress::UNKNOWN)
+    }
+
+    fn add_child_with_id(&mut self, name: impl Into<String>, id: Id) -> Tracker {
+        let task = Arc::new(Task::new(name.into(), id));
+        self.tasks.lock().unwrap().push(task.clone());
+        Tracker {
+            tasks: self.tasks.clone(),
+            task,
+        }
+    }
+}
+
+/// Turns the tasks of a [`Tracker`] into [`Event`]s, remembering what it told last so that
+/// nothing is told twice.
+#[derive(Default)]
+struct Sampler {
+    told: HashMap<Phase, Event>,
+}
+
+impl Sampler {
+    fn sample(&mut self, tasks: &Mutex<Vec<Arc<Task>>>, progress: &Reporter) {
+        use gix::odb::pack::{bundle, index};
+        let remote = Id::from(gix::remote::fetch::ProgressId::RemoteProgress);
+        let read_pack = Id::from(bundle::write::ProgressId::ReadPackBytes);
+        let indexed = Id::from(index::write::ProgressId::IndexObjects);
+        let resolved = Id::from(index::write::ProgressId::ResolveObjects);
+        let checked_out = Id::from(gix::clone::checkout::main_worktree::ProgressId::CheckoutFiles);
+
+        let mut bytes = None;
+        let mut counts = vec![];
+        for task in tasks.lock().unwrap().iter() {
+            let done = task.step.load(Ordering::Relaxed) as u64;
+            let total = task.max.lock().unwrap().map(|total| total as u64);
+            let name = task.name.lock().unwrap();
+            // The remote names what it is doing in the task, as git prints it.
+            let phase = match task.id {
+                id if id == remote && name.contains("Counting") => Phase::Counting,
+                id if id == remote && name.contains("Compressing") => Phase::Compressing,
+                id if id == read_pack => {
+                    bytes = Some(done);
+                    continue;
+                }
+                id if id == indexed => Phase::Receiving,
+                id if id == resolved => Phase::Resolving,
+                id if id == checked_out => Phase::CheckingOut,
+                _ => continue,
+            };
+            counts.push((phase, done, total));
+        }
+        // The phases overlap, and the last one told is taken for the one going on.
+        counts.sort_by_key(|(phase, _, _)| *phase);
+        for (phase, done, total) in counts {
+            let event = Event::Progress {
+                phase,
+                done,
+                total,
+                bytes: if phase == Phase::Receiving {
+                    bytes
+                } else {
+                    None
+                },
+            };
+            if self.told.get(&phase) != Some(&event) {
+                progress.report(event.clone());
+                self.told.insert(phase, event);
+            }
+        }
+    }
+}
+
 /// The message of an error together with the ones beneath it.
 ///
 /// gix nests what actually went wrong several levels down, and Display shows only the outermost
@@ -151,13 +663,16 @@ fn chain(e: &dyn std::error::Error) -> String {
     message
 }
 
-fn do_fetch(repo: &gix::Repository, remote: &str) -> Result<Outcome> {
+fn do_fetch(repo: &gix::Repository, remote: &str, progress: &Reporter) -> Result<Outcome> {
     use gix::{progress::Discard, remote::Direction::Fetch};
     log::info!("Fetching from remote: {remote}");
 
+    // Every tag is fetched, not only the ones reachable from the branches, since a repository
+    // may be pinned to any of them.
     let remote = repo
         .find_remote(remote)
-        .map_err(|e| Error::Git(format!("Failed to find remote: {e}")))?;
+        .map_err(|e| Error::Git(format!("Failed to find remote: {e}")))?
+        .with_fetch_tags(gix::remote::fetch::Tags::All);
     let c = remote
         .connect(Fetch)
         .map_err(|e| Error::Git(format!("Failed to connect to remote: {e}")))?;
@@ -171,9 +686,10 @@ fn do_fetch(repo: &gix::Repository, remote: &str) -> Result<Outcome> {
             },
         )
         .map_err(|e| Error::Git(format!("Failed to prepare fetch: {e}")))?;
-    let outcome = r
-        .receive(Discard, &gix::interrupt::IS_INTERRUPTED)
-        .map_err(|e| Error::Git(format!("Failed to receive fetch: {}", chain(&e))))?;
+    let outcome = watched(progress, |tracker| {
+        r.receive(tracker, &gix::interrupt::IS_INTERRUPTED)
+            .map_err(|e| Error::Git(format!("Failed to receive fetch: {}", chain(&e))))
+    })?;
     log::info!("Fetch completed: {:?}", outcome.status);
     Ok(outcome)
 }
@@ -323,7 +839,69 @@ fn fast_forward(repo: &Repository, current_branch: &str, remote_id: gix::Id) ->
     Ok(())
 }
 
+/// Points `HEAD` at `branch` and checks it out, unless it is checked out already.
+///
+/// The branch is created at the remote tracking branch when the clone has none of that name,
+/// which is what lets the branch in the configuration change after the clone was made.
+fn switch_to(repo: &Repository, remote: &str, branch: &str) -> Result<()> {
+    if head_branch(repo).is_ok_and(|current| current == branch) {
+        return Ok(());
+    }
+    log::info!("Switching to branch {branch}");
+    let local_ref_name = format!("refs/heads/{branch}");
+    let local = repo
+        .try_find_reference(&local_ref_name)
+        .map_err(|e| Error::Git(format!("Failed to find local branch: {e}")))?;
+    let target = match local {
+        Some(mut reference) => reference
+            .peel_to_id()
+            .map_err(|e| Error::Git(format!("{local_ref_name}: failed to peel: {e}")))?
+            .detach(),
+        None => {
+            let remote_tracking_name = format!("refs/remotes/{remote}/{branch}");
+            let id = repo
+                .find_reference(&remote_tracking_name)
+                .map_err(|e| Error::Git(format!("Failed to find remote tracking branch: {e}")))?
+                .peel_to_id()
+                .map_err(|e| Error::Git(format!("{remote_tracking_name}: failed to peel: {e}")))?
+                .detach();
+            repo.reference(
+                local_ref_name.as_str(),
+                id,
+                gix::refs::transaction::PreviousValue::MustNotExist,
+                format!("branch: Created from {remote}/{branch}"),
+            )
+            .map_err(|e| Error::Git(format!("Failed to create local branch: {e}")))?;
+            id
+        }
+    };
+    // The same order as in fast_forward: the files first, then the reference that says which
+    // revision they are at.
+    reset_worktree(repo, target)?;
+    let head = gix::refs::transaction::RefEdit {
+        change: gix::refs::transaction::Change::Update {
+            log: gix::refs::transaction::LogChange {
+                message: format!("checkout: moving to {branch}").into(),
+                ..Default::default()
+            },
+            expected: gix::refs::transaction::PreviousValue::Any,
+            new: gix::refs::Target::Symbolic(
+                local_ref_name
+                    .as_str()
+                    .try_into()
+                    .map_err(|e| Error::Git(format!("{branch}: invalid branch name: {e}")))?,
+            ),
+        },
+        name: "HEAD".try_into().expect("HEAD is a valid reference name"),
+        deref: false,
+    };
+    repo.edit_reference(head)
+        .map_err(|e| Error::Git(format!("Failed to check out {branch}: {e}")))?;
+    Ok(())
+}
+
 fn do_merge(repo: &mut Repository, remote: &str, branch: &str) -> Result<()> {
+    switch_to(repo, remote, branch)?;
     let (_local_id, remote_id, strategy) = find_merge_strategy(repo, remote, branch)?;
     if strategy == Strategy::NoNeed {
         log::info!("Already up to date.");
@@ -353,7 +931,10 @@ fn name_the_committer(repo: &mut Repository) {
     log::debug!("no committer is configured; signing the reflog as gixor");
     let mut config = gix::config::File::new(gix::config::file::Metadata::api());
     let fallbacks = [
-        (&gix::config::tree::gitoxide::Committer::NAME_FALLBACK, "gixor"),
+        (
+            &gix::config::tree::gitoxide::Committer::NAME_FALLBACK,
+            "gixor",
+        ),
         (
             &gix::config::tree::gitoxide::Committer::EMAIL_FALLBACK,
             "gixor@users.noreply.github.com",
@@ -368,59 +949,26 @@ fn name_the_committer(repo: &mut Repository) {
     repo.config_snapshot_mut().append(config);
 }
 
-pub fn pull(path: &Path, remote: &str, branch: &str) -> Result<()> {
-    let mut repo =
-        gix::open(path).map_err(|e| Error::Git(format!("Failed to open repository: {e}")))?;
+pub fn pull(path: &Path, remote: &str, branch: &str, progress: &Reporter) -> Result<()> {
+    let mut repo = open(path)?;
     name_the_committer(&mut repo);
-    let _fetch_outcome = do_fetch(&repo, remote)?;
+    let _fetch_outcome = do_fetch(&repo, remote, progress)?;
     do_merge(&mut repo, remote, branch)
 }
 
+pub fn fetch(path: &Path, remote: &str, progress: &Reporter) -> Result<()> {
+    let mut repo = open(path)?;
+    name_the_committer(&mut repo);
+    do_fetch(&repo, remote, progress).map(|_| ())
+}
+
 #[cfg(test)]
 mod tests {
+    use crate::progress::{Event, Observer, Phase, Reporter};
     use crate::repos::Repository;
+    use crate::tests::{commit, git};
     use std::path::{Path, PathBuf};
-    use std::process::Command;
-
-    fn git(dir: &Path, args: &[&str]) -> String {
-        let out = Command::new("git")
-            .args(args)
-            .current_dir(dir)
-            .env("GIT_AUTHOR_NAME", "gixor")
-            .env("GIT_AUTHOR_EMAIL", "gixor@example.com")
-            .env("GIT_COMMITTER_NAME", "gixor")
-            .env("GIT_COMMITTER_EMAIL", "gixor@example.com")
-            .output()
-            .unwrap();
-        assert!(
-            out.status.success(),
-            "git {:?}: {}",
-            args,
-            String::from_utf8_lossy(&out.stderr)
-        );
-        String::from_utf8_lossy(&out.stdout).trim().to_string()
-    }
-
-    fn commit(dir: &Path, message: &str, date: &str) -> String {
-        git(dir, &["add", "-A"]);
-        let out = Command::new("git")
-            .args(["commit", "-m", message])
-            .current_dir(dir)
-            .env("GIT_AUTHOR_NAME", "gixor")
-            .env("GIT_AUTHOR_EMAIL", "gixor@example.com")
-            .env("GIT_COMMITTER_NAME", "gixor")
-            .env("GIT_COMMITTER_EMAIL", "gixor@example.com")
-            .env("GIT_AUTHOR_DATE", date)
-            .env("GIT_COMMITTER_DATE", date)
-            .output()
-            .unwrap();
-        assert!(
-            out.status.success(),
-            "git commit: {}",
-            String::from_utf8_lossy(&out.stderr)
-        );
-        git(dir, &["rev-parse", "HEAD"])
-    }
+    use std::sync::Arc;
 
     fn repository() -> Repository {
         Repository {
@@ -429,6 +977,12 @@ mod tests {
             owner: "github".to_string(),
             repo_name: "gitignore".to_string(),
             path: PathBuf::from("repo"),
+            remote: None,
+            branch: None,
+            rev: None,
+            permalink_template: None,
+            priority: 0,
+            enabled: true,
         }
     }
 
@@ -478,49 +1032,78 @@ mod tests {
         assert_ne!(expected_foo, git(&dir, &["rev-parse", "HEAD"]));
     }
 
-    /// `pull` must leave the working tree at the fetched revision, since gixor reads the
-    /// boilerplates from disk rather than from the object database.
+    /// `read_at` reads the object database rather than the working tree, and `merge` keeps the
+    /// changes of both sides where they do not touch the same lines.
+    #[test]
+    fn test_read_at_and_merge() {
+        let base = tempfile::tempdir().unwrap();
+        let dir = base.path().join("repo");
+        std::fs::create_dir_all(&dir).unwrap();
+
+        git(&dir, &["init", "-b", "main"]);
+        std::fs::write(dir.join("Foo.gitignore"), "a\nb\nc\n").unwrap();
+        let first = commit(&dir, "first", "2024-01-01T00:00:00+0000");
+        std::fs::write(dir.join("Foo.gitignore"), "a\nb\nc\nd\n").unwrap();
+        commit(&dir, "second", "2024-02-01T00:00:00+0000");
+
+        let first = hex::decode(first).unwrap();
+        let read = |path: &str| super::read_at(&dir, &first, Path::new(path)).unwrap();
+        assert_eq!(read("Foo.gitignore").as_deref(), Some(&b"a\nb\nc\n"[..]));
+        assert_eq!(read("Absent.gitignore"), None);
+
+        let merged = super::merge("a\nb\nc\n", "a\nB\nc\n", "a\nb\nc\nd\n").unwrap();
+        assert_eq!(merged.as_deref(), Some("a\nB\nc\nd\n"));
+        let conflict = super::merge("a\nb\nc\n", "a\nB\nc\n", "a\nX\nc\n").unwrap();
+        assert_eq!(conflict, None);
+    }
+
+    #[derive(Default)]
+    struct Recorder(std::sync::Mutex<Vec<(String, Event)>>);
+
+    impl Observer for Recorder {
+        fn on_event(&self, repository: &str, event: &Event) {
+            self.0
+                .lock()
+                .unwrap()
+                .push((repository.to_string(), event.clone()));
+        }
+    }
+
+    /// The counters of gix have to come out as events, the last of them at their totals, and
+    /// all of them under the name of the repository.
     #[test]
-    fn test_pull_updates_the_working_tree() {
+    fn test_prepare_reports_the_progress_of_the_clone() {
         let base = tempfile::tempdir().unwrap();
         let remote = base.path().join("remote");
-        let work = base.path().join("work");
         std::fs::create_dir_all(remote.join("Global")).unwrap();
-
         git(&remote, &["init", "-b", "main"]);
-        std::fs::write(remote.join("Foo.gitignore"), "v1\n").unwrap();
+        std::fs::write(remote.join("Foo.gitignore"), "foo\n").unwrap();
         std::fs::write(remote.join("Global/Bar.gitignore"), "bar\n").unwrap();
         commit(&remote, "v1", "2024-01-01T00:00:00+0000");
 
-        super::clone(remote.to_string_lossy(), &work).unwrap();
-        assert_eq!(
-            std::fs::read_to_string(work.join("Foo.gitignore")).unwrap(),
-            "v1\n"
-        );
-
-        // modify one boilerplate, drop another one, and add a third
-        std::fs::write(remote.join("Foo.gitignore"), "v2\n").unwrap();
-        std::fs::remove_file(remote.join("Global/Bar.gitignore")).unwrap();
-        std::fs::write(remote.join("Baz.gitignore"), "baz\n").unwrap();
-        commit(&remote, "v2", "2024-02-01T00:00:00+0000");
-
-        super::pull(&work, "origin", "main").unwrap();
-
-        assert_eq!(
-            std::fs::read_to_string(work.join("Foo.gitignore")).unwrap(),
-            "v2\n"
-        );
-        assert_eq!(
-            std::fs::read_to_string(work.join("Baz.gitignore")).unwrap(),
-            "baz\n"
-        );
-        assert!(!work.join("Global/Bar.gitignore").exists());
-        assert!(
-            !work.join("Global").exists(),
-            "emptied directory is left behind"
-        );
-        // the index has to follow along, or the repository looks dirty to the developer
-        assert_eq!(git(&work, &["status", "--short"]), "");
+        let recorder = Arc::new(Recorder::default());
+        let repo = Repository::new_with("local", &remote.to_string_lossy());
+        let progress = Reporter::new("local", recorder.clone());
+        repo.prepare_with(base.path(), &progress).unwrap();
+
+        let events = recorder.0.lock().unwrap();
+        assert!(events.iter().all(|(name, _)| name == "local"));
+        assert_eq!(events.first().map(|(_, e)| e), Some(&Event::Started));
+        assert_eq!(events.last().map(|(_, e)| e), Some(&Event::Finished));
+        let last_of = |phase| {
+            events.iter().rev().find_map(|(_, event)| match event {
+                Event::Progress {
+                    phase: p,
+                    done,
+                    total,
+                    ..
+                } if *p == phase => Some((*done, *total)),
+                _ => None,
+            })
+        };
+        // two blobs, a tree for Global, the root tree and the commit
+        assert_eq!(last_of(Phase::Receiving), Some((5, Some(5))));
+        assert_eq!(last_of(Phase::CheckingOut), Some((2, Some(2))));
     }
 
     // These clone into a temporary directory rather than into the repository. Nothing here
@@ -530,7 +1113,14 @@ mod tests {
     fn test_clone_https() {
         let dir = tempfile::tempdir().unwrap();
         let url = "https://github.com/github/gitignore.git";
-        super::clone(url, dir.path().join("gitignore-https")).unwrap();
+        super::clone(
+            url,
+            dir.path().join("gitignore-https"),
+            "origin",
+            None,
+            &Reporter::silent(),
+        )
+        .unwrap();
     }
 
     /// Ignored because it needs an SSH key the remote accepts, which CI has no way to hold.
@@ -540,6 +1130,13 @@ mod tests {
     fn test_clone_ssh() {
         let dir = tempfile::tempdir().unwrap();
         let url = "git@github.com:github/gitignore.git";
-        super::clone(url, dir.path().join("gitignore-ssh")).unwrap();
+        super::clone(
+            url,
+            dir.path().join("gitignore-ssh"),
+            "origin",
+            None,
+            &Reporter::silent(),
+        )
+        .unwrap();
     }
 }
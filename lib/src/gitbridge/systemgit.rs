@@ -2,23 +2,50 @@
 //! This module provides functions to interact with Git repositories
 //! using the system's Git command-line tool.
 //! It includes functions to clone repositories, pull updates, and retrieve commit hashes.
-use std::{path::Path, process::Command};
+use std::io::Read;
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::{
+    path::{Path, PathBuf},
+    process::{Command, Stdio},
+};
 
-use crate::repos::Boilerplate;
 use crate::Result;
+use crate::progress::{Event, Phase, Reporter};
+use crate::repos::{Boilerplate, Commit};
 
-/// Run `git pull {remote} {branch}` on the repository at the given path.
-pub fn pull(repo_path: &Path, remote: &str, branch: &str) -> Result<()> {
-    let args = ["pull", remote, branch];
-    log::info!("Executing: git {:?} on {repo_path:?}", args.join(" "));
-    let r = Command::new("git")
-        .args(args)
-        .current_dir(repo_path)
-        .output();
-    match r {
+/// Set by [`interrupt`]. A Ctrl-C reaches the git running at the time by itself, so this only
+/// has to keep the next one from starting.
+static INTERRUPTED: AtomicBool = AtomicBool::new(false);
+
+pub fn interrupt() {
+    INTERRUPTED.store(true, Ordering::Relaxed);
+}
+
+pub fn is_interrupted() -> bool {
+    INTERRUPTED.load(Ordering::Relaxed)
+}
+
+fn check_interrupted() -> Result<()> {
+    if is_interrupted() {
+        Err(crate::Error::Git("Interrupted".into()))
+    } else {
+        Ok(())
+    }
+}
+
+/// Runs `git {args}` in `dir` and returns what it printed to the standard output.
+fn git(dir: Option<&Path>, args: &[&str]) -> Result<String> {
+    check_interrupted()?;
+    log::info!("Executing: git {}", args.join(" "));
+    let mut command = Command::new("git");
+    command.args(args);
+    if let Some(dir) = dir {
+        command.current_dir(dir);
+    }
+    match command.output() {
         Ok(output) => {
             if output.status.success() {
-                Ok(())
+                Ok(String::from_utf8_lossy(&output.stdout).trim().to_string())
             } else {
                 let err_msg = String::from_utf8_lossy(&output.stderr);
                 Err(crate::Error::Git(format!("Git command failed: {err_msg}")))
@@ -28,22 +55,384 @@ pub fn pull(repo_path: &Path, remote: &str, branch: &str) -> Result<()> {
     }
 }
 
-/// Run `git clone {url} {dest_path}` to clone the repository.
-pub fn clone<S: AsRef<str>, P: AsRef<Path>>(url: S, dest_path: P) -> crate::Result<()> {
-    let dest_path = dest_path.as_ref().to_string_lossy().to_string();
-    let args = ["clone", url.as_ref(), dest_path.as_ref()];
+/// Runs `git {args}` in `dir`, which has to be given `--progress`, and tells `progress` what
+/// git reports on its standard error as it goes.
+///
+/// git redraws a progress line by ending it with a carriage return, so the standard error is
+/// split at both line endings. What is not progress, such as the reason of a failure, is kept
+/// for the error message.
+fn git_with_progress(dir: Option<&Path>, args: &[&str], progress: &Reporter) -> Result<()> {
+    check_interrupted()?;
     log::info!("Executing: git {}", args.join(" "));
-    let r = Command::new("git").args(args).output();
-    match r {
-        Ok(output) => {
-            if output.status.success() {
-                Ok(())
+    let mut command = Command::new("git");
+    command
+        .args(args)
+        .stdin(Stdio::null())
+        .stdout(Stdio::null())
+        .stderr(Stdio::piped());
+    if let Some(dir) = dir {
+        command.current_dir(dir);
+    }
+    let mut child = command.spawn().map_err(crate::Error::IO)?;
+    let mut stderr = child.stderr.take().expect("the standard error is piped");
+    let mut messages = vec![];
+    let mut line = vec![];
+    let mut buffer = [0u8; 4096];
+    loop {
+        let n = stderr.read(&mut buffer).map_err(crate::Error::IO)?;
+        if n == 0 {
+            break;
+        }
+        for &byte in &buffer[..n] {
+            if byte == b'\r' || byte == b'\n' {
+                let text = String::from_utf8_lossy(&line).to_string();
+                match parse_progress(&text) {
+                    Some(event) => progress.report(event),
+                    None if !text.trim().is_empty() => messages.push(text),
+                    None => {}
+                }
+                line.clear();
             } else {
-                let err_msg = String::from_utf8_lossy(&output.stderr);
-                Err(crate::Error::Git(format!("Git command failed: {err_msg}")))
+                line.push(byte);
             }
         }
-        Err(e) => Err(crate::Error::IO(e)),
+    }
+    messages.push(String::from_utf8_lossy(&line).to_string());
+    let status = child.wait().map_err(crate::Error::IO)?;
+    if status.success() {
+        Ok(())
+    } else {
+        let err_msg = messages.join("\n");
+        Err(crate::Error::Git(format!(
+            "Git command failed: {}",
+            err_msg.trim()
+        )))
+    }
+}
+
+/// Parses a progress line of git, such as `Receiving objects:  45% (450/1000), 1.20 MiB |
+/// 2.00 MiB/s`, into an [`Event`]. Returns `None` for any other line.
+fn parse_progress(line: &str) -> Option<Event> {
+    let line = line.trim_start_matches("remote:").trim();
+    let (title, rest) = line.split_once(':')?;
+    let phase = match title {
+        "Counting objects" => Phase::Counting,
+        "Compressing objects" => Phase::Compressing,
+        "Receiving objects" => Phase::Receiving,
+        "Resolving deltas" => Phase::Resolving,
+        "Updating files" => Phase::CheckingOut,
+        _ => return None,
+    };
+    let rest = rest.trim();
+    // A count whose total is known comes as `50% (1/2)`, and one whose total is not as `12`.
+    let (done, total) = match rest.split_once('(') {
+        Some((_, count)) => {
+            let (count, _) = count.split_once(')')?;
+            let (done, total) = count.split_once('/')?;
+            (done.parse().ok()?, Some(total.parse().ok()?))
+        }
+        None => {
+            let done = rest.split(|c: char| !c.is_ascii_digit()).next()?;
+            (done.parse().ok()?, None)
+        }
+    };
+    let bytes = rest
+        .split_once("), ")
+        .and_then(|(_, received)| received.split(['|', ',']).next())
+        .and_then(parse_size);
+    Some(Event::Progress {
+        phase,
+        done,
+        total,
+        bytes,
+    })
+}
+
+/// Parses a size as git prints it, such as `420 bytes` or `1.20 MiB`.
+fn parse_size(size: &str) -> Option<u64> {
+    let (number, unit) = size.trim().split_once(' ')?;
+    let number = number.parse::<f64>().ok()?;
+    let unit: u64 = match unit {
+        "bytes" | "byte" => 1,
+        "KiB" => 1 << 10,
+        "MiB" => 1 << 20,
+        "GiB" => 1 << 30,
+        "TiB" => 1 << 40,
+        _ => return None,
+    };
+    Some((number * unit as f64) as u64)
+}
+
+/// Run `git fetch {remote}` and merge `{remote}/{branch}` into `branch`, which is what
+/// `git pull {remote} {branch}` does once `branch` is the one checked out.
+pub fn pull(repo_path: &Path, remote: &str, branch: &str, progress: &Reporter) -> Result<()> {
+    git_with_progress(Some(repo_path), &["fetch", "--progress", remote], progress)?;
+    let tracking = format!("{remote}/{branch}");
+    if head_branch(repo_path).ok().as_deref() != Some(branch) {
+        let local = format!("refs/heads/{branch}");
+        if git(
+            Some(repo_path),
+            &["rev-parse", "--verify", "--quiet", &local],
+        )
+        .is_ok()
+        {
+            git(Some(repo_path), &["switch", branch])?;
+        } else {
+            git(
+                Some(repo_path),
+                &["switch", "-c", branch, "--track", &tracking],
+            )?;
+        }
+    }
+    git(Some(repo_path), &["merge", &tracking]).map(|_| ())
+}
+
+/// Run `git clone --origin {remote} [--branch {branch}] {url} {dest_path}` to clone the
+/// repository, and returns the branch checked out.
+pub fn clone<S: AsRef<str>, P: AsRef<Path>>(
+    url: S,
+    dest_path: P,
+    remote: &str,
+    branch: Option<&str>,
+    progress: &Reporter,
+) -> crate::Result<String> {
+    let dest_path = dest_path.as_ref();
+    let dest = dest_path.to_string_lossy().to_string();
+    let mut args = vec!["clone", "--progress", "--origin", remote];
+    if let Some(branch) = branch {
+        args.extend(["--branch", branch]);
+    }
+    args.extend([url.as_ref(), dest.as_str()]);
+    git_with_progress(None, &args, progress)?;
+    current_branch(dest_path)
+}
+
+/// Run `git symbolic-ref --short HEAD` to find the branch checked out.
+fn head_branch(repo_path: &Path) -> Result<String> {
+    git(Some(repo_path), &["symbolic-ref", "--short", "HEAD"])
+}
+
+/// Returns the branch checked out, or the only local branch when `HEAD` is detached, which is
+/// what a clone pinned to a revision looks like.
+pub fn current_branch(repo_path: &Path) -> Result<String> {
+    head_branch(repo_path).or_else(|e| {
+        let branches = git(
+            Some(repo_path),
+            &["for-each-ref", "--format=%(refname:short)", "refs/heads"],
+        )?;
+        match branches.lines().collect::<Vec<_>>().as_slice() {
+            [branch] => Ok(branch.to_string()),
+            _ => Err(e),
+        }
+    })
+}
+
+/// Run `git fetch --tags {remote}`.
+pub fn fetch(repo_path: &Path, remote: &str, progress: &Reporter) -> Result<()> {
+    git_with_progress(
+        Some(repo_path),
+        &["fetch", "--progress", "--tags", remote],
+        progress,
+    )
+}
+
+/// Run `git remote set-url {remote} {url}`.
+pub fn set_remote_url(repo_path: &Path, remote: &str, url: &str) -> Result<()> {
+    git(Some(repo_path), &["remote", "set-url", remote, url]).map(|_| ())
+}
+
+/// Run `git for-each-ref refs/tags` to list the tags with the commits they point at.
+pub fn tags(repo_path: &Path) -> Result<Vec<(String, Vec<u8>)>> {
+    let output = git(
+        Some(repo_path),
+        &[
+            "for-each-ref",
+            "--format=%(refname:strip=2) %(objectname) %(*objectname)",
+            "refs/tags",
+        ],
+    )?;
+    let mut tags = vec![];
+    for line in output.lines() {
+        let mut fields = line.split_whitespace();
+        let (Some(name), Some(object)) = (fields.next(), fields.next()) else {
+            continue;
+        };
+        // `%(*objectname)` is the commit an annotated tag points at, and empty otherwise.
+        let commit = fields.next().unwrap_or(object);
+        let commit = hex::decode(commit)
+            .map_err(|e| crate::Error::Git(format!("Failed to decode hash: {e}")))?;
+        tags.push((name.to_string(), commit));
+    }
+    Ok(tags)
+}
+
+/// Run `git rev-parse --verify {rev}^{commit}` to find the commit `rev` abbreviates.
+pub fn find_commit(repo_path: &Path, rev: &str) -> Result<Option<Vec<u8>>> {
+    let rev = format!("{rev}^{{commit}}");
+    match git(Some(repo_path), &["rev-parse", "--verify", "--quiet", &rev]) {
+        Ok(hash) => hex::decode(hash)
+            .map(Some)
+            .map_err(|e| crate::Error::Git(format!("Failed to decode hash: {e}"))),
+        Err(_) => Ok(None),
+    }
+}
+
+/// Run `git rev-parse HEAD`.
+pub fn head(repo_path: &Path) -> Result<Vec<u8>> {
+    let hash = git(Some(repo_path), &["rev-parse", "HEAD"])?;
+    hex::decode(hash).map_err(|e| crate::Error::Git(format!("Failed to decode hash: {e}")))
+}
+
+/// Run `git merge-base --is-ancestor {ancestor} {descendant}`, which answers with its exit
+/// status alone.
+pub fn is_ancestor(repo_path: &Path, ancestor: &[u8], descendant: &[u8]) -> Result<bool> {
+    let (ancestor, descendant) = (hex::encode(ancestor), hex::encode(descendant));
+    Ok(git(
+        Some(repo_path),
+        &["merge-base", "--is-ancestor", &ancestor, &descendant],
+    )
+    .is_ok())
+}
+
+/// Run `git checkout --detach {commit}`.
+pub fn checkout(repo_path: &Path, commit: &[u8]) -> Result<()> {
+    let commit = hex::encode(commit);
+    git(
+        Some(repo_path),
+        &[
+            "-c",
+            "advice.detachedHead=false",
+            "checkout",
+            "--detach",
+            &commit,
+        ],
+    )
+    .map(|_| ())
+}
+
+/// Run `git show {commit}:{path}`, asking `git cat-file -e` first whether the commit has the
+/// path, since `git show` fails the same way for a missing path and a missing commit.
+pub fn read_at(repo_path: &Path, commit: &[u8], path: &Path) -> Result<Option<Vec<u8>>> {
+    let commit = hex::encode(commit);
+    let spec = format!("{commit}:{}", path.to_string_lossy().replace('\\', "/"));
+    if git(Some(repo_path), &["cat-file", "-e", &spec]).is_err() {
+        // only a commit that is there is missing the path; a missing commit is an error
+        let commit = format!("{commit}^{{commit}}");
+        git(Some(repo_path), &["cat-file", "-e", &commit])?;
+        return Ok(None);
+    }
+    log::info!("Executing: git show {spec}");
+    let output = Command::new("git")
+        .args(["show", &spec])
+        .current_dir(repo_path)
+        .output()
+        .map_err(crate::Error::IO)?;
+    if output.status.success() {
+        Ok(Some(output.stdout))
+    } else {
+        let err_msg = String::from_utf8_lossy(&output.stderr);
+        Err(crate::Error::Git(format!("Git command failed: {err_msg}")))
+    }
+}
+
+/// Run `git log --format=%H%x09%s {since}..HEAD -- {path}`, a line per commit with its hash and
+/// the first line of its message apart by a tab.
+pub fn log(repo_path: &Path, since: &[u8], path: &Path) -> Result<Vec<Commit>> {
+    let range = format!("{}..HEAD", hex::encode(since));
+    let path = path.to_string_lossy().replace('\\', "/");
+    let out = git(
+        Some(repo_path),
+        &["log", "--format=%H%x09%s", &range, "--", &path],
+    )?;
+    out.lines()
+        .filter(|line| !line.is_empty())
+        .map(|line| {
+            let (id, summary) = line.split_once('\t').unwrap_or((line, ""));
+            let id = hex::decode(id)
+                .map_err(|e| crate::Error::Git(format!("Failed to decode hash: {e}")))?;
+            Ok(Commit {
+                id,
+                summary: summary.to_string(),
+            })
+        })
+        .collect()
+}
+
+/// Run `git diff-tree -r -M100% --diff-filter=R --name-status` on the newest commit since
+/// `since` that changed `path`, which for a path that is gone is the one that removed it. Each
+/// rename comes as `R100`, the old path and the new one, apart by tabs.
+pub fn renamed(repo_path: &Path, since: &[u8], path: &Path) -> Result<Option<PathBuf>> {
+    let Some(removal) = log(repo_path, since, path)?.into_iter().next() else {
+        return Ok(None);
+    };
+    let removal = hex::encode(removal.id);
+    let parent = format!("{removal}^");
+    let path = path.to_string_lossy().replace('\\', "/");
+    let out = git(
+        Some(repo_path),
+        &[
+            "diff-tree",
+            "-r",
+            "-M100%",
+            "--diff-filter=R",
+            "--name-status",
+            &parent,
+            &removal,
+        ],
+    )?;
+    Ok(out.lines().find_map(|line| {
+        let mut fields = line.split('\t').skip(1);
+        match (fields.next(), fields.next()) {
+            (Some(from), Some(to)) if from == path => Some(PathBuf::from(to)),
+            _ => None,
+        }
+    }))
+}
+
+/// Run `git merge-file -p {current} {ancestor} {other}` on copies of the three in a directory
+/// of their own, which is removed afterwards. The exit status is the number of conflicts, and
+/// negative on an error.
+pub fn merge(ancestor: &str, current: &str, other: &str) -> Result<Option<String>> {
+    static COUNT: AtomicUsize = AtomicUsize::new(0);
+    let dir = std::env::temp_dir().join(format!(
+        "gixor-merge-{}-{}",
+        std::process::id(),
+        COUNT.fetch_add(1, Ordering::Relaxed)
+    ));
+    let result = merge_in(&dir, ancestor, current, other);
+    if let Err(e) = std::fs::remove_dir_all(&dir) {
+        log::warn!("{}: failed to remove: {e}", dir.display());
+    }
+    result
+}
+
+fn merge_in(dir: &Path, ancestor: &str, current: &str, other: &str) -> Result<Option<String>> {
+    std::fs::create_dir_all(dir).map_err(crate::Error::IO)?;
+    let files = [
+        ("current", current),
+        ("ancestor", ancestor),
+        ("other", other),
+    ]
+    .into_iter()
+    .map(|(name, content)| {
+        let path = dir.join(name);
+        std::fs::write(&path, content).map(|_| path)
+    })
+    .collect::<std::io::Result<Vec<_>>>()
+    .map_err(crate::Error::IO)?;
+    log::info!("Executing: git merge-file -p current ancestor other");
+    let output = Command::new("git")
+        .args(["merge-file", "-p", "--quiet"])
+        .args(&files)
+        .output()
+        .map_err(crate::Error::IO)?;
+    match output.status.code() {
+        Some(0) => Ok(Some(String::from_utf8_lossy(&output.stdout).to_string())),
+        Some(1..=127) => Ok(None),
+        _ => {
+            let err_msg = String::from_utf8_lossy(&output.stderr);
+            Err(crate::Error::Git(format!("Git command failed: {err_msg}")))
+        }
     }
 }
 
@@ -79,3 +468,69 @@ pub fn hash<P: AsRef<Path>>(boilerplate: &Boilerplate, base_path: P) -> Result<V
         Err(e) => Err(crate::Error::IO(e)),
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_merge() {
+        let merged = merge("a\nb\nc\n", "a\nB\nc\n", "a\nb\nc\nd\n").unwrap();
+        assert_eq!(merged.as_deref(), Some("a\nB\nc\nd\n"));
+        assert_eq!(merge("a\nb\nc\n", "a\nB\nc\n", "a\nX\nc\n").unwrap(), None);
+    }
+
+    #[test]
+    fn test_parse_progress() {
+        assert_eq!(
+            parse_progress("remote: Counting objects:  50% (1/2)"),
+            Some(Event::Progress {
+                phase: Phase::Counting,
+                done: 1,
+                total: Some(2),
+                bytes: None,
+            })
+        );
+        assert_eq!(
+            parse_progress("Receiving objects:  45% (450/1000), 1.50 MiB | 2.00 MiB/s"),
+            Some(Event::Progress {
+                phase: Phase::Receiving,
+                done: 450,
+                total: Some(1000),
+                bytes: Some(3 << 19),
+            })
+        );
+        assert_eq!(
+            parse_progress("Receiving objects: 100% (5/5), 420 bytes | 420.00 KiB/s, done."),
+            Some(Event::Progress {
+                phase: Phase::Receiving,
+                done: 5,
+                total: Some(5),
+                bytes: Some(420),
+            })
+        );
+        assert_eq!(
+            parse_progress("remote: Counting objects: 12"),
+            Some(Event::Progress {
+                phase: Phase::Counting,
+                done: 12,
+                total: None,
+                bytes: None,
+            })
+        );
+        assert!(matches!(
+            parse_progress("Updating files: 100% (3/3), done."),
+            Some(Event::Progress {
+                phase: Phase::CheckingOut,
+                done: 3,
+                ..
+            })
+        ));
+        assert_eq!(parse_progress("Cloning into 'gitignore'..."), None);
+        assert_eq!(
+            parse_progress("remote: Total 5 (delta 0), reused 0 (delta 0)"),
+            None
+        );
+        assert_eq!(parse_progress("fatal: repository 'x' does not exist"), None);
+    }
+}
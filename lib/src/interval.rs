@@ -0,0 +1,108 @@
+//! The `update-interval` of the configuration: how old a fetch may get before the repository is
+//! fetched again.
+//!
+//! The interval is written for people, as a number with a unit, such as `30m`, `12h` or `7d`.
+//! `0` fetches on every run, which is how gixor behaved before the interval existed.
+use std::time::Duration;
+
+use serde::{Deserialize, Deserializer, Serializer};
+
+use crate::{Error, Result};
+
+/// The interval for a configuration that gives none. A day keeps the boilerplates about as
+/// fresh as anyone needs them, while sparing every command a round trip to every remote.
+pub(crate) const DEFAULT: Duration = Duration::from_secs(24 * 60 * 60);
+
+const UNITS: [(&str, u64); 5] = [
+    ("w", 7 * 24 * 60 * 60),
+    ("d", 24 * 60 * 60),
+    ("h", 60 * 60),
+    ("m", 60),
+    ("s", 1),
+];
+
+/// Parses an interval such as `12h`. A number with no unit counts seconds.
+pub(crate) fn parse(s: &str) -> Result<Duration> {
+    let s = s.trim();
+    let split = s.find(|c: char| !c.is_ascii_digit()).unwrap_or(s.len());
+    let (number, unit) = s.split_at(split);
+    let number = number
+        .parse::<u64>()
+        .map_err(|e| Error::Fatal(format!("{s}: invalid update interval: {e}")))?;
+    let seconds = match unit {
+        "" => 1,
+        unit => UNITS
+            .iter()
+            .find(|(name, _)| *name == unit)
+            .map(|(_, seconds)| *seconds)
+            .ok_or_else(|| {
+                Error::Fatal(format!(
+                    "{s}: invalid update interval: the unit has to be one of w, d, h, m, or s"
+                ))
+            })?,
+    };
+    number
+        .checked_mul(seconds)
+        .map(Duration::from_secs)
+        .ok_or_else(|| Error::Fatal(format!("{s}: invalid update interval: out of range")))
+}
+
+/// Formats the interval in the largest unit that divides it, the way it would be written.
+pub(crate) fn format(interval: &Duration) -> String {
+    let secs = interval.as_secs();
+    if secs == 0 {
+        return "0".to_string();
+    }
+    UNITS
+        .iter()
+        .find(|(_, seconds)| secs.is_multiple_of(*seconds))
+        .map(|(unit, seconds)| format!("{}{unit}", secs / seconds))
+        .unwrap_or_else(|| format!("{secs}s"))
+}
+
+pub(crate) fn serialize<S: Serializer>(
+    interval: &Option<Duration>,
+    serializer: S,
+) -> core::result::Result<S::Ok, S::Error> {
+    match interval {
+        Some(interval) => serializer.serialize_str(&format(interval)),
+        None => serializer.serialize_none(),
+    }
+}
+
+pub(crate) fn deserialize<'de, D: Deserializer<'de>>(
+    deserializer: D,
+) -> core::result::Result<Option<Duration>, D::Error> {
+    match Option::<String>::deserialize(deserializer)? {
+        Some(s) => parse(&s).map(Some).map_err(serde::de::Error::custom),
+        None => Ok(None),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse() {
+        assert_eq!(parse("0").unwrap(), Duration::ZERO);
+        assert_eq!(parse("90").unwrap(), Duration::from_secs(90));
+        assert_eq!(parse("30m").unwrap(), Duration::from_secs(30 * 60));
+        assert_eq!(parse("12h").unwrap(), Duration::from_secs(12 * 60 * 60));
+        assert_eq!(parse("7d").unwrap(), Duration::from_secs(7 * 24 * 60 * 60));
+        assert_eq!(parse("2w").unwrap(), Duration::from_secs(14 * 24 * 60 * 60));
+        assert!(parse("12x").is_err());
+        assert!(parse("h").is_err());
+        assert!(parse("").is_err());
+        assert!(parse("99999999999999999w").is_err());
+        assert!(parse("99999999999999999999").is_err());
+    }
+
+    #[test]
+    fn test_format_round_trips() {
+        for s in ["0", "45s", "30m", "12h", "1d", "36h", "2w"] {
+            assert_eq!(format(&parse(s).unwrap()), s);
+        }
+        assert_eq!(format(&parse("14d").unwrap()), "2w");
+    }
+}
@@ -11,7 +11,7 @@
 //!
 //! // load configuration file and build Gixor object.
 //! let gixor = GixorFactory::load("testdata/config.json").unwrap();
-//! gixor.prepare(true).unwrap(); // clone or update all repositories, if needed.
+//! gixor.prepare(true).into_result().unwrap(); // clone or update all repositories, if needed.
 //! // create vec of Name instance.
 //! let names = Name::parse_all(vec!["rust", "macos", "linux", "windows"]);
 //! // dump the boilerplate of rust, macos, linux, and windows into stdout.
@@ -30,6 +30,7 @@
 use std::{
     fmt::Display,
     path::{Path, PathBuf},
+    sync::Arc,
 };
 
 use serde::{Deserialize, Deserializer, Serialize};
@@ -44,8 +45,14 @@ compile_error!(
 compile_error!("The features `local` and `embedded` cannot be enabled at the same time.");
 
 pub mod aliases;
+pub mod diff;
+pub mod document;
 #[cfg(feature = "local")]
 pub mod gitbridge;
+mod interval;
+pub mod lock;
+mod permalink;
+pub mod progress;
 pub mod repos;
 mod source;
 
@@ -61,8 +68,15 @@ pub enum Error {
     Alias(String),
     /// Error when the alias is not found.
     AliasNotFound(String),
-    /// Error when the boilerplate is not found.
-    BoilerplateNotFound(String),
+    /// Error when the boilerplate name is found in several repositories of the same priority,
+    /// with the qualified names of the candidates, such as `default/Rust`.
+    Ambiguous(String, Vec<String>),
+    /// Error when the boilerplate is not found, with the names of boilerplates and aliases
+    /// close to it, which may be empty.
+    BoilerplateNotFound(String, Vec<String>),
+    /// Error when the edits by hand in the block of the boilerplate conflict with the changes of
+    /// the boilerplate upstream, as [`document::EditPolicy::Merge`] finds.
+    Conflict(String),
     /// Error when the file is not found.
     FileNotFound(PathBuf),
     /// Fatal error.
@@ -73,6 +87,18 @@ pub enum Error {
     IO(std::io::Error),
     /// JSON error.
     Json(serde_json::Error),
+    /// Error when the boilerplates at the commits of the lock are not the content it records,
+    /// or lack the edits it records merged into them, as [`Gixor::sync`] finds.
+    LockMismatch(Vec<String>),
+    /// Error when the blocks of the boilerplates were edited by hand since they were dumped,
+    /// and [`document::EditPolicy::Refuse`] keeps the dump from overwriting them.
+    Modified(Vec<String>),
+    /// Error when the gitignore is not what dumping would make of it, as [`Check::into_result`]
+    /// finds.
+    Mismatch(PathBuf),
+    /// Error when the boilerplates changed upstream since they were dumped, as
+    /// [`Outdated::into_result`] finds.
+    Outdated(Vec<String>),
     /// Error when the repository is not found.
     RepositoryNotFound(String),
 }
@@ -119,24 +145,52 @@ impl Display for Error {
             }
             Alias(msg) => write!(f, "{msg}"),
             AliasNotFound(name) => write!(f, "{name}: alias not found"),
-            BoilerplateNotFound(name) => write!(f, "{name}: boilerplate not found"),
+            Ambiguous(name, candidates) => write!(
+                f,
+                "{name}: ambiguous boilerplate name, qualify it as one of {}",
+                candidates.join(", ")
+            ),
+            BoilerplateNotFound(name, suggestions) if suggestions.is_empty() => {
+                write!(f, "{name}: boilerplate not found")
+            }
+            BoilerplateNotFound(name, suggestions) => write!(
+                f,
+                "{name}: boilerplate not found, did you mean {}?",
+                suggestions.join(", ")
+            ),
+            Conflict(name) => write!(
+                f,
+                "{name}: the edits by hand conflict with the changes upstream"
+            ),
             FileNotFound(path) => write!(f, "{}: file not found", path.display()),
             Git(e) => write!(f, "Git error: {e}"),
             IO(e) => write!(f, "IO error: {e}"),
             Json(e) => write!(f, "JSON error: {e}"),
+            LockMismatch(names) => {
+                write!(f, "{}: not the content the lock records", names.join(", "))
+            }
             Fatal(msg) => write!(f, "Fatal error: {msg}"),
+            Mismatch(path) => write!(f, "{}: differs from what gixor dumps", path.display()),
+            Outdated(names) => write!(f, "{}: changed upstream since dumped", names.join(", ")),
+            Modified(names) => write!(f, "{}: edited by hand since dumped", names.join(", ")),
             RepositoryNotFound(name) => write!(f, "{name}: repository not found"),
         }
     }
 }
 
 mod routine;
+mod shorthand;
+mod suggest;
 
 /// Finds the entries of `.gitignore` file in the given path.
 /// The given path should be a directory containing a `.gitignore` file or
 /// a regular file which is treats as a `.gitignore` file.
 /// If the `.gitignore` file is not found, returns [`Error::FileNotFound`] error.
-pub fn entries<P: AsRef<Path>>(path: P) -> Result<Vec<String>> {
+///
+/// A boilerplate dumped by this version is read back with the repository it came from, such
+/// as `team/Rust`, so that dumping again takes it from the same repository. One written by an
+/// older version only tells its stem, or its path, from the line of its source.
+pub fn entries<P: AsRef<Path>>(path: P) -> Result<Vec<Entry>> {
     log::info!("Find current entries from {}", path.as_ref().display());
     routine::entries(path)
 }
@@ -145,7 +199,8 @@ pub fn entries<P: AsRef<Path>>(path: P) -> Result<Vec<String>> {
 ///
 /// ## Returns
 ///
-/// If the given `repository_names` is empty, all repositories managed by `gixor` are returned.
+/// If the given `repository_names` is empty, all enabled repositories managed by `gixor` are
+/// returned, in the order they are searched for a boilerplate.
 /// Otherwise, the repositories matched with the given names are returned.
 ///
 /// ## Errors
@@ -167,13 +222,18 @@ pub fn find_target_repositories<S: AsRef<str>>(
 
 /// The name of the boilerplate which contains the repository name and the boilerplate name.
 /// The repository name is [`repos::Repository::name`].
-/// The boilerplate name is the file stem of the boilerplate (gitignore) file.
+/// The boilerplate name is the file stem of the boilerplate (gitignore) file, such as `macOS`,
+/// or its path from the repository root without the extension, such as `Global/macOS`.
 #[derive(Debug, Clone)]
 pub struct Name {
     /// The repository name for of the boilerplate. If `None`, the repository name do not care.
     pub repository_name: Option<String>,
     /// The boilerplate name.
     pub boilerplate_name: String,
+    /// The revision to take the boilerplate at, written after `@`, such as `Rust@v2.0`: a tag,
+    /// a commit, or a semver range over the tags, as [`repos::Repository::rev`] takes. If
+    /// `None`, the boilerplate is taken as the clone has it.
+    pub revision: Option<String>,
 }
 
 impl Serialize for Name {
@@ -198,8 +258,12 @@ impl<'de> Deserialize<'de> for Name {
 impl Display for Name {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match &self.repository_name {
-            Some(repo) => write!(f, "{}/{}", repo, self.boilerplate_name),
-            None => write!(f, "{}", self.boilerplate_name),
+            Some(repo) => write!(f, "{}/{}", repo, self.boilerplate_name)?,
+            None => write!(f, "{}", self.boilerplate_name)?,
+        }
+        match &self.revision {
+            Some(revision) => write!(f, "@{revision}"),
+            None => Ok(()),
         }
     }
 }
@@ -210,6 +274,363 @@ impl From<&str> for Name {
     }
 }
 
+/// A boilerplate listed in a `.gitignore` file, as [`entries`] reads it back.
+#[derive(Debug, Clone)]
+pub struct Entry {
+    /// The name of the boilerplate, qualified with the repository when the `.gitignore`
+    /// records it.
+    pub name: Name,
+    /// The commit the boilerplate was dumped at, in lowercase hex, when its permalink
+    /// records it.
+    pub commit: Option<String>,
+}
+
+impl Display for Entry {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.name)
+    }
+}
+
+/// A gitignore compared with what dumping would make of it, as [`Gixor::check`] and
+/// [`Gixor::diff`] find.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Check {
+    /// The path of the gitignore.
+    pub path: PathBuf,
+    /// The content of the gitignore.
+    pub actual: String,
+    /// The content dumping would give it.
+    pub expected: String,
+}
+
+impl Check {
+    /// Returns `true` if the gitignore is what dumping would make of it.
+    pub fn is_up_to_date(&self) -> bool {
+        self.actual == self.expected
+    }
+
+    /// Returns the hunks that turn the gitignore into what dumping would make of it.
+    pub fn hunks(&self) -> Vec<diff::Hunk> {
+        diff::hunks(&self.actual, &self.expected)
+    }
+
+    /// Returns the hunks grouped by the part of the gitignore they change: a block of a
+    /// boilerplate, or the text of the user, in the order their first changes come in.
+    ///
+    /// Two blocks are kept apart however close their changes are, so that each change tells
+    /// which boilerplate it is about. The hunks of a block come together even when those of
+    /// another one fall between them, as they do when a block gives way to another with much
+    /// the same content.
+    pub fn changes(&self) -> Vec<Change> {
+        let owners = |text: &str| {
+            document::GitignoreDocument::parse(text)
+                .owners()
+                .into_iter()
+                .map(|owner| owner.map(|name| name.to_string()))
+                .collect::<Vec<_>>()
+        };
+        let (old, new) = (owners(&self.actual), owners(&self.expected));
+        let hunks = diff::grouped_hunks(
+            &self.actual,
+            &self.expected,
+            |i| old.get(i).cloned().flatten(),
+            |j| new.get(j).cloned().flatten(),
+        );
+        let mut changes: Vec<(Option<String>, Vec<diff::Hunk>)> = vec![];
+        for (owner, hunk) in hunks {
+            match changes.iter_mut().find(|(other, _)| *other == owner) {
+                Some((_, hunks)) => hunks.push(hunk),
+                None => changes.push((owner, vec![hunk])),
+            }
+        }
+        changes
+            .into_iter()
+            .map(|(owner, hunks)| {
+                let kind = match &owner {
+                    Some(_) if !old.contains(&owner) => ChangeKind::Added,
+                    Some(_) if !new.contains(&owner) => ChangeKind::Removed,
+                    _ => ChangeKind::Updated,
+                };
+                Change {
+                    name: owner.map(Name::parse),
+                    kind,
+                    hunks,
+                }
+            })
+            .collect()
+    }
+
+    /// Renders the differences as a unified diff, or nothing when there are none.
+    pub fn diff(&self) -> String {
+        let path = self.path.display().to_string();
+        diff::unified(
+            &self.actual,
+            &self.expected,
+            &path,
+            &format!("{path} (dumped)"),
+        )
+    }
+
+    /// Treats a difference as an error, [`Error::Mismatch`].
+    pub fn into_result(self) -> Result<()> {
+        if self.is_up_to_date() {
+            Ok(())
+        } else {
+            Err(Error::Mismatch(self.path))
+        }
+    }
+}
+
+/// What becomes of a part of a gitignore, as [`Check::changes`] tells.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ChangeKind {
+    /// The block of the boilerplate is new.
+    Added,
+    /// The block of the boilerplate goes away.
+    Removed,
+    /// The block of the boilerplate, or the text of the user, changes.
+    Updated,
+}
+
+impl Display for ChangeKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ChangeKind::Added => write!(f, "added"),
+            ChangeKind::Removed => write!(f, "removed"),
+            ChangeKind::Updated => write!(f, "updated"),
+        }
+    }
+}
+
+/// The changes to a part of a gitignore: a block of a boilerplate, or the text of the user.
+#[derive(Debug, Clone)]
+pub struct Change {
+    /// The boilerplate of the block, or `None` for the text of the user.
+    pub name: Option<Name>,
+    /// What becomes of the part.
+    pub kind: ChangeKind,
+    /// The hunks that change the part, in order.
+    pub hunks: Vec<diff::Hunk>,
+}
+
+/// How a boilerplate of a gitignore stands against its repository now.
+#[derive(Debug, Clone, Serialize)]
+#[serde(tag = "status", rename_all = "kebab-case")]
+pub enum Freshness {
+    /// The boilerplate has not changed since the commit its block records.
+    UpToDate,
+    /// The boilerplate changed since then, in these commits, the newest first.
+    Outdated { commits: Vec<repos::Commit> },
+    /// The boilerplate was moved to another path since then, keeping its content, and goes by
+    /// this name now.
+    Renamed { to: Name },
+    /// The boilerplate was removed from its repository since then.
+    Removed,
+    /// There is no telling, such as for a block recording no commit, for the reason given.
+    Unknown { reason: String },
+}
+
+/// A boilerplate of a gitignore, with how it stands against its repository.
+#[derive(Debug, Clone, Serialize)]
+pub struct Lag {
+    /// The name of the boilerplate, as [`entries`] reads it back.
+    pub name: Name,
+    /// The commit the boilerplate was dumped at, in lowercase hex, when its block records it.
+    pub commit: Option<String>,
+    /// How the boilerplate stands against its repository.
+    #[serde(flatten)]
+    pub freshness: Freshness,
+}
+
+impl Lag {
+    /// Returns `true` if the boilerplate changed upstream since it was dumped. One there is no
+    /// telling about is not.
+    pub fn is_behind(&self) -> bool {
+        !matches!(
+            self.freshness,
+            Freshness::UpToDate | Freshness::Unknown { .. }
+        )
+    }
+}
+
+impl Display for Lag {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match &self.freshness {
+            Freshness::UpToDate => write!(f, "{}: up to date", self.name),
+            Freshness::Outdated { commits } => {
+                let plural = if commits.len() == 1 { "" } else { "s" };
+                write!(f, "{}: {} commit{plural} behind", self.name, commits.len())?;
+                for commit in commits {
+                    write!(f, "\n    {commit}")?;
+                }
+                Ok(())
+            }
+            Freshness::Renamed { to } => write!(f, "{}: renamed to {to}", self.name),
+            Freshness::Removed => write!(f, "{}: removed upstream", self.name),
+            Freshness::Unknown { reason } => write!(f, "{}: unknown, {reason}", self.name),
+        }
+    }
+}
+
+/// The boilerplates of a gitignore compared with their repositories, as [`Gixor::outdated`]
+/// finds.
+#[derive(Debug, Clone, Serialize)]
+pub struct Outdated {
+    /// The path of the gitignore.
+    pub path: PathBuf,
+    /// The boilerplates in the order of the gitignore.
+    pub boilerplates: Vec<Lag>,
+}
+
+impl Outdated {
+    /// Returns `true` if no boilerplate changed upstream since it was dumped.
+    pub fn is_up_to_date(&self) -> bool {
+        !self.boilerplates.iter().any(Lag::is_behind)
+    }
+
+    /// Treats the boilerplates that changed upstream as an error, [`Error::Outdated`].
+    pub fn into_result(self) -> Result<()> {
+        let names = self
+            .boilerplates
+            .iter()
+            .filter(|lag| lag.is_behind())
+            .map(|lag| lag.name.to_string())
+            .collect::<Vec<_>>();
+        if names.is_empty() {
+            Ok(())
+        } else {
+            Err(Error::Outdated(names))
+        }
+    }
+}
+
+impl Display for Outdated {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        for (i, lag) in self.boilerplates.iter().enumerate() {
+            if i > 0 {
+                writeln!(f)?;
+            }
+            write!(f, "{lag}")?;
+        }
+        Ok(())
+    }
+}
+
+/// A boilerplate whose block a dump rewrote, with the commits its block recorded before and
+/// records after, when they do.
+#[derive(Debug, Clone)]
+pub struct Update {
+    /// The name of the boilerplate.
+    pub name: Name,
+    /// The commit the block recorded before the dump, in lowercase hex.
+    pub from: Option<String>,
+    /// The commit the block records after the dump, in lowercase hex.
+    pub to: Option<String>,
+}
+
+impl Display for Update {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let short = |commit: &Option<String>| match commit {
+            Some(commit) => commit.chars().take(7).collect(),
+            None => String::from("?"),
+        };
+        if self.from == self.to {
+            write!(f, "{}", self.name)
+        } else {
+            write!(
+                f,
+                "{} ({} -> {})",
+                self.name,
+                short(&self.from),
+                short(&self.to)
+            )
+        }
+    }
+}
+
+/// A boilerplate of the gitignore that a dump left out because its name no longer finds it,
+/// such as one renamed or removed upstream.
+#[derive(Debug, Clone)]
+pub struct Dropped {
+    /// The name of the boilerplate, as the gitignore records it.
+    pub name: Name,
+    /// Why the name no longer finds the boilerplate.
+    pub reason: String,
+}
+
+/// What a dump did to the blocks of the gitignore, as [`Gixor::dump_to`] returns it.
+///
+/// A block is matched by its name before and after, so a boilerplate renamed upstream and dumped
+/// under its new name is removed under the old one and added under the new one.
+#[derive(Debug, Clone, Default)]
+pub struct DumpReport {
+    /// The path the gitignore was written to, `-` for stdout.
+    pub path: PathBuf,
+    /// The boilerplates the gitignore did not have before.
+    pub added: Vec<Name>,
+    /// The boilerplates whose blocks were rewritten.
+    pub updated: Vec<Update>,
+    /// The boilerplates whose blocks stayed as they were.
+    pub unchanged: Vec<Name>,
+    /// The boilerplates the gitignore no longer has, save the dropped ones.
+    pub removed: Vec<Name>,
+    /// The boilerplates of the gitignore left out because they no longer resolve.
+    pub dropped: Vec<Dropped>,
+}
+
+impl DumpReport {
+    /// Returns `true` if the dump left every block as it was.
+    pub fn is_unchanged(&self) -> bool {
+        self.added.is_empty()
+            && self.updated.is_empty()
+            && self.removed.is_empty()
+            && self.dropped.is_empty()
+    }
+}
+
+impl Display for DumpReport {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let counts = [
+            (self.added.len(), "added"),
+            (self.updated.len(), "updated"),
+            (self.unchanged.len(), "unchanged"),
+            (self.removed.len(), "removed"),
+            (self.dropped.len(), "dropped"),
+        ]
+        .into_iter()
+        .filter(|(count, _)| *count > 0)
+        .map(|(count, what)| format!("{count} {what}"))
+        .collect::<Vec<_>>();
+        if counts.is_empty() {
+            write!(f, "{}: no boilerplates", self.path.display())?;
+        } else {
+            write!(f, "{}: {}", self.path.display(), counts.join(", "))?;
+        }
+        for name in &self.added {
+            write!(f, "\n    added    {name}")?;
+        }
+        for update in &self.updated {
+            write!(f, "\n    updated  {update}")?;
+        }
+        for name in &self.removed {
+            write!(f, "\n    removed  {name}")?;
+        }
+        for dropped in &self.dropped {
+            write!(f, "\n    dropped  {} ({})", dropped.name, dropped.reason)?;
+        }
+        Ok(())
+    }
+}
+
+/// What [`Gixor::build`] makes of a gitignore: the content with the lock of its boilerplates,
+/// and the gitignore it replaces with the names dropped from it.
+struct Built {
+    current: document::GitignoreDocument,
+    content: String,
+    lock: lock::Lock,
+    dropped: Vec<Dropped>,
+}
+
 /// Represents a boilerplate name for finding a boilerplate.
 impl Name {
     /// Create a new `Name` instance with boilerplate name.
@@ -218,6 +639,7 @@ impl Name {
         Self {
             repository_name: None,
             boilerplate_name: boilerplate_name.as_ref().to_string(),
+            revision: None,
         }
     }
 
@@ -227,20 +649,33 @@ impl Name {
         Self {
             repository_name: Some(repository_name.as_ref().to_string()),
             boilerplate_name,
+            revision: None,
         }
     }
 
     /// Create a new `Name` instance with the given name.
-    /// The given name should format `<repository_name>/<boilerplate_name>`.
+    /// The given name should format `<repository_name>/<boilerplate_name>`, where the
+    /// boilerplate name may be a path such as `default/Global/macOS`.
     /// If the given string do not contain `/`, the repository name is `None`.
+    ///
+    /// The first part of a name such as `Global/macOS` may as well be a directory as a
+    /// repository, which only the boilerplates can tell, so it is kept as the repository name
+    /// and [`repos::Boilerplate::matches`] tries it both ways.
+    ///
+    /// A revision may follow the name after `@`, such as `default/Rust@v2.0`.
     pub fn parse<S: AsRef<str>>(name: S) -> Self {
         let name = name.as_ref();
-        let items = name.split('/').collect::<Vec<_>>();
-        if items.len() >= 2 {
-            Self::new(items[0], items[1])
-        } else {
-            Self::new_of(name)
-        }
+        let (name, revision) = match name.rsplit_once('@') {
+            Some((name, revision)) if !revision.is_empty() => (name, Some(revision.to_string())),
+            _ => (name, None),
+        };
+        let name = match name.split_once('/') {
+            Some((repository_name, boilerplate_name)) => {
+                Self::new(repository_name, boilerplate_name)
+            }
+            None => Self::new_of(name),
+        };
+        Self { revision, ..name }
     }
 
     /// Create a vec of `Name` instance from the given string vec.
@@ -262,6 +697,9 @@ impl Name {
 pub struct Gixor {
     config: Config,
     load_from: PathBuf,
+    observer: Arc<dyn progress::Observer>,
+    edit_policy: document::EditPolicy,
+    revision_policy: document::RevisionPolicy,
 }
 
 /// Provides the functions for management of the boilerplate repositories.
@@ -277,10 +715,22 @@ pub trait RepositoryManager {
     /// Add the given new repository and returns the new instance of Gixor.
     fn add_repository(&mut self, repo: repos::Repository) -> Result<()>;
     /// Add a repository build from the given url and returns the new instance of Gixor.
+    /// The url may be a shorthand, see [`Gixor::resolve_url`].
     fn add_repository_of<S: AsRef<str>>(&mut self, url: S) -> Result<()>;
     /// Remove the repository which has the given name, and returns the new instance of Gixor.
     fn remove_repository_with<S: AsRef<str>>(&mut self, name: S, keep_repo_dir: bool)
         -> Result<()>;
+    /// Rename the repository `old` to `new`, moving its clone and rewriting the aliases that
+    /// refer to its boilerplates.
+    fn rename_repository<S: AsRef<str>>(&mut self, old: S, new: S) -> Result<()>;
+    /// Point the repository which has the given name at `url`, keeping its clone.
+    fn set_repository_url<S: AsRef<str>>(&mut self, name: S, url: S) -> Result<()>;
+    /// Set the priority of the repository which has the given name, see
+    /// [`repos::Repository::priority`].
+    fn set_repository_priority<S: AsRef<str>>(&mut self, name: S, priority: i32) -> Result<()>;
+    /// Enable or disable the repository which has the given name, see
+    /// [`repos::Repository::enabled`].
+    fn set_repository_enabled<S: AsRef<str>>(&mut self, name: S, enabled: bool) -> Result<()>;
     /// Remove the repository which has the given name, and returns the new instance of Gixor.
     fn remove_repository<S: AsRef<str>>(&mut self, name: S) -> Result<()>;
 }
@@ -317,11 +767,10 @@ impl Default for Gixor {
                     repositories,
                     base_path: dir.join("gixor").join("boilerplates"),
                     aliases: None,
+                    update_interval: None,
+                    shorthands: None,
                 };
-                Self {
-                    config,
-                    load_from: dir.join("gixor").join("config.json"),
-                }
+                Self::new(config, dir.join("gixor").join("config.json"))
             }
             None => panic!("Failed to get the config directory"),
         }
@@ -344,6 +793,8 @@ impl GixorFactory {
                 repositories: source::repositories(),
                 base_path: PathBuf::new(),
                 aliases: None,
+                update_interval: None,
+                shorthands: None,
             },
             PathBuf::new(),
         )
@@ -375,6 +826,8 @@ impl GixorFactory {
                 repositories: vec![repos::Repository::default()],
                 base_path: path.parent().unwrap_or(Path::new(".")).join("boilerplates"),
                 aliases: None,
+                update_interval: None,
+                shorthands: None,
             },
             path.to_path_buf(),
         )
@@ -406,7 +859,13 @@ impl Gixor {
     fn new(config: Config, load_from: PathBuf) -> Self {
         log::debug!("config path: {load_from:?}");
         log::debug!("config: {}", serde_json::to_string_pretty(&config).unwrap());
-        Gixor { config, load_from }
+        Gixor {
+            config,
+            load_from,
+            observer: Arc::new(progress::Silent),
+            edit_policy: document::EditPolicy::default(),
+            revision_policy: document::RevisionPolicy::default(),
+        }
     }
     /// Returns the base path of this configuration.
     pub fn base_path(&self) -> &Path {
@@ -416,12 +875,93 @@ impl Gixor {
     /// Prepare the repositories in the local environment by cloning or updating them.
     ///
     /// This method will iterate through all configured repositories. If a repository
-    /// does not exist locally, it will be cloned. If it exists, it will be updated (pulled).
+    /// does not exist locally, it will be cloned. If it exists, it will be updated (pulled),
+    /// unless it was fetched within the update interval of the configuration
+    /// ([`Gixor::update_interval`]). Use [`Gixor::update`] to update them regardless.
+    ///
+    /// The repositories are prepared concurrently, and the returned [`repos::Report`] tells
+    /// what became of each of them, failures included. Call [`repos::Report::into_result`] to
+    /// treat any failure as an error.
     ///
     /// # Arguments
     /// * `no_network` - If true, skip network operations (no clone or pull).
-    pub fn prepare(&self, no_network: bool) -> Result<()> {
-        self.config.prepare(no_network)
+    #[must_use = "the failures of the repositories are in the report"]
+    pub fn prepare(&self, no_network: bool) -> repos::Report {
+        self.prepare_with(no_network, repos::PreparePolicy::Strict)
+    }
+
+    /// Prepares the repositories as [`Gixor::prepare`] does, with `policy` deciding what a
+    /// failure to update a repository amounts to.
+    ///
+    /// With [`repos::PreparePolicy::KeepStale`], a repository that cannot be updated but was
+    /// cloned before is used as it stands, and only a repository that was never cloned fails.
+    #[must_use = "the failures of the repositories are in the report"]
+    pub fn prepare_with(&self, no_network: bool, policy: repos::PreparePolicy) -> repos::Report {
+        self.config
+            .prepare(no_network, false, policy, &self.observer)
+    }
+
+    /// Clones or updates every repository, however recently it was fetched.
+    ///
+    /// This is what an explicit request to update asks for, where [`Gixor::prepare`] is what
+    /// every other command does on its way to the boilerplates.
+    #[must_use = "the failures of the repositories are in the report"]
+    pub fn update(&self) -> repos::Report {
+        self.config
+            .prepare(false, true, repos::PreparePolicy::Strict, &self.observer)
+    }
+
+    /// Installs the observer told how the clones and the fetches of [`Gixor::prepare`],
+    /// [`Gixor::update`] and [`RepositoryManager::add_repository`] go. It is
+    /// [`progress::Silent`] until this is called.
+    pub fn set_observer(&mut self, observer: Arc<dyn progress::Observer>) {
+        self.observer = observer;
+    }
+
+    /// Sets what [`Gixor::build_gitignore`], and the dumps built on it, make of a block of the
+    /// destination that was edited by hand since it was dumped. It is
+    /// [`document::EditPolicy::Refuse`] until this is called.
+    pub fn set_edit_policy(&mut self, policy: document::EditPolicy) {
+        self.edit_policy = policy;
+    }
+
+    /// Sets which revision of the boilerplates [`Gixor::build_gitignore`], and the dumps built
+    /// on it, write. It is [`document::RevisionPolicy::Latest`] until this is called.
+    pub fn set_revision_policy(&mut self, policy: document::RevisionPolicy) {
+        self.revision_policy = policy;
+    }
+
+    /// Returns how old the last fetch of a repository may get before [`Gixor::prepare`]
+    /// fetches it again. It is `update-interval` in the configuration file, a day by default,
+    /// and zero fetches every time.
+    pub fn update_interval(&self) -> std::time::Duration {
+        self.config.update_interval.unwrap_or(interval::DEFAULT)
+    }
+
+    /// Sets the interval [`Gixor::update_interval`] returns.
+    pub fn set_update_interval(&mut self, interval: std::time::Duration) {
+        self.config.update_interval = Some(interval);
+    }
+
+    /// Expands the repository shorthand `url` to the URL it stands for, and returns anything
+    /// else as it is.
+    ///
+    /// `owner` and `owner/repo` are on GitHub, such as `github` for
+    /// `https://github.com/github/gitignore.git`. The prefixes `gh:`, `gl:`, `bb:` and
+    /// `codeberg:` name the forge, as in `gl:group/gitignore`, and `shorthands` in the
+    /// configuration file adds prefixes of its own, such as
+    /// `"corp": "https://git.corp.example.com/{path}.git"`. A path starting with `./`, `../` or
+    /// `/` is a local repository, whether or not a directory of the same name is at hand.
+    pub fn resolve_url<S: AsRef<str>>(&self, url: S) -> Result<String> {
+        shorthand::expand(url.as_ref(), self.config.shorthands.as_ref())
+    }
+
+    fn repository_mut(&mut self, name: &str) -> Result<&mut repos::Repository> {
+        self.config
+            .repositories
+            .iter_mut()
+            .find(|repo| repo.name == name)
+            .ok_or_else(|| Error::RepositoryNotFound(name.to_string()))
     }
 
     /// Write the content of boilerplates corresponding to the given names to the destination.
@@ -445,30 +985,66 @@ impl Gixor {
     ///
     /// Use this to preview the result, or to write it somewhere else.
     ///
+    /// The text of the user's own in the destination stays where it is: the prologue before
+    /// the first boilerplate, and whatever follows the end marker of a boilerplate. A
+    /// boilerplate of the destination is rewritten in its place when it is among `names`, and
+    /// dropped otherwise; the other `names` follow the last boilerplate that stays.
+    ///
+    /// A boilerplate to be rewritten whose block was edited by hand is what
+    /// [`Gixor::set_edit_policy`] decides: by default, the edits fail the call with
+    /// [`Error::Modified`] rather than being lost.
+    ///
+    /// A name of a boilerplate the destination already has is dropped when it no longer finds
+    /// the boilerplate, since a boilerplate renamed or removed upstream must not block the
+    /// update, and keeps the first candidate in the search order when it became ambiguous.
+    /// Any other name that does not find its boilerplate fails the call.
+    ///
+    /// A boilerplate the lock of the destination has is taken at its locked commit, as
+    /// [`Gixor::dump_to`] takes it.
+    ///
     /// # Arguments
     /// * `names` - A vector of [`Name`] instances representing the boilerplates to dump.
-    /// * `dest` - The path the content is destined for. Its prologue is carried over, and
-    ///   `"-"` reads the prologue from `.gitignore` in the current directory.
-    /// * `clear_prologue` - If true, drop the prologue of the destination.
+    /// * `dest` - The path the content is destined for. Its text of the user is carried over,
+    ///   and `"-"` reads it from `.gitignore` in the current directory.
+    /// * `clear_prologue` - If true, drop the text of the user in the destination.
     pub fn build_gitignore<P: AsRef<Path>>(
         &self,
         names: Vec<Name>,
         dest: P,
         clear_prologue: bool,
     ) -> Result<String> {
-        let dest = dest.as_ref();
-        let prologue = if clear_prologue {
-            vec![]
+        let from = routine::source_of(dest.as_ref());
+        let lock = lock::Lock::load(&lock::path_of(&from))?;
+        self.build(names, &from, clear_prologue, &lock, self.edit_policy)
+            .map(|built| built.content)
+    }
+
+    /// Builds the content for the gitignore at `from`, with the boilerplates `lock` has taken
+    /// at their locked commits, and the edits by hand left to `policy`.
+    fn build(
+        &self,
+        names: Vec<Name>,
+        from: &Path,
+        clear_prologue: bool,
+        lock: &lock::Lock,
+        policy: document::EditPolicy,
+    ) -> Result<Built> {
+        let current = routine::load_document(from);
+        let (boilerplates, dropped) =
+            routine::resolve_boilerplates(self, names, &current, lock, policy)?;
+        let boilerplates = boilerplates.into_iter().map(|b| lock.pin(b)).collect();
+        let base = if clear_prologue {
+            document::GitignoreDocument::default()
         } else {
-            let from = if dest == Path::new("-") {
-                PathBuf::from(".gitignore")
-            } else {
-                routine::find_gitignore(dest)
-            };
-            routine::load_prologue(&from)
+            current.clone()
         };
-        let boilerplates = routine::find_boilerplates(self, names)?;
-        routine::build_content(boilerplates, prologue, self.base_path())
+        let (content, lock) = routine::build_locked(self, boilerplates, base, policy)?;
+        Ok(Built {
+            current,
+            content,
+            lock,
+            dropped,
+        })
     }
 
     /// Builds the same content as [`Gixor::build_gitignore`], from a gitignore already in hand
@@ -479,12 +1055,97 @@ impl Gixor {
     ///
     /// # Arguments
     /// * `names` - A vector of [`Name`] instances representing the boilerplates to dump.
-    /// * `current` - The current content of the gitignore. Its prologue, the part before the
-    ///   first boilerplate, is carried over. Pass `""` to start from nothing.
+    /// * `current` - The current content of the gitignore. Its text of the user, before the
+    ///   first boilerplate and after the end marker of each, is carried over in place. Pass
+    ///   `""` to start from nothing.
     pub fn build_gitignore_with(&self, names: Vec<Name>, current: &str) -> Result<String> {
-        let prologue = routine::prologue_of(current);
-        let boilerplates = routine::find_boilerplates(self, names)?;
-        routine::build_content(boilerplates, prologue, self.base_path())
+        let current = document::GitignoreDocument::parse(current);
+        let lock = lock::Lock::default();
+        let (boilerplates, _) =
+            routine::resolve_boilerplates(self, names, &current, &lock, self.edit_policy)?;
+        routine::build_content(self, boilerplates, current, self.edit_policy)
+    }
+
+    /// Compares the gitignore at `dest` with what [`Gixor::build_gitignore`] builds for `names`
+    /// into it, for a check that the gitignore is what dumping would make of it.
+    ///
+    /// The content is built as [`Gixor::build_gitignore`] builds it, names dropped and all, and
+    /// the edits a dump merged into a block are expected as they were merged. A block edited by
+    /// hand since it was written, and one whose boilerplate was removed upstream, counts as a
+    /// difference rather than stopping the check. With
+    /// [`document::RevisionPolicy::Recorded`], the blocks are expected at the paths and the
+    /// commits they record, so that the check holds however far upstream has moved since.
+    ///
+    /// # Arguments
+    /// * `names` - A vector of [`Name`] instances, such as the [`entries`] of `dest`.
+    /// * `dest` - The gitignore, or the directory holding it. `"-"` means `.gitignore` in the
+    ///   current directory. A missing one is [`Error::FileNotFound`].
+    pub fn check<P: AsRef<Path>>(&self, names: Vec<Name>, dest: P) -> Result<Check> {
+        let (path, actual) = routine::read_gitignore(dest)?;
+        let lock = lock::Lock::load(&lock::path_of(&path))?;
+        let current = document::GitignoreDocument::parse(&actual);
+        let policy = document::EditPolicy::Overwrite;
+        let (boilerplates, _) =
+            routine::resolve_boilerplates(self, names, &current, &lock, policy)?;
+        let boilerplates = boilerplates.into_iter().map(|b| lock.pin(b)).collect();
+        let policy = document::EditPolicy::Refuse;
+        let expected = routine::build_content(self, boilerplates, current.forget_edits(), policy)?;
+        Ok(Check {
+            path,
+            actual,
+            expected,
+        })
+    }
+
+    /// Compares the gitignore with what [`Gixor::dump_to`] would write in its place, without
+    /// touching it. [`Check::changes`] tells the differences block by block.
+    ///
+    /// A missing gitignore is taken as an empty one, which dumping would create.
+    ///
+    /// # Arguments
+    /// * `names` - A vector of [`Name`] instances representing the boilerplates to dump.
+    /// * `dest` - The gitignore, or the directory holding it. `"-"` means `.gitignore` in the
+    ///   current directory.
+    /// * `clear_prologue` - If true, drop the text of the user in the destination.
+    pub fn diff<P: AsRef<Path>>(
+        &self,
+        names: Vec<Name>,
+        dest: P,
+        clear_prologue: bool,
+    ) -> Result<Check> {
+        let (path, actual) = match routine::read_gitignore(&dest) {
+            Ok(read) => read,
+            Err(Error::FileNotFound(path)) => (path, String::new()),
+            Err(e) => return Err(e),
+        };
+        let expected = self.build_gitignore(names, dest, clear_prologue)?;
+        Ok(Check {
+            path,
+            actual,
+            expected,
+        })
+    }
+
+    /// Finds how the boilerplates of the gitignore stand against their repositories: whether
+    /// each of them changed upstream since the commit its block records, as the clone knows
+    /// it, and in which commits.
+    ///
+    /// A boilerplate whose name no longer finds it at the path its block records was renamed
+    /// when the commit that removed it has its content at another path, and removed otherwise.
+    /// One whose block records no commit, such as one dumped from a host with no permalink,
+    /// or that cannot be found in the history of its clone, is [`Freshness::Unknown`].
+    ///
+    /// # Arguments
+    /// * `dest` - The gitignore, or the directory holding it. `"-"` means `.gitignore` in the
+    ///   current directory. A missing one is [`Error::FileNotFound`].
+    pub fn outdated<P: AsRef<Path>>(&self, dest: P) -> Result<Outdated> {
+        let (path, content) = routine::read_gitignore(dest)?;
+        let document = document::GitignoreDocument::parse(&content);
+        let boilerplates = document
+            .blocks()
+            .map(|block| routine::lag(self, block))
+            .collect();
+        Ok(Outdated { path, boilerplates })
     }
 
     /// Writes the selected boilerplates to a file or stdout.
@@ -496,6 +1157,14 @@ impl Gixor {
     /// The destination is replaced by a rename once the whole content has been built and
     /// written elsewhere, so an error leaves the existing file exactly as it was.
     ///
+    /// Returns what became of each block of the destination, and of each block added to it, as
+    /// [`DumpReport`] tells. The names are resolved as [`Gixor::build_gitignore`] does.
+    ///
+    /// A boilerplate the lock of the destination, beside it as [`lock::path_of`] names it, has is read from the
+    /// path the lock records at its locked commit, even one removed upstream since, unless the
+    /// name gives a revision of its own, and the lock is written anew for the boilerplates of
+    /// the file. See [`lock`], and [`Gixor::upgrade`] to move it.
+    ///
     /// # Arguments
     /// * `names` - A vector of [`Name`] instances.
     /// * `dest` - The destination path or `"-"` for stdout.
@@ -505,7 +1174,7 @@ impl Gixor {
         names: Vec<Name>,
         dest: P,
         clear_flag: bool,
-    ) -> Result<()> {
+    ) -> Result<DumpReport> {
         let p = dest.as_ref();
         log::info!(
             "dump {} entries into {} with clear_flag: {clear_flag}.",
@@ -514,14 +1183,120 @@ impl Gixor {
         );
         // The content is built first and in full. Nothing here opens the destination until the
         // result is known to be complete, so a failure leaves the existing file untouched.
-        let content = self.build_gitignore(names, p, clear_flag)?;
-        if p == Path::new("-") {
+        let from = routine::source_of(p);
+        let lock = lock::Lock::load(&lock::path_of(&from))?;
+        let built = self.build(names, &from, clear_flag, &lock, self.edit_policy)?;
+        let path = if p == Path::new("-") {
             use std::io::Write;
             let mut out = std::io::stdout();
-            out.write_all(content.as_bytes()).map_err(Error::IO)?;
-            return out.flush().map_err(Error::IO);
+            out.write_all(built.content.as_bytes()).map_err(Error::IO)?;
+            out.flush().map_err(Error::IO)?;
+            p.to_path_buf()
+        } else {
+            routine::write_atomically(&from, &built.content)?;
+            built.lock.store(&lock::path_of(&from))?;
+            from
+        };
+        Ok(routine::report(
+            path,
+            &built.current,
+            &built.content,
+            built.dropped,
+        ))
+    }
+
+    /// Moves the boilerplates of `names` in the lock of the gitignore to their latest content,
+    /// and dumps the gitignore with them, writing the lock anew. With no `names`, every
+    /// boilerplate of the gitignore is moved.
+    ///
+    /// A name giving a revision, such as `rust@v1.0`, moves the boilerplate to that revision
+    /// instead. A name of no boilerplate of the gitignore fails the call.
+    ///
+    /// # Arguments
+    /// * `names` - The boilerplates to move, which the gitignore must have.
+    /// * `dest` - The gitignore, or the directory holding it. `"-"` means `.gitignore` in the
+    ///   current directory. A missing one is [`Error::FileNotFound`].
+    pub fn upgrade<P: AsRef<Path>>(&self, names: Vec<Name>, dest: P) -> Result<DumpReport> {
+        let (path, content) = routine::read_gitignore(dest)?;
+        let current = document::GitignoreDocument::parse(&content);
+        let mut entries = current
+            .blocks()
+            .map(|block| block.name())
+            .collect::<Vec<_>>();
+        let lock = if names.is_empty() {
+            lock::Lock::default()
+        } else {
+            let mut lock = lock::Lock::load(&lock::path_of(&path))?;
+            let boilerplates = routine::find_boilerplates(self, names.clone())?;
+            let errs = boilerplates
+                .iter()
+                .filter(|b| !current.blocks().any(|block| b.matches(&block.name())))
+                .map(|b| Error::Fatal(format!("{}: not in {}", b.name(), path.display())))
+                .collect();
+            Error::to_err((), errs)?;
+            lock.release(&boilerplates);
+            // the names giving a revision take the boilerplates to it, in their places
+            entries.extend(names);
+            lock
+        };
+        log::info!("upgrade {} entries of {}", entries.len(), path.display());
+        let built = self.build(entries, &path, false, &lock, self.edit_policy)?;
+        routine::write_atomically(&path, &built.content)?;
+        built.lock.store(&lock::path_of(&path))?;
+        Ok(routine::report(
+            path,
+            &built.current,
+            &built.content,
+            built.dropped,
+        ))
+    }
+
+    /// Builds the gitignore from its lock, exactly as the lock records it, and writes it.
+    ///
+    /// The repositories of the lock that are not configured are added under the names the lock
+    /// gives them, and cloned, and a clone missing a locked commit is fetched. The text of the
+    /// user in the gitignore, if there is one, is carried over as a dump does; the lock itself
+    /// is left as it is.
+    ///
+    /// The edits a dump merged into a block are kept in the gitignore alone, and the lock
+    /// records only their checksum, so a gitignore that lost them is [`Error::LockMismatch`]
+    /// rather than rebuilt without them, unless the edit policy overwrites them.
+    ///
+    /// # Arguments
+    /// * `dest` - The gitignore, or the directory holding it. `"-"` means `.gitignore` in the
+    ///   current directory. A missing lock beside it is [`Error::FileNotFound`].
+    pub fn sync<P: AsRef<Path>>(&mut self, dest: P) -> Result<DumpReport> {
+        let path = routine::source_of(dest.as_ref());
+        let lock_path = lock::path_of(&path);
+        if !lock_path.exists() {
+            return Err(Error::FileNotFound(lock_path));
+        }
+        let lock = lock::Lock::load(&lock_path)?;
+        for locked in &lock.boilerplates {
+            let url = &locked.url;
+            if self
+                .repositories()
+                .any(|repo| lock::same_url(&repo.url, url))
+            {
+                continue;
+            }
+            let name = match &locked.name.repository_name {
+                Some(name) => name.clone(),
+                None => repos::Repository::new(url).name,
+            };
+            if let Some(other) = self.repository(&name) {
+                return Err(Error::Fatal(format!(
+                    "{name}: the repository of {}, not of {url} the lock records",
+                    other.url
+                )));
+            }
+            log::info!("{name}: adding {url} the lock records");
+            self.add_repository(repos::Repository::new_with(name.as_str(), url.as_str()))?;
         }
-        routine::write_atomically(&routine::find_gitignore(p), &content)
+        let current = routine::load_document(&path);
+        let content = routine::build_synced(self, &lock, current.clone(), self.edit_policy)?;
+        routine::write_atomically(&path, &content)?;
+        Ok(routine::report(path, &current, &content, vec![]))
     }
 
     /// Store the configuration to the configuration path.
@@ -591,10 +1366,16 @@ impl RepositoryManager for Gixor {
     }
 
     /// Add the given new repository and returns the new instance of Gixor.
-    fn add_repository(&mut self, repo: repos::Repository) -> Result<()> {
-        match repo.clone_repo_to(&self.config.base_path) {
+    /// A repository with no branch given records the one its clone follows, which is the
+    /// default branch of the remote.
+    fn add_repository(&mut self, mut repo: repos::Repository) -> Result<()> {
+        let progress = progress::Reporter::new(&repo.name, self.observer.clone());
+        match repo.clone_repo_to_with(&self.config.base_path, &progress) {
             Err(e) => Err(e),
             Ok(_) => {
+                if repo.branch.is_none() {
+                    repo.branch = repo.branch_at(&self.config.base_path);
+                }
                 self.config.repositories.push(repo);
                 Ok(())
             }
@@ -603,7 +1384,7 @@ impl RepositoryManager for Gixor {
 
     /// Add a repository build from the given url and returns the new instance of Gixor.
     fn add_repository_of<S: AsRef<str>>(&mut self, url: S) -> Result<()> {
-        let repo = repos::Repository::new(url);
+        let repo = repos::Repository::new(self.resolve_url(url)?);
         self.add_repository(repo)
     }
 
@@ -636,6 +1417,87 @@ impl RepositoryManager for Gixor {
     fn remove_repository<S: AsRef<str>>(&mut self, name: S) -> Result<()> {
         self.remove_repository_with(name, false)
     }
+
+    /// Rename the repository `old` to `new`.
+    ///
+    /// A clone kept in the directory named after the repository, which is where
+    /// [`repos::Repository::new`] keeps it, moves along to the directory of the new name rather
+    /// than being cloned again. A clone kept elsewhere stays where it is. Every `old/...` in the
+    /// aliases becomes `new/...`, so that the aliases keep naming the same boilerplates.
+    fn rename_repository<S: AsRef<str>>(&mut self, old: S, new: S) -> Result<()> {
+        let (old, new) = (old.as_ref(), new.as_ref());
+        if new.is_empty() || new.contains('/') || new == "alias" {
+            return Err(Error::Fatal(format!(
+                "{new}: invalid repository name, which has to be neither empty, alias, nor contain /"
+            )));
+        }
+        let index = self
+            .config
+            .repositories
+            .iter()
+            .position(|repo| repo.name == old)
+            .ok_or_else(|| Error::RepositoryNotFound(old.to_string()))?;
+        if self
+            .config
+            .repositories
+            .iter()
+            .any(|repo| repo.name != old && repo.name.eq_ignore_ascii_case(new))
+        {
+            return Err(Error::Fatal(format!("{new}: repository already exists")));
+        }
+        let mut renamed = self.config.repositories[index].clone();
+        renamed.name = new.to_string();
+        if renamed.path == Path::new(old) {
+            renamed.path = PathBuf::from(new);
+        }
+        source::relocate(
+            &self.config.repositories[index],
+            &renamed,
+            &self.config.base_path,
+        )?;
+        if let Some(aliases) = self.config.aliases.as_mut() {
+            aliases.rename_repository(old, new);
+        }
+        self.config.repositories[index] = renamed;
+        Ok(())
+    }
+
+    /// Point the repository which has the given name at `url`.
+    ///
+    /// The owner and the repository name follow the new URL, and the remote of the clone is
+    /// pointed at it, so the next update fetches from there on top of what is already fetched.
+    fn set_repository_url<S: AsRef<str>>(&mut self, name: S, url: S) -> Result<()> {
+        let (name, url) = (name.as_ref(), url.as_ref());
+        let repo = self
+            .config
+            .repositories
+            .iter_mut()
+            .find(|repo| repo.name == name)
+            .ok_or_else(|| Error::RepositoryNotFound(name.to_string()))?;
+        let parsed = repos::Repository::new_with(name, url);
+        let updated = repos::Repository {
+            url: parsed.url,
+            owner: parsed.owner,
+            repo_name: parsed.repo_name,
+            ..repo.clone()
+        };
+        source::set_url(&updated, &self.config.base_path)?;
+        *repo = updated;
+        Ok(())
+    }
+
+    /// Set the priority of the repository which has the given name.
+    fn set_repository_priority<S: AsRef<str>>(&mut self, name: S, priority: i32) -> Result<()> {
+        self.repository_mut(name.as_ref())?.priority = priority;
+        Ok(())
+    }
+
+    /// Enable or disable the repository which has the given name. The clone of a disabled
+    /// repository is kept, for it to be enabled again without cloning.
+    fn set_repository_enabled<S: AsRef<str>>(&mut self, name: S, enabled: bool) -> Result<()> {
+        self.repository_mut(name.as_ref())?.enabled = enabled;
+        Ok(())
+    }
 }
 
 fn update_base_path(config: Config, path: &Path) -> Config {
@@ -648,8 +1510,7 @@ fn update_base_path(config: Config, path: &Path) -> Config {
     };
     Config {
         base_path: new_base_path,
-        repositories: config.repositories,
-        aliases: config.aliases,
+        ..config
     }
 }
 
@@ -660,22 +1521,91 @@ struct Config {
     #[serde(flatten)]
     pub(crate) aliases: Option<aliases::Aliases>,
     pub(crate) base_path: PathBuf,
+    #[serde(default, with = "interval", skip_serializing_if = "Option::is_none")]
+    pub(crate) update_interval: Option<std::time::Duration>,
+    /// The prefixes of the repository shorthands this configuration adds, see [`shorthand`].
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub(crate) shorthands: Option<std::collections::BTreeMap<String, String>>,
 }
 
 impl Config {
     /// Find the related boilerplates by the names from all of repositories.
     /// The method matches the given name with an alias and, the boilerplate name in the repository..
+    ///
+    /// A name found in several repositories is taken from the one of the highest priority. When
+    /// more than one repository shares that priority, nothing says which of them is meant, and
+    /// picking one would change the `.gitignore` behind the back of the user, so the name is
+    /// [`Error::Ambiguous`] until it is qualified as `repo/name`.
+    ///
+    /// The boilerplates found are taken at the revision of the name, if it has one.
     fn find(&self, name: Name) -> Result<Vec<repos::Boilerplate<'_>>> {
         if let Some(r) = aliases::extract_alias(self, &name) {
-            Ok(r)
-        } else {
-            for repo in &self.repositories {
-                if let Some(item) = repo.find(&name, &self.base_path) {
-                    log::trace!("{}: found from repository {}", name, item.repository_name());
-                    return Ok(vec![item]);
-                }
-            }
-            Err(Error::BoilerplateNotFound(name.boilerplate_name))
+            return Ok(r.into_iter().map(|b| b.at(name.revision.clone())).collect());
+        }
+        let mut candidates = self
+            .active()
+            .filter_map(|repo| {
+                repo.find(&name, &self.base_path)
+                    .map(|b| (repo.priority, b))
+            })
+            .collect::<Vec<_>>();
+        let Some((top, _)) = candidates.first() else {
+            let suggestions = self.suggestions(&name);
+            return Err(Error::BoilerplateNotFound(
+                name.boilerplate_name,
+                suggestions,
+            ));
+        };
+        let top = *top;
+        candidates.retain(|(priority, _)| *priority == top);
+        if candidates.len() > 1 {
+            return Err(Error::Ambiguous(
+                name.to_string(),
+                candidates
+                    .iter()
+                    .map(|(_, b)| b.name().to_string())
+                    .collect(),
+            ));
+        }
+        let (_, item) = candidates.remove(0);
+        log::trace!("{}: found from repository {}", name, item.repository_name());
+        Ok(vec![item.at(name.revision)])
+    }
+
+    /// Returns the names of the boilerplates and the aliases close to `name`, which was not
+    /// found. A name qualified with a repository is compared with the boilerplates of that
+    /// repository only, and suggested qualified the same way. The names are compared in the
+    /// forms [`Config::find`] takes, the stem and the path, such as `Global/macOS`.
+    fn suggestions(&self, name: &Name) -> Vec<String> {
+        let is_repository = |repo: &str| {
+            repo == "alias"
+                || self
+                    .repositories
+                    .iter()
+                    .any(|r| r.name.eq_ignore_ascii_case(repo))
+        };
+        // a qualifier naming no repository is the directory of a path, such as `Global/macOS`
+        let (wanted, typed) = match name.repository_name.as_deref() {
+            Some(repo) if is_repository(repo) => (Some(repo), name.boilerplate_name.clone()),
+            Some(dir) => (None, format!("{dir}/{}", name.boilerplate_name)),
+            None => (None, name.boilerplate_name.clone()),
+        };
+        let boilerplates = self
+            .iter()
+            .filter(|b| wanted.is_none_or(|repo| repo.eq_ignore_ascii_case(b.repository_name())))
+            .flat_map(|b| [b.boilerplate_name().to_string(), b.path_name()]);
+        let aliases = self
+            .iter_aliases()
+            .filter(|_| wanted.is_none_or(|repo| repo == "alias"))
+            .map(|alias| alias.name.clone());
+        let candidates = boilerplates.chain(aliases).collect::<Vec<_>>();
+        let suggestions = suggest::close_matches(&typed, candidates);
+        match wanted {
+            Some(repo) => suggestions
+                .into_iter()
+                .map(|suggestion| format!("{repo}/{suggestion}"))
+                .collect(),
+            None => suggestions,
         }
     }
 
@@ -694,28 +1624,132 @@ impl Config {
 
     /// Iterate the boilerplates from all repositories.
     fn iter(&self) -> impl Iterator<Item = repos::Boilerplate<'_>> {
-        self.repositories
-            .iter()
+        self.active()
             .flat_map(move |repo| repo.iter(&self.base_path))
     }
 
+    /// Iterate the enabled repositories in the order they are searched: the higher
+    /// [`repos::Repository::priority`] first, and those of the same priority in the order of
+    /// the configuration.
+    fn active(&self) -> impl Iterator<Item = &repos::Repository> {
+        let mut repositories = self
+            .repositories
+            .iter()
+            .filter(|repo| repo.enabled)
+            .collect::<Vec<_>>();
+        repositories.sort_by_key(|repo| std::cmp::Reverse(repo.priority));
+        repositories.into_iter()
+    }
+
     /// Prepare the repositories in the local environment by cloning or updating them.
-    fn prepare(&self, no_network: bool) -> Result<()> {
-        let mut errs = vec![];
+    /// Unless `force` is given, a repository fetched within the update interval is left as
+    /// it is. `policy` decides whether a failure to update a clone is fatal.
+    ///
+    /// The repositories are prepared all at once, one thread each, since each of them spends
+    /// its time waiting on its own remote and none touches the clone of another.
+    /// A disabled repository is left out altogether, its clone as it was.
+    fn prepare(
+        &self,
+        no_network: bool,
+        force: bool,
+        policy: repos::PreparePolicy,
+        observer: &Arc<dyn progress::Observer>,
+    ) -> repos::Report {
+        use repos::{Outcome, Prepared};
         if no_network {
             log::info!("Network access is disabled.");
-            Ok(())
-        } else {
-            self.repositories.iter().for_each(|repo| {
-                if let Err(e) = repo.prepare(&self.base_path) {
-                    errs.push(e);
-                }
-            });
-            Error::to_err((), errs)
+            let repositories = self
+                .repositories
+                .iter()
+                .filter(|repo| repo.enabled)
+                .map(|repo| Prepared {
+                    name: repo.name.clone(),
+                    outcome: Outcome::Skipped,
+                })
+                .collect();
+            return repos::Report { repositories };
         }
+        let interval = self.update_interval.unwrap_or(interval::DEFAULT);
+        let prepare = |repo: &repos::Repository| {
+            if !force && repo.is_fresh(&self.base_path, interval) {
+                log::info!(
+                    "{}: fetched within {}, skipped",
+                    repo.name,
+                    interval::format(&interval)
+                );
+                Outcome::Skipped
+            } else {
+                // Asked before preparing, since a clone made by this very call is no older
+                // clone to fall back on.
+                let cloned = repo.path(&self.base_path).join(".git").exists();
+                let progress = progress::Reporter::new(&repo.name, observer.clone());
+                match repo.prepare_with(&self.base_path, &progress) {
+                    Ok(outcome) => outcome,
+                    Err(e) if cloned && policy == repos::PreparePolicy::KeepStale => {
+                        log::warn!(
+                            "{}: failed to update, using the clone as it is: {e}",
+                            repo.name
+                        );
+                        Outcome::Stale(e)
+                    }
+                    Err(e) => Outcome::Failed(e),
+                }
+            }
+        };
+        let enabled = self
+            .repositories
+            .iter()
+            .filter(|repo| repo.enabled)
+            .collect::<Vec<_>>();
+        let outcomes = prepare_all(&enabled, prepare);
+        let repositories = enabled
+            .iter()
+            .zip(outcomes)
+            .map(|(repo, outcome)| Prepared {
+                name: repo.name.clone(),
+                outcome,
+            })
+            .collect();
+        repos::Report { repositories }
     }
 }
 
+/// Runs `prepare` on every repository, each on a thread of its own.
+#[cfg(feature = "local")]
+fn prepare_all<F>(repositories: &[&repos::Repository], prepare: F) -> Vec<repos::Outcome>
+where
+    F: Fn(&repos::Repository) -> repos::Outcome + Sync,
+{
+    std::thread::scope(|scope| {
+        let handles = repositories
+            .iter()
+            .map(|repo| scope.spawn(|| prepare(repo)))
+            .collect::<Vec<_>>();
+        repositories
+            .iter()
+            .zip(handles)
+            .map(|(repo, handle)| {
+                handle.join().unwrap_or_else(|_| {
+                    repos::Outcome::Failed(Error::Fatal(format!(
+                        "{}: preparing the repository panicked",
+                        repo.name
+                    )))
+                })
+            })
+            .collect()
+    })
+}
+
+/// The embedded snapshot has nothing to wait on, and wasm, where it is mostly used, has no
+/// threads to spawn.
+#[cfg(feature = "embedded")]
+fn prepare_all<F>(repositories: &[&repos::Repository], prepare: F) -> Vec<repos::Outcome>
+where
+    F: Fn(&repos::Repository) -> repos::Outcome,
+{
+    repositories.iter().copied().map(prepare).collect()
+}
+
 impl AliasManager for Config {
     fn iter_aliases(&self) -> impl Iterator<Item = &aliases::Alias> {
         self.aliases.iter().flat_map(|a| a.iter_aliases())
@@ -735,7 +1769,7 @@ impl AliasManager for Config {
 }
 
 fn remove_repo_dir<P: AsRef<Path>>(base_path: P, repo: repos::Repository) -> Result<()> {
-    let path = base_path.as_ref().join(repo.name);
+    let path = repo.path(base_path);
     match std::fs::remove_dir_all(&path) {
         // The repository is gone from the configuration either way, and a clone that was never
         // made is not a failure to remove it. The embedded build never has one at all.
@@ -793,10 +1827,93 @@ mod tests {
             GixorFactory::load(config_path())
                 .unwrap()
                 .prepare(false)
-                .unwrap()
+                .into_result()
+                .unwrap();
         });
     }
 
+    /// Runs `git {args}` in `dir` as a fixed user, and returns what it printed, trimmed.
+    #[cfg(feature = "local")]
+    pub(crate) fn git(dir: &Path, args: &[&str]) -> String {
+        let out = std::process::Command::new("git")
+            .args(args)
+            .current_dir(dir)
+            .env("GIT_AUTHOR_NAME", "gixor")
+            .env("GIT_AUTHOR_EMAIL", "gixor@example.com")
+            .env("GIT_COMMITTER_NAME", "gixor")
+            .env("GIT_COMMITTER_EMAIL", "gixor@example.com")
+            .output()
+            .unwrap();
+        assert!(
+            out.status.success(),
+            "git {:?}: {}",
+            args,
+            String::from_utf8_lossy(&out.stderr)
+        );
+        String::from_utf8_lossy(&out.stdout).trim().to_string()
+    }
+
+    /// Commits everything in the working tree of `dir` as made at `date`, and returns the
+    /// commit.
+    #[cfg(feature = "local")]
+    pub(crate) fn commit(dir: &Path, message: &str, date: &str) -> String {
+        git(dir, &["add", "-A"]);
+        let out = std::process::Command::new("git")
+            .args(["commit", "-q", "-m", message])
+            .current_dir(dir)
+            .env("GIT_AUTHOR_NAME", "gixor")
+            .env("GIT_AUTHOR_EMAIL", "gixor@example.com")
+            .env("GIT_COMMITTER_NAME", "gixor")
+            .env("GIT_COMMITTER_EMAIL", "gixor@example.com")
+            .env("GIT_AUTHOR_DATE", date)
+            .env("GIT_COMMITTER_DATE", date)
+            .output()
+            .unwrap();
+        assert!(
+            out.status.success(),
+            "git commit: {}",
+            String::from_utf8_lossy(&out.stderr)
+        );
+        git(dir, &["rev-parse", "HEAD"])
+    }
+
+    /// Makes a repository at `dir` on the branch `main`, with `files` in its first commit,
+    /// which it returns. The tests clone it as they would a remote.
+    #[cfg(feature = "local")]
+    pub(crate) fn init_with(dir: &Path, files: &[(&str, &str)]) -> String {
+        std::fs::create_dir_all(dir).unwrap();
+        git(dir, &["init", "-q", "-b", "main"]);
+        for (path, content) in files {
+            let path = dir.join(path);
+            std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+            std::fs::write(path, content).unwrap();
+        }
+        commit(dir, "first", "2024-01-01T00:00:00+0000")
+    }
+
+    /// Makes a Gixor of a configuration in `dir` whose only repository, `test`, is cloned from
+    /// the repository at `remote`.
+    #[cfg(feature = "local")]
+    pub(crate) fn gixor_cloning(dir: &Path, remote: &Path) -> Gixor {
+        let mut gixor = GixorFactory::new_at(dir.join("config.json"));
+        gixor.remove_repository_with("default", true).unwrap();
+        let repo = repos::Repository::new_with("test", &remote.to_string_lossy());
+        gixor.add_repository(repo).unwrap();
+        gixor
+    }
+
+    /// Makes a Gixor of a configuration in a temporary directory whose `default` repository is
+    /// made in place of its clone, with `files` in its first commit. Returns the directory to
+    /// hold, the Gixor, and the path of the repository.
+    #[cfg(feature = "local")]
+    pub(crate) fn repo_with(files: &[(&str, &str)]) -> (tempfile::TempDir, Gixor, PathBuf) {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let gixor = GixorFactory::new_at(temp_dir.path().join("config.json"));
+        let repo = gixor.base_path().join("default");
+        init_with(&repo, files);
+        (temp_dir, gixor, repo)
+    }
+
     #[test]
     fn test_vec_result_to_result_vec() {
         let value = vec![Ok(1), Ok(2), Ok(3)];
@@ -833,6 +1950,388 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_check_changes_block_by_block() {
+        let block = |name: &str, body: &str| {
+            format!(
+                "### Generated by Gixor (https://github.com/tamada/gixor) (default/{name})\n### {name}.gitignore\n{body}### End of Gixor (default/{name})\n\n"
+            )
+        };
+        let check = Check {
+            path: PathBuf::from(".gitignore"),
+            actual: format!(
+                "# mine\n{}{}",
+                block("Go", "*.exe\n"),
+                block("Rust", "target/\n")
+            ),
+            expected: format!("# ours\n{}", block("Rust", "target/\nCargo.lock\n")),
+        };
+        let changes = check.changes();
+        let summary = changes
+            .iter()
+            .map(|change| {
+                let name = change.name.as_ref().map(|name| name.to_string());
+                (name, change.kind, change.hunks.len())
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(
+            summary,
+            vec![
+                (None, ChangeKind::Updated, 1),
+                (Some("default/Go".into()), ChangeKind::Removed, 1),
+                (Some("default/Rust".into()), ChangeKind::Updated, 1),
+            ]
+        );
+        assert_eq!(
+            changes[2].hunks[0].to_string(),
+            "@@ -7,5 +2,6 @@\n ### Generated by Gixor (https://github.com/tamada/gixor) (default/Rust)\n ### Rust.gitignore\n target/\n+Cargo.lock\n ### End of Gixor (default/Rust)\n \n"
+        );
+    }
+
+    /// A block stays at the commit it records when the revision policy asks for it, however far
+    /// the clone moved since.
+    #[cfg(feature = "local")]
+    #[test]
+    fn test_build_gitignore_keeps_the_recorded_revisions() {
+        let (dir, mut gixor, repo) = repo_with(&[("Rust.gitignore", "target/\n")]);
+        let first = git(&repo, &["rev-parse", "HEAD"]);
+        let names = || Name::parse_all(vec!["rust"]);
+        let dumped = gixor.build_gitignore_with(names(), "").unwrap();
+
+        std::fs::write(repo.join("Rust.gitignore"), "target/\nCargo.lock\n").unwrap();
+        commit(&repo, "second", "2024-01-02T00:00:00+0000");
+        let latest = gixor.build_gitignore_with(names(), &dumped).unwrap();
+        assert!(latest.contains("Cargo.lock"), "{latest}");
+
+        gixor.set_revision_policy(document::RevisionPolicy::Recorded);
+        assert_eq!(
+            gixor.build_gitignore_with(names(), &dumped).unwrap(),
+            dumped
+        );
+        // a boilerplate not dumped before has no commit to keep
+        assert_eq!(gixor.build_gitignore_with(names(), "").unwrap(), latest);
+        // a name with a revision moves the block to it, whether or not it is kept otherwise
+        let at_first = || vec![Name::parse(format!("rust@{}", &first[..7]))];
+        assert_eq!(
+            gixor.build_gitignore_with(at_first(), &latest).unwrap(),
+            dumped
+        );
+        gixor.set_revision_policy(document::RevisionPolicy::Latest);
+        assert_eq!(gixor.build_gitignore_with(at_first(), "").unwrap(), dumped);
+
+        // a boilerplate removed upstream is kept at the commit of its block, never dropped
+        git(&repo, &["rm", "-q", "Rust.gitignore"]);
+        commit(&repo, "third", "2024-01-03T00:00:00+0000");
+        assert!(!gixor
+            .build_gitignore_with(names(), &dumped)
+            .unwrap()
+            .contains("target/"));
+        gixor.set_revision_policy(document::RevisionPolicy::Recorded);
+        assert_eq!(
+            gixor.build_gitignore_with(names(), &dumped).unwrap(),
+            dumped
+        );
+        let unknown = dumped.replace(&first.to_uppercase(), &"0".repeat(40));
+        assert!(gixor.build_gitignore_with(names(), &unknown).is_err());
+
+        // and the check expects it there, too
+        let dest = dir.path().join(".gitignore");
+        std::fs::write(&dest, &dumped).unwrap();
+        let check = gixor.check(names(), &dest).unwrap();
+        assert!(check.into_result().is_ok());
+        gixor.set_revision_policy(document::RevisionPolicy::Latest);
+        let check = gixor.check(names(), &dest).unwrap();
+        assert!(matches!(check.into_result(), Err(Error::Mismatch(_))));
+    }
+
+    /// Makes a repository of `Rust` and `Go` as [`repo_with`] does, and dumps both of them into
+    /// the `.gitignore` of the temporary directory, which it returns last.
+    #[cfg(feature = "local")]
+    fn dumped_with_lock() -> (tempfile::TempDir, Gixor, PathBuf, PathBuf) {
+        let files = [
+            ("Rust.gitignore", "target/\n"),
+            ("Go.gitignore", "vendor/\n"),
+        ];
+        let (dir, gixor, repo) = repo_with(&files);
+        let dest = dir.path().join(".gitignore");
+        gixor
+            .dump_to(Name::parse_all(vec!["rust", "go"]), &dest, false)
+            .unwrap();
+        (dir, gixor, repo, dest)
+    }
+
+    /// Returns the boilerplates of the lock of `dest`, each with the commit it is locked at,
+    /// abbreviated.
+    #[cfg(feature = "local")]
+    fn locked_in(dest: &Path) -> Vec<String> {
+        let lock = lock::Lock::load(&lock::path_of(dest)).unwrap();
+        lock.boilerplates
+            .iter()
+            .map(|locked| format!("{} {}", locked.name, &locked.commit[..7]))
+            .collect()
+    }
+
+    #[cfg(feature = "local")]
+    #[test]
+    fn test_dump_records_the_boilerplates_in_the_lock() {
+        let (_dir, _, repo, dest) = dumped_with_lock();
+        let first = git(&repo, &["rev-parse", "--short=7", "HEAD"]);
+        assert_eq!(
+            locked_in(&dest),
+            vec![
+                format!("default/Rust {first}"),
+                format!("default/Go {first}")
+            ]
+        );
+        let lock = lock::Lock::load(&lock::path_of(&dest)).unwrap();
+        assert_eq!(
+            lock.boilerplates[0].url,
+            "https://github.com/github/gitignore.git"
+        );
+        assert_eq!(lock.boilerplates[0].path, "Rust.gitignore");
+    }
+
+    /// A dump keeps a boilerplate at the commit its lock records, however far upstream moves,
+    /// until it is upgraded.
+    #[cfg(feature = "local")]
+    #[test]
+    fn test_the_lock_holds_the_boilerplates_until_upgraded() {
+        let (_dir, gixor, repo, dest) = dumped_with_lock();
+        let first = git(&repo, &["rev-parse", "--short=7", "HEAD"]);
+        let dumped = std::fs::read_to_string(&dest).unwrap();
+        std::fs::write(repo.join("Rust.gitignore"), "target/\nCargo.lock\n").unwrap();
+        std::fs::write(repo.join("Go.gitignore"), "vendor/\ngo.work\n").unwrap();
+        let second = commit(&repo, "second", "2024-01-02T00:00:00+0000");
+        let report = gixor
+            .dump_to(Name::parse_all(vec!["rust", "go"]), &dest, false)
+            .unwrap();
+        assert!(report.is_unchanged(), "{report}");
+        assert_eq!(std::fs::read_to_string(&dest).unwrap(), dumped);
+
+        let report = gixor.upgrade(Name::parse_all(vec!["rust"]), &dest).unwrap();
+        assert_eq!(report.updated.len(), 1, "{report}");
+        assert_eq!(
+            locked_in(&dest),
+            vec![
+                format!("default/Rust {}", &second[..7]),
+                format!("default/Go {first}")
+            ]
+        );
+        assert!(matches!(
+            gixor.upgrade(Name::parse_all(vec!["default/Python"]), &dest),
+            Err(Error::BoilerplateNotFound(..))
+        ));
+    }
+
+    /// A boilerplate removed upstream stays as the lock has it until upgraded, which drops it.
+    #[cfg(feature = "local")]
+    #[test]
+    fn test_the_lock_keeps_a_removed_boilerplate_until_upgraded() {
+        let (_dir, gixor, repo, dest) = dumped_with_lock();
+        let first = git(&repo, &["rev-parse", "--short=7", "HEAD"]);
+        let dumped = std::fs::read_to_string(&dest).unwrap();
+        git(&repo, &["rm", "-q", "Go.gitignore"]);
+        commit(&repo, "second", "2024-01-02T00:00:00+0000");
+        let report = gixor
+            .dump_to(Name::parse_all(vec!["rust", "go"]), &dest, false)
+            .unwrap();
+        assert!(report.is_unchanged(), "{report}");
+        assert_eq!(std::fs::read_to_string(&dest).unwrap(), dumped);
+        assert_eq!(locked_in(&dest).len(), 2);
+
+        let report = gixor.upgrade(vec![], &dest).unwrap();
+        assert_eq!(report.dropped.len(), 1, "{report}");
+        assert_eq!(report.dropped[0].name.to_string(), "default/Go");
+        assert_eq!(locked_in(&dest), vec![format!("default/Rust {first}")]);
+    }
+
+    /// The lock alone rebuilds the gitignore, and there is nothing to sync without one.
+    #[cfg(feature = "local")]
+    #[test]
+    fn test_sync_rebuilds_the_gitignore_from_the_lock() {
+        let (dir, mut gixor, repo, dest) = dumped_with_lock();
+        let dumped = std::fs::read_to_string(&dest).unwrap();
+        std::fs::write(repo.join("Rust.gitignore"), "target/\nCargo.lock\n").unwrap();
+        commit(&repo, "second", "2024-01-02T00:00:00+0000");
+        std::fs::remove_file(&dest).unwrap();
+        gixor.sync(&dest).unwrap();
+        assert_eq!(std::fs::read_to_string(&dest).unwrap(), dumped);
+        assert!(matches!(
+            gixor.sync(dir.path().join("web.gitignore")),
+            Err(Error::FileNotFound(_))
+        ));
+    }
+
+    /// Gitignores side by side each have a lock of their own.
+    #[cfg(feature = "local")]
+    #[test]
+    fn test_each_gitignore_has_its_own_lock() {
+        let files = [
+            ("Rust.gitignore", "target/\n"),
+            ("Go.gitignore", "vendor/\n"),
+        ];
+        let (dir, gixor, _) = repo_with(&files);
+        let root = dir.path().join(".gitignore");
+        let web = dir.path().join("web.gitignore");
+        gixor
+            .dump_to(Name::parse_all(vec!["rust"]), &root, false)
+            .unwrap();
+        gixor
+            .dump_to(Name::parse_all(vec!["go"]), &web, false)
+            .unwrap();
+        let locked = |path: &Path| {
+            let lock = lock::Lock::load(&lock::path_of(path)).unwrap();
+            lock.boilerplates
+                .iter()
+                .map(|locked| locked.name.to_string())
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(locked(&root), vec!["default/Rust"]);
+        assert_eq!(locked(&web), vec!["default/Go"]);
+    }
+
+    /// The lock records the edits merged into a block, and sync refuses to rebuild the block
+    /// without them.
+    #[cfg(feature = "local")]
+    #[test]
+    fn test_sync_keeps_the_merged_edits_or_refuses() {
+        let (dir, mut gixor, _) = repo_with(&[("Rust.gitignore", "target/\n")]);
+        let dest = dir.path().join(".gitignore");
+        let names = || Name::parse_all(vec!["rust"]);
+        gixor.dump_to(names(), &dest, false).unwrap();
+        let dumped = std::fs::read_to_string(&dest).unwrap();
+        std::fs::write(&dest, dumped.replace("target/\n", "target/\n*.local\n")).unwrap();
+        gixor.set_edit_policy(document::EditPolicy::Merge);
+        gixor.dump_to(names(), &dest, false).unwrap();
+        let merged = std::fs::read_to_string(&dest).unwrap();
+        let lock = lock::Lock::load(&lock::path_of(&dest)).unwrap();
+        let block = document::GitignoreDocument::parse(&merged);
+        let block = block.blocks().next().unwrap();
+        assert_eq!(lock.boilerplates[0].merged.as_deref(), block.merged());
+
+        gixor.set_edit_policy(document::EditPolicy::Refuse);
+        gixor.sync(&dest).unwrap();
+        assert_eq!(std::fs::read_to_string(&dest).unwrap(), merged);
+        std::fs::remove_file(&dest).unwrap();
+        match gixor.sync(&dest) {
+            Err(Error::LockMismatch(names)) => assert_eq!(names, vec!["default/Rust"]),
+            r => panic!("{r:?}"),
+        }
+        assert!(!dest.exists());
+        gixor.set_edit_policy(document::EditPolicy::Overwrite);
+        gixor.sync(&dest).unwrap();
+        assert_eq!(std::fs::read_to_string(&dest).unwrap(), dumped);
+    }
+
+    /// The check expects the edits `dump --merge` merged, and tells the ones made since.
+    #[cfg(feature = "local")]
+    #[test]
+    fn test_check_keeps_the_merged_edits() {
+        let (dir, mut gixor, _) = repo_with(&[("Rust.gitignore", "target/\n")]);
+        let dest = dir.path().join(".gitignore");
+        let names = || Name::parse_all(vec!["rust"]);
+        gixor.dump_to(names(), &dest, false).unwrap();
+        let dumped = std::fs::read_to_string(&dest).unwrap();
+        std::fs::write(&dest, dumped.replace("target/\n", "target/\n*.local\n")).unwrap();
+        gixor.set_edit_policy(document::EditPolicy::Merge);
+        gixor.dump_to(names(), &dest, false).unwrap();
+        let merged = std::fs::read_to_string(&dest).unwrap();
+        assert!(merged.contains(document::MERGED), "{merged}");
+
+        gixor.set_edit_policy(document::EditPolicy::Refuse);
+        let check = gixor.check(names(), &dest).unwrap();
+        assert!(check.into_result().is_ok());
+
+        std::fs::write(&dest, merged.replace("*.local\n", "*.local\n*.bak\n")).unwrap();
+        let check = gixor.check(names(), &dest).unwrap();
+        assert_eq!(check.expected, dumped);
+        assert!(matches!(check.into_result(), Err(Error::Mismatch(_))));
+    }
+
+    /// Each block is compared with the history of the clone since the commit it records.
+    #[cfg(feature = "local")]
+    #[test]
+    fn test_outdated_follows_the_history_since_the_recorded_commit() {
+        let files = ["Foo", "Bar", "Baz", "Qux"]
+            .map(|name| (format!("{name}.gitignore"), format!("{name}\n")));
+        let files = files
+            .iter()
+            .map(|(path, content)| (path.as_str(), content.as_str()));
+        let (dir, gixor, repo) = repo_with(&files.collect::<Vec<_>>());
+        let dest = dir.path().join(".gitignore");
+        let names = Name::parse_all(vec!["Foo", "Bar", "Baz", "Qux"]);
+        gixor.dump_to(names, &dest, false).unwrap();
+
+        std::fs::write(repo.join("Foo.gitignore"), "Foo\nfoo/\n").unwrap();
+        commit(&repo, "Update Foo", "2024-01-02T00:00:00+0000");
+        std::fs::create_dir_all(repo.join("Global")).unwrap();
+        git(&repo, &["mv", "Bar.gitignore", "Global/Bar.gitignore"]);
+        git(&repo, &["rm", "-q", "Baz.gitignore"]);
+        commit(&repo, "Move Bar and drop Baz", "2024-01-03T00:00:00+0000");
+
+        let outdated = gixor.outdated(&dest).unwrap();
+        let lags = outdated.boilerplates;
+        let statuses = lags.iter().map(|lag| lag.to_string()).collect::<Vec<_>>();
+        assert_eq!(statuses.len(), 4);
+        assert!(
+            statuses[0].starts_with("default/Foo: 1 commit behind\n    "),
+            "{}",
+            statuses[0]
+        );
+        assert!(statuses[0].ends_with(" Update Foo"), "{}", statuses[0]);
+        assert_eq!(statuses[1], "default/Bar: renamed to default/Global/Bar");
+        assert_eq!(statuses[2], "default/Baz: removed upstream");
+        assert_eq!(statuses[3], "default/Qux: up to date");
+        assert!(lags.iter().take(3).all(Lag::is_behind));
+        assert!(!lags[3].is_behind());
+
+        // with no commit recorded, there is no telling
+        std::fs::write(&dest, "### Generated by Gixor (https://github.com/tamada/gixor) (Qux)\n### Qux.gitignore\nQux\n").unwrap();
+        let outdated = gixor.outdated(&dest).unwrap();
+        let unknown = &outdated.boilerplates[0];
+        assert!(matches!(unknown.freshness, Freshness::Unknown { .. }));
+        assert!(!unknown.is_behind());
+    }
+
+    #[test]
+    fn test_outdated_to_json() {
+        let lag = |name: &str, freshness| Lag {
+            name: Name::parse(name),
+            commit: Some("0123abcd".into()),
+            freshness,
+        };
+        let outdated = Outdated {
+            path: PathBuf::from(".gitignore"),
+            boilerplates: vec![
+                lag("default/Rust", Freshness::UpToDate),
+                lag(
+                    "default/Go",
+                    Freshness::Outdated {
+                        commits: vec![repos::Commit {
+                            id: vec![0xab, 0xcd],
+                            summary: "Update Go".into(),
+                        }],
+                    },
+                ),
+                lag(
+                    "default/Bar",
+                    Freshness::Renamed {
+                        to: Name::parse("default/Global/Bar"),
+                    },
+                ),
+            ],
+        };
+        assert_eq!(
+            serde_json::to_string(&outdated).unwrap(),
+            r#"{"path":".gitignore","boilerplates":[{"name":"default/Rust","commit":"0123abcd","status":"up-to-date"},{"name":"default/Go","commit":"0123abcd","status":"outdated","commits":[{"id":"abcd","summary":"Update Go"}]},{"name":"default/Bar","commit":"0123abcd","status":"renamed","to":"default/Global/Bar"}]}"#
+        );
+        assert!(!outdated.is_up_to_date());
+        assert!(matches!(
+            outdated.into_result(),
+            Err(Error::Outdated(names)) if names == vec!["default/Go", "default/Bar"]
+        ));
+    }
+
     #[test]
     fn test_error_display() {
         assert_eq!(
@@ -844,18 +2343,51 @@ mod tests {
             "IO error: hoge"
         );
         assert_eq!(
-            Error::BoilerplateNotFound("name".to_string()).to_string(),
+            Error::BoilerplateNotFound("name".to_string(), vec![]).to_string(),
             "name: boilerplate not found"
         );
+        assert_eq!(
+            Error::BoilerplateNotFound("pyhton".into(), vec!["Python".into(), "Jython".into()])
+                .to_string(),
+            "pyhton: boilerplate not found, did you mean Python, Jython?"
+        );
         assert_eq!(Error::Git("hoge".into()).to_string(), "Git error: hoge");
         assert_eq!(
             Error::AliasNotFound("hoge".into()).to_string(),
             "hoge: alias not found"
         );
+        assert_eq!(
+            Error::Ambiguous(
+                "rust".into(),
+                vec!["default/Rust".into(), "team/Rust".into()]
+            )
+            .to_string(),
+            "rust: ambiguous boilerplate name, qualify it as one of default/Rust, team/Rust"
+        );
         assert_eq!(
             Error::FileNotFound("hoge".into()).to_string(),
             "hoge: file not found"
         );
+        assert_eq!(
+            Error::LockMismatch(vec!["default/Rust".into()]).to_string(),
+            "default/Rust: not the content the lock records"
+        );
+        assert_eq!(
+            Error::Modified(vec!["default/Rust".into(), "default/Go".into()]).to_string(),
+            "default/Rust, default/Go: edited by hand since dumped"
+        );
+        assert_eq!(
+            Error::Conflict("default/Rust".into()).to_string(),
+            "default/Rust: the edits by hand conflict with the changes upstream"
+        );
+        assert_eq!(
+            Error::Mismatch(".gitignore".into()).to_string(),
+            ".gitignore: differs from what gixor dumps"
+        );
+        assert_eq!(
+            Error::Outdated(vec!["default/Rust".into(), "default/Go".into()]).to_string(),
+            "default/Rust, default/Go: changed upstream since dumped"
+        );
         assert_eq!(
             Error::RepositoryNotFound("hoge".into()).to_string(),
             "hoge: repository not found"
@@ -891,6 +2423,18 @@ mod tests {
         let target = Name::parse("devcontainer");
         assert_eq!(target.repository_name, None);
         assert_eq!(target.boilerplate_name, "devcontainer");
+
+        let target = Name::parse("default/community/Golang/Hugo");
+        assert_eq!(target.repository_name, Some("default".to_string()));
+        assert_eq!(target.boilerplate_name, "community/Golang/Hugo");
+        assert_eq!(target.to_string(), "default/community/Golang/Hugo");
+        assert_eq!(target.revision, None);
+
+        let target = Name::parse("default/Rust@v2.0");
+        assert_eq!(target.repository_name, Some("default".to_string()));
+        assert_eq!(target.boilerplate_name, "Rust");
+        assert_eq!(target.revision, Some("v2.0".to_string()));
+        assert_eq!(target.to_string(), "default/Rust@v2.0");
     }
 
     #[test]
@@ -919,6 +2463,8 @@ mod tests {
                 repositories: vec![],
                 base_path: temp_dir.path().join("boilerplates"),
                 aliases: None,
+                update_interval: None,
+                shorthands: None,
             },
             config_path,
         );
@@ -938,6 +2484,323 @@ mod tests {
         assert!(gixor.is_empty());
     }
 
+    /// The clone kept under the name moves along with it, and the aliases follow, while no
+    /// other repository may be renamed over. The embedded snapshot refuses to be renamed.
+    #[cfg(feature = "local")]
+    #[test]
+    fn test_rename_repository() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let base_path = temp_dir.path().join("boilerplates");
+        let aliases: aliases::Aliases = serde_json::from_str(
+            r#"{"aliases": [{"name": "team", "description": "", "boilerplates": ["old/Foo", "other/Bar", "Baz"]}]}"#,
+        )
+        .unwrap();
+        let mut gixor = Gixor::new(
+            Config {
+                repositories: vec![
+                    repos::Repository::new_with("old", "https://github.com/old/gitignore.git"),
+                    repos::Repository::new_with("other", "https://github.com/other/gitignore.git"),
+                ],
+                base_path: base_path.clone(),
+                aliases: Some(aliases),
+                update_interval: None,
+                shorthands: None,
+            },
+            temp_dir.path().join("config.json"),
+        );
+        std::fs::create_dir_all(base_path.join("old")).unwrap();
+        std::fs::write(base_path.join("old").join("Foo.gitignore"), "foo\n").unwrap();
+
+        assert!(gixor.rename_repository("old", "other").is_err());
+        assert!(gixor.rename_repository("old", "a/b").is_err());
+        assert!(gixor.rename_repository("missing", "new").is_err());
+        gixor.rename_repository("old", "new").unwrap();
+
+        assert!(gixor.repository("old").is_none());
+        assert_eq!(gixor.repository("new").unwrap().path, PathBuf::from("new"));
+        assert!(base_path.join("new").join("Foo.gitignore").exists());
+        assert!(!base_path.join("old").exists());
+        let names = gixor.iter_aliases().next().unwrap().boilerplates.iter();
+        assert_eq!(
+            names.map(|name| name.to_string()).collect::<Vec<_>>(),
+            vec!["new/Foo", "other/Bar", "Baz"]
+        );
+    }
+
+    /// A repository moved to another URL keeps its clone, which fetches from the new URL from
+    /// then on.
+    #[cfg(feature = "local")]
+    #[test]
+    fn test_set_repository_url_keeps_the_clone() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let remote = temp_dir.path().join("remote");
+        init_with(&remote, &[("Foo.gitignore", "v1\n")]);
+        let moved = temp_dir.path().join("moved");
+        git(temp_dir.path(), &["clone", "-q", "remote", "moved"]);
+        std::fs::write(moved.join("Foo.gitignore"), "v2\n").unwrap();
+        commit(&moved, "v2", "2024-02-01T00:00:00+0000");
+
+        let mut gixor = gixor_cloning(temp_dir.path(), &remote);
+        let work = gixor.base_path().join("test");
+        std::fs::write(work.join(".git/marker"), "").unwrap();
+
+        let url = moved.to_string_lossy().to_string();
+        gixor.set_repository_url("test", &url).unwrap();
+        let repo = gixor.repository("test").unwrap();
+        assert_eq!(
+            (repo.url.as_str(), repo.repo_name.as_str()),
+            (url.as_str(), "moved")
+        );
+        assert_eq!(git(&work, &["remote", "get-url", "origin"]), url);
+
+        gixor.update().into_result().unwrap();
+        assert_eq!(
+            std::fs::read_to_string(work.join("Foo.gitignore")).unwrap(),
+            "v2\n"
+        );
+        assert!(
+            work.join(".git/marker").exists(),
+            "the clone was made again"
+        );
+        assert!(gixor.set_repository_url("missing", &url).is_err());
+    }
+
+    /// A boilerplate is taken from the repository of the highest priority that has it, or
+    /// refused as ambiguous when the repositories tie, and a disabled repository is neither
+    /// searched nor prepared, while its clone stays.
+    #[cfg(feature = "local")]
+    #[test]
+    fn test_priority_and_enabled() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let base_path = temp_dir.path().join("boilerplates");
+        for name in ["github", "team"] {
+            std::fs::create_dir_all(base_path.join(name)).unwrap();
+            std::fs::write(base_path.join(name).join("Python.gitignore"), name).unwrap();
+        }
+        std::fs::write(base_path.join("team").join("Team.gitignore"), "").unwrap();
+        let mut gixor = Gixor::new(
+            Config {
+                repositories: vec![
+                    repos::Repository::new("https://github.com/github/gitignore.git"),
+                    repos::Repository::new("https://github.com/team/gitignore.git"),
+                ],
+                base_path: base_path.clone(),
+                aliases: None,
+                update_interval: None,
+                shorthands: None,
+            },
+            temp_dir.path().join("config.json"),
+        );
+        let found = |gixor: &Gixor| {
+            let boilerplates = gixor.find(Name::parse("Python")).unwrap();
+            boilerplates[0].repository_name().to_string()
+        };
+        match gixor.find(Name::parse("python")) {
+            Err(Error::Ambiguous(name, candidates)) => {
+                assert_eq!(name, "python");
+                assert_eq!(candidates, vec!["github/Python", "team/Python"]);
+            }
+            _ => panic!("python is in both repositories of the same priority"),
+        }
+        let qualified = gixor.find(Name::parse("team/Python")).unwrap();
+        assert_eq!(qualified[0].repository_name(), "team");
+
+        gixor.set_repository_priority("team", 10).unwrap();
+        assert_eq!(found(&gixor), "team");
+        assert_eq!(gixor.iter().next().unwrap().repository_name(), "team");
+
+        gixor.set_repository_enabled("team", false).unwrap();
+        assert_eq!(found(&gixor), "github");
+        assert!(gixor.find(Name::parse("Team")).is_err());
+        assert_eq!(gixor.iter().count(), 1);
+        let report = gixor.prepare(true);
+        assert_eq!(report.repositories.len(), 1);
+        assert!(base_path.join("team").exists());
+        assert!(gixor.set_repository_enabled("missing", true).is_err());
+
+        gixor.store().unwrap();
+        let stored = std::fs::read_to_string(temp_dir.path().join("config.json")).unwrap();
+        assert!(
+            stored.contains(r#""priority":10,"enabled":false"#),
+            "{stored}"
+        );
+        assert_eq!(stored.matches("priority").count(), 1, "{stored}");
+    }
+
+    /// The directory removed along with a repository is the one configured, not the one
+    /// named after it.
+    #[test]
+    fn test_remove_repository_removes_the_configured_directory() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let base_path = temp_dir.path().join("boilerplates");
+        let mut repo = repos::Repository::new_with("team", "https://github.com/team/gitignore.git");
+        repo.path = PathBuf::from("elsewhere");
+        let mut gixor = Gixor::new(
+            Config {
+                repositories: vec![repo],
+                base_path: base_path.clone(),
+                aliases: None,
+                update_interval: None,
+                shorthands: None,
+            },
+            temp_dir.path().join("config.json"),
+        );
+        std::fs::create_dir_all(base_path.join("elsewhere")).unwrap();
+        std::fs::create_dir_all(base_path.join("team")).unwrap();
+
+        gixor.remove_repository("team").unwrap();
+        assert!(!base_path.join("elsewhere").exists());
+        assert!(base_path.join("team").exists());
+    }
+
+    /// A repository fetched within the update interval is left alone by `prepare`, while
+    /// `update` fetches it regardless.
+    /// A block edited by hand goes with its boilerplate removed upstream only when the edits
+    /// are to be overwritten; an unedited one is dropped as ever.
+    #[cfg(feature = "local")]
+    #[test]
+    fn test_edited_blocks_of_removed_boilerplates_are_not_dropped_silently() {
+        let files = [
+            ("Rust.gitignore", "target/\n"),
+            ("Go.gitignore", "vendor/\n"),
+        ];
+        let (_dir, mut gixor, repo) = repo_with(&files);
+        let names = || Name::parse_all(vec!["rust", "go"]);
+        let dumped = gixor.build_gitignore_with(names(), "").unwrap();
+        let edited = dumped.replace("vendor/\n", "vendor/\n!vendor/keep\n");
+        git(&repo, &["rm", "-q", "Go.gitignore"]);
+        commit(&repo, "second", "2024-01-02T00:00:00+0000");
+
+        let rebuilt = gixor.build_gitignore_with(names(), &dumped).unwrap();
+        assert!(!rebuilt.contains("vendor/"), "{rebuilt}");
+        match gixor.build_gitignore_with(names(), &edited) {
+            Err(Error::Modified(names)) => assert_eq!(names, vec!["default/Go"]),
+            r => panic!("{r:?}"),
+        }
+        gixor.set_edit_policy(document::EditPolicy::Merge);
+        assert!(matches!(
+            gixor.build_gitignore_with(names(), &edited),
+            Err(Error::Conflict(name)) if name == "default/Go"
+        ));
+        gixor.set_edit_policy(document::EditPolicy::Overwrite);
+        let rebuilt = gixor.build_gitignore_with(names(), &edited).unwrap();
+        assert!(!rebuilt.contains("vendor/"), "{rebuilt}");
+    }
+
+    #[cfg(feature = "local")]
+    #[test]
+    fn test_prepare_skips_fresh_repositories_and_update_does_not() {
+        use std::time::Duration;
+
+        let temp_dir = tempfile::tempdir().unwrap();
+        let remote = temp_dir.path().join("remote");
+        init_with(&remote, &[("Foo.gitignore", "v1\n")]);
+        let mut gixor = gixor_cloning(temp_dir.path(), &remote);
+        let work = gixor.base_path().join("test");
+        let content = || std::fs::read_to_string(work.join("Foo.gitignore")).unwrap();
+        assert_eq!(gixor.update_interval(), Duration::from_secs(24 * 60 * 60));
+
+        std::fs::write(remote.join("Foo.gitignore"), "v2\n").unwrap();
+        commit(&remote, "v2", "2024-02-01T00:00:00+0000");
+        gixor.prepare(false).into_result().unwrap();
+        assert_eq!(content(), "v1\n", "a fresh clone was fetched again");
+
+        gixor.set_update_interval(Duration::ZERO);
+        gixor.prepare(false).into_result().unwrap();
+        assert_eq!(content(), "v2\n");
+
+        std::fs::write(remote.join("Foo.gitignore"), "v3\n").unwrap();
+        commit(&remote, "v3", "2024-03-01T00:00:00+0000");
+        gixor.set_update_interval(Duration::from_secs(7 * 24 * 60 * 60));
+        gixor.update().into_result().unwrap();
+        assert_eq!(content(), "v3\n");
+
+        gixor.store().unwrap();
+        let config = temp_dir.path().join("config.json");
+        let stored = std::fs::read_to_string(&config).unwrap();
+        assert!(stored.contains(r#""update-interval":"1w""#), "{stored}");
+        let gixor = GixorFactory::load(&config).unwrap();
+        assert_eq!(
+            gixor.update_interval(),
+            Duration::from_secs(7 * 24 * 60 * 60)
+        );
+    }
+
+    /// The report names each repository in the order of the configuration, with the commits
+    /// it moved between, and a failing repository does not keep the others from being updated.
+    #[cfg(feature = "local")]
+    #[test]
+    fn test_prepare_reports_each_repository() {
+        use repos::Outcome;
+
+        let temp_dir = tempfile::tempdir().unwrap();
+        let remote = temp_dir.path().join("remote");
+        let v1 = init_with(&remote, &[("Foo.gitignore", "v1\n")]);
+        let mut gixor = gixor_cloning(temp_dir.path(), &remote);
+        let nowhere = temp_dir.path().join("nowhere");
+        // added by hand, since add_repository refuses what it cannot clone
+        gixor.config.repositories.push(repos::Repository::new_with(
+            "broken",
+            &nowhere.to_string_lossy(),
+        ));
+
+        std::fs::write(remote.join("Foo.gitignore"), "v2\n").unwrap();
+        let v2 = commit(&remote, "v2", "2024-02-01T00:00:00+0000");
+        let report = gixor.update();
+        let names = report.iter().map(|p| p.name.as_str()).collect::<Vec<_>>();
+        assert_eq!(names, vec!["test", "broken"]);
+        match &report.repositories[0].outcome {
+            Outcome::FastForwarded { from, to } => {
+                assert_eq!(hex::encode(from), v1);
+                assert_eq!(hex::encode(to), v2);
+            }
+            other => panic!("unexpected outcome: {other:?}"),
+        }
+        assert!(matches!(report.repositories[1].outcome, Outcome::Failed(_)));
+        assert!(!report.is_success());
+        assert!(report.into_result().is_err());
+
+        let report = gixor.update();
+        assert!(matches!(
+            &report.repositories[0].outcome,
+            Outcome::Unchanged { commit } if hex::encode(commit) == v2
+        ));
+        assert!(matches!(
+            gixor.prepare(true).repositories[0].outcome,
+            Outcome::Skipped
+        ));
+    }
+
+    /// A clone whose remote has gone away is kept and used when the policy allows it, while a
+    /// repository that was never cloned fails either way.
+    #[cfg(feature = "local")]
+    #[test]
+    fn test_prepare_keeps_stale_clones_by_policy() {
+        use repos::{Outcome, PreparePolicy};
+
+        let temp_dir = tempfile::tempdir().unwrap();
+        let remote = temp_dir.path().join("remote");
+        init_with(&remote, &[("Foo.gitignore", "v1\n")]);
+        let mut gixor = gixor_cloning(temp_dir.path(), &remote);
+        gixor.set_update_interval(std::time::Duration::ZERO);
+        std::fs::remove_dir_all(&remote).unwrap();
+
+        let report = gixor.prepare_with(false, PreparePolicy::KeepStale);
+        assert!(matches!(report.repositories[0].outcome, Outcome::Stale(_)));
+        assert!(report.into_result().is_ok());
+        assert!(gixor.find(Name::parse("Foo")).is_ok());
+
+        let report = gixor.prepare_with(false, PreparePolicy::Strict);
+        assert!(matches!(report.repositories[0].outcome, Outcome::Failed(_)));
+
+        let never = repos::Repository::new_with("never", &remote.to_string_lossy());
+        gixor.config.repositories.push(never);
+        let report = gixor.prepare_with(false, PreparePolicy::KeepStale);
+        assert!(matches!(report.repositories[0].outcome, Outcome::Stale(_)));
+        assert!(matches!(report.repositories[1].outcome, Outcome::Failed(_)));
+        assert!(report.into_result().is_err());
+    }
+
     #[test]
     fn test_alias_manager() {
         let mut gixor = Gixor::new(
@@ -945,6 +2808,8 @@ mod tests {
                 repositories: vec![],
                 base_path: PathBuf::from("."),
                 aliases: None,
+                update_interval: None,
+                shorthands: None,
             },
             PathBuf::from("config.json"),
         );
@@ -966,6 +2831,8 @@ mod tests {
                 repositories: vec![],
                 base_path: PathBuf::from("."),
                 aliases: None,
+                update_interval: None,
+                shorthands: None,
             },
             config_path.clone(),
         );
@@ -980,6 +2847,8 @@ mod tests {
             repositories: vec![],
             base_path: PathBuf::from("boilerplates"),
             aliases: None,
+            update_interval: None,
+            shorthands: None,
         };
         let path = PathBuf::from("/etc/gixor/config.json");
         let updated = update_base_path(config, &path);
@@ -989,6 +2858,8 @@ mod tests {
             repositories: vec![],
             base_path: PathBuf::from("/absolute/path"),
             aliases: None,
+            update_interval: None,
+            shorthands: None,
         };
         let updated2 = update_base_path(config2, &path);
         assert_eq!(updated2.base_path, PathBuf::from("/absolute/path"));
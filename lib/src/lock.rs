@@ -0,0 +1,225 @@
+//! The lock file of a gitignore, kept next to it: `.gixor.lock` for a `.gitignore`, and
+//! `<name>.gixor.lock` for a gitignore of any other name, so that each has its own.
+//!
+//! The header of a block records the commit it was dumped at only when the host of its
+//! repository has permalinks, and nothing in it tells which repository a name such as `default`
+//! stood for. The lock records both for every block, with the checksum of its content, so that
+//! [`crate::Gixor::sync`] builds the same gitignore on any machine, and a dump moves no block
+//! the lock has but those [`crate::Gixor::upgrade`] releases.
+//!
+//! The lock is JSON, laid out over several lines so that a change to it reads well in a diff:
+//!
+//! ```json
+//! {
+//!   "version": 1,
+//!   "boilerplates": [
+//!     {
+//!       "name": "default/Rust",
+//!       "url": "https://github.com/github/gitignore",
+//!       "path": "Rust.gitignore",
+//!       "commit": "0b2f17fda63970b5b3f2e9c5310cefa23d2bb198",
+//!       "checksum": "sha256:26431918e449693f4385438e3955a1e078dbc9a4c78e68d8e6caf7a21647b1ff"
+//!     }
+//!   ]
+//! }
+//! ```
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+
+use crate::repos::{Boilerplate, RepoUrl};
+use crate::{Error, Name, Result};
+
+/// The name of the lock file of a `.gitignore`, kept in the directory of the gitignore it
+/// locks. A gitignore of another name has its name before this one.
+pub const FILE_NAME: &str = ".gixor.lock";
+
+/// The layout of the lock file this version writes.
+const VERSION: u32 = 1;
+
+/// The boilerplates of a gitignore, each with where exactly it was taken from.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Lock {
+    /// The layout of the lock file, for a later version of gixor to tell.
+    pub version: u32,
+    /// The boilerplates in the order of the gitignore.
+    pub boilerplates: Vec<Locked>,
+}
+
+/// A boilerplate of the lock.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Locked {
+    /// The name of the boilerplate, qualified with the repository, as its block records it.
+    pub name: Name,
+    /// The URL of the repository the boilerplate is taken from.
+    pub url: String,
+    /// The path of the boilerplate from the root of the repository, separated by `/`.
+    pub path: String,
+    /// The commit the boilerplate is taken at, in lowercase hex.
+    pub commit: String,
+    /// The checksum of the content of the boilerplate at the commit, such as `sha256:5e8c...`.
+    pub checksum: String,
+    /// The checksum of the content with the edits by hand merged into it, when the block was
+    /// written by [`crate::document::EditPolicy::Merge`]. The merge itself stays in the
+    /// gitignore, which the lock does not hold.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub merged: Option<String>,
+}
+
+impl Default for Lock {
+    fn default() -> Self {
+        Self {
+            version: VERSION,
+            boilerplates: vec![],
+        }
+    }
+}
+
+/// Returns the path of the lock file of the gitignore at `gitignore`, such as `.gixor.lock`
+/// for `.gitignore`, and `web.gitignore.gixor.lock` for `web.gitignore` beside it.
+pub fn path_of(gitignore: &Path) -> PathBuf {
+    match gitignore.file_name() {
+        Some(name) if name != ".gitignore" => {
+            let mut name = name.to_os_string();
+            name.push(FILE_NAME);
+            gitignore.with_file_name(name)
+        }
+        _ => gitignore.with_file_name(FILE_NAME),
+    }
+}
+
+/// Returns the path of a boilerplate as the lock records it, separated by `/` on any platform.
+pub(crate) fn portable(path: &Path) -> String {
+    path.to_string_lossy().replace('\\', "/")
+}
+
+/// Returns `true` if the two URLs are of the same repository: the same host, namespace, and
+/// name, however the URLs reach it, such as over SSH or HTTPS, and with or without `.git`.
+pub(crate) fn same_url(a: &str, b: &str) -> bool {
+    let identity = |url: &str| {
+        RepoUrl::parse(url)
+            .ok()
+            .map(|url| (url.host, url.namespace, url.name))
+    };
+    match (identity(a), identity(b)) {
+        (Some(a), Some(b)) => a == b,
+        _ => a == b,
+    }
+}
+
+impl Lock {
+    /// Reads the lock file at `path`. A missing one is an empty lock, which locks nothing.
+    pub fn load(path: &Path) -> Result<Lock> {
+        let content = match std::fs::read_to_string(path) {
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Lock::default()),
+            Err(e) => return Err(Error::IO(e)),
+            Ok(content) => content,
+        };
+        let lock: Lock = serde_json::from_str(&content).map_err(Error::Json)?;
+        if lock.version > VERSION {
+            return Err(Error::Fatal(format!(
+                "{}: written by a newer version of gixor (version {})",
+                path.display(),
+                lock.version
+            )));
+        }
+        Ok(lock)
+    }
+
+    /// Writes the lock file to `path`, replacing it by a rename as the gitignore is.
+    pub(crate) fn store(&self, path: &Path) -> Result<()> {
+        let mut content = serde_json::to_string_pretty(self).map_err(Error::Json)?;
+        content.push('\n');
+        crate::routine::write_atomically(path, &content)
+    }
+
+    /// Returns the entry of the boilerplate, when the lock has it from the same repository and
+    /// path.
+    pub fn find(&self, boilerplate: &Boilerplate) -> Option<&Locked> {
+        let name = boilerplate.name().to_string();
+        let path = portable(boilerplate.path());
+        self.boilerplates.iter().find(|locked| {
+            locked.name.to_string().eq_ignore_ascii_case(&name)
+                && same_url(&locked.url, &boilerplate.repository().url)
+                && locked.path == path
+        })
+    }
+
+    /// Takes the boilerplate at the commit the lock has it at, unless it was named with a
+    /// revision of its own.
+    pub(crate) fn pin<'a>(&self, boilerplate: Boilerplate<'a>) -> Boilerplate<'a> {
+        if boilerplate.revision().is_some() {
+            return boilerplate;
+        }
+        match self.find(&boilerplate).map(|locked| locked.commit.clone()) {
+            Some(commit) => boilerplate.at(Some(commit)),
+            None => boilerplate,
+        }
+    }
+
+    /// Drops the entries of the boilerplates, so that they are taken at their latest content
+    /// from now on.
+    pub(crate) fn release(&mut self, boilerplates: &[Boilerplate]) {
+        let names = boilerplates
+            .iter()
+            .map(|b| b.name().to_string())
+            .collect::<Vec<_>>();
+        self.boilerplates.retain(|locked| {
+            let name = locked.name.to_string();
+            !names.iter().any(|other| other.eq_ignore_ascii_case(&name))
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_same_url_compares_the_repositories() {
+        let https = "https://github.com/github/gitignore";
+        assert!(same_url(https, "https://github.com/github/gitignore.git"));
+        assert!(same_url(https, "git@github.com:github/gitignore.git"));
+        assert!(same_url(https, "ssh://git@github.com/github/gitignore"));
+        assert!(same_url(https, "https://GitHub.com/github/gitignore/"));
+        assert!(!same_url(https, "https://github.com/tamada/gitignore"));
+        assert!(!same_url(https, "https://gitlab.com/github/gitignore"));
+    }
+
+    #[test]
+    fn test_lock_round_trips_through_the_file() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let path = path_of(&temp_dir.path().join(".gitignore"));
+        assert_eq!(path, temp_dir.path().join(".gixor.lock"));
+        let other = path_of(&temp_dir.path().join("web.gitignore"));
+        assert_eq!(other, temp_dir.path().join("web.gitignore.gixor.lock"));
+        // no lock file locks nothing
+        assert!(Lock::load(&path).unwrap().boilerplates.is_empty());
+
+        let lock = Lock {
+            version: VERSION,
+            boilerplates: vec![Locked {
+                name: Name::parse("default/Rust"),
+                url: "https://github.com/github/gitignore".into(),
+                path: "Rust.gitignore".into(),
+                commit: "0b2f17fda63970b5b3f2e9c5310cefa23d2bb198".into(),
+                checksum: "sha256:2643".into(),
+                merged: None,
+            }],
+        };
+        lock.store(&path).unwrap();
+        let content = std::fs::read_to_string(&path).unwrap();
+        assert!(
+            content.contains("\n      \"name\": \"default/Rust\",\n"),
+            "{content}"
+        );
+        assert!(!content.contains("merged"), "{content}");
+        let loaded = Lock::load(&path).unwrap();
+        assert_eq!(loaded.boilerplates.len(), 1);
+        assert_eq!(loaded.boilerplates[0].name.to_string(), "default/Rust");
+        assert_eq!(loaded.boilerplates[0].commit, lock.boilerplates[0].commit);
+
+        std::fs::write(&path, content.replace("\"version\": 1", "\"version\": 2")).unwrap();
+        assert!(matches!(Lock::load(&path), Err(Error::Fatal(_))));
+    }
+}
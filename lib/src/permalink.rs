@@ -0,0 +1,212 @@
+//! The `permalink-template` of a repository: where the header of a dumped boilerplate points.
+//!
+//! Every Git host lays out the URL of a file at a commit in its own way. A repository may give
+//! the layout as a template, such as `{base}/{owner}/{repo}/raw/{commit}/{path}`, or as the name
+//! of one of the [`PRESETS`], such as `gitea` for a self-hosted Gitea. A repository giving none
+//! is looked up by its host among the [`HOSTS`] gixor knows, and a host it does not know gets
+//! no permalink at all, rather than failing the dump over a comment.
+//!
+//! A template may use the following placeholders.
+//!
+//! - `{base}`: the web address of the host, such as `https://gitlab.example.com`.
+//! - `{host}`: the host name alone.
+//! - `{owner}`: the owner of the repository, which is the whole group path on GitLab.
+//! - `{repo}`: the name of the repository.
+//! - `{commit}`: the hash of the commit that last changed the boilerplate, in lowercase.
+//! - `{COMMIT}`: the same in uppercase, which the permalinks of gixor have always used.
+//! - `{path}`: the path of the boilerplate from the root of the repository.
+use std::path::Path;
+
+use crate::repos::{RepoUrl, Repository};
+
+/// The layouts gixor knows by name. The ones for GitHub, GitLab and Bitbucket write the commit
+/// in uppercase, as gixor has always written them, so that dumping again changes no header.
+pub(crate) const PRESETS: [(&str, &str); 7] = [
+    ("github", "https://raw.github.com/{owner}/{repo}/{COMMIT}/{path}"),
+    ("gitlab", "{base}/{owner}/{repo}/-/raw/{COMMIT}/{path}"),
+    ("bitbucket", "{base}/{owner}/{repo}/raw/{COMMIT}/{path}"),
+    ("gitea", "{base}/{owner}/{repo}/raw/commit/{commit}/{path}"),
+    ("forgejo", "{base}/{owner}/{repo}/raw/commit/{commit}/{path}"),
+    (
+        "azure-devops",
+        "{base}/{owner}/_apis/git/repositories/{repo}/items?path=/{path}&versionDescriptor.version={commit}&versionDescriptor.versionType=commit",
+    ),
+    ("sourcehut", "{base}/{owner}/{repo}/blob/{commit}/{path}"),
+];
+
+/// The hosts whose layout is known without a `permalink-template`.
+const HOSTS: [(&str, &str); 8] = [
+    ("github.com", "github"),
+    ("gitlab.com", "gitlab"),
+    ("bitbucket.org", "bitbucket"),
+    ("gitea.com", "gitea"),
+    ("codeberg.org", "forgejo"),
+    ("dev.azure.com", "azure-devops"),
+    ("ssh.dev.azure.com", "azure-devops"),
+    ("git.sr.ht", "sourcehut"),
+];
+
+fn preset(name: &str) -> Option<&'static str> {
+    PRESETS
+        .iter()
+        .find(|(preset, _)| *preset == name)
+        .map(|(_, template)| *template)
+}
+
+/// Returns the template the permalinks of `repo` are made with, or `None` when its host is not
+/// known and it gives no template of its own.
+pub(crate) fn template_of(repo: &Repository) -> Option<&str> {
+    match repo.permalink_template.as_deref() {
+        Some(template) if template.contains('{') => Some(template),
+        Some(name) => {
+            let template = preset(name);
+            if template.is_none() {
+                log::warn!(
+                    "{}: unknown permalink-template preset {name}, expected one of {}",
+                    repo.name,
+                    PRESETS.map(|(name, _)| name).join(", ")
+                );
+            }
+            template
+        }
+        None => {
+            let host = RepoUrl::parse(&repo.url).ok()?.host?;
+            HOSTS
+                .iter()
+                .find(|(known, _)| *known == host)
+                .and_then(|(_, name)| preset(name))
+        }
+    }
+}
+
+/// Fills `template` in for the boilerplate at `path` of `repo`, last changed by `commit`.
+pub(crate) fn render(template: &str, repo: &Repository, commit: &[u8], path: &Path) -> String {
+    let url = RepoUrl::parse(&repo.url).ok();
+    let base = url.as_ref().and_then(RepoUrl::web_base).unwrap_or_default();
+    let host = url.and_then(|url| url.host).unwrap_or_default();
+    let commit = hex::encode(commit);
+    template
+        .replace("{base}", &base)
+        .replace("{host}", &host)
+        .replace("{owner}", &repo.owner)
+        .replace("{repo}", &repo.repo_name)
+        .replace("{COMMIT}", &commit.to_uppercase())
+        .replace("{commit}", &commit)
+        .replace("{path}", &path.to_string_lossy().replace('\\', "/"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn repository(url: &str, template: Option<&str>) -> Repository {
+        let mut repo = Repository::new(url);
+        repo.permalink_template = template.map(String::from);
+        repo
+    }
+
+    fn permalink(url: &str, template: Option<&str>) -> Option<String> {
+        let repo = repository(url, template);
+        let commit = hex::decode("0b2f17fda63970b5b3f2e9c5310cefa23d2bb198").unwrap();
+        template_of(&repo).map(|t| render(t, &repo, &commit, Path::new("Global/macOS.gitignore")))
+    }
+
+    #[test]
+    fn test_known_hosts_need_no_template() {
+        let commit = "0b2f17fda63970b5b3f2e9c5310cefa23d2bb198";
+        let upper = commit.to_uppercase();
+        let cases = [
+            (
+                "https://github.com/github/gitignore.git",
+                format!("https://raw.github.com/github/gitignore/{upper}/Global/macOS.gitignore"),
+            ),
+            (
+                "git@github.com:github/gitignore.git",
+                format!("https://raw.github.com/github/gitignore/{upper}/Global/macOS.gitignore"),
+            ),
+            (
+                "https://gitlab.com/htamada/gitignore.git",
+                format!("https://gitlab.com/htamada/gitignore/-/raw/{upper}/Global/macOS.gitignore"),
+            ),
+            (
+                "https://gitlab.com/group/sub/gitignore.git",
+                format!("https://gitlab.com/group/sub/gitignore/-/raw/{upper}/Global/macOS.gitignore"),
+            ),
+            (
+                "git@ssh.dev.azure.com:v3/org/project/gitignore",
+                format!(
+                    "https://dev.azure.com/org/project/_apis/git/repositories/gitignore/items?path=/Global/macOS.gitignore&versionDescriptor.version={commit}&versionDescriptor.versionType=commit"
+                ),
+            ),
+            (
+                "https://bitbucket.org/owner/gitignore.git",
+                format!("https://bitbucket.org/owner/gitignore/raw/{upper}/Global/macOS.gitignore"),
+            ),
+            (
+                "https://codeberg.org/owner/gitignore.git",
+                format!(
+                    "https://codeberg.org/owner/gitignore/raw/commit/{commit}/Global/macOS.gitignore"
+                ),
+            ),
+            (
+                "https://git.sr.ht/~owner/gitignore",
+                format!("https://git.sr.ht/~owner/gitignore/blob/{commit}/Global/macOS.gitignore"),
+            ),
+        ];
+        for (url, expected) in cases {
+            assert_eq!(permalink(url, None), Some(expected), "{url}");
+        }
+    }
+
+    #[test]
+    fn test_presets_and_templates_serve_the_other_hosts() {
+        let commit = "0b2f17fda63970b5b3f2e9c5310cefa23d2bb198";
+        assert_eq!(
+            permalink("https://git.example.com/owner/gitignore.git", Some("gitea")),
+            Some(format!(
+                "https://git.example.com/owner/gitignore/raw/commit/{commit}/Global/macOS.gitignore"
+            ))
+        );
+        assert_eq!(
+            permalink("http://git.example.com:3000/owner/gitignore.git", Some("forgejo")),
+            Some(format!(
+                "http://git.example.com:3000/owner/gitignore/raw/commit/{commit}/Global/macOS.gitignore"
+            ))
+        );
+        assert_eq!(
+            permalink(
+                "ssh://git@gitlab.example.com:2222/owner/gitignore.git",
+                Some("gitlab")
+            ),
+            Some(format!(
+                "https://gitlab.example.com/owner/gitignore/-/raw/{}/Global/macOS.gitignore",
+                commit.to_uppercase()
+            ))
+        );
+        assert_eq!(
+            permalink(
+                "https://git.example.com/owner/gitignore.git",
+                Some("{base}/{owner}/{repo}/raw/{commit}/{path}?host={host}")
+            ),
+            Some(format!(
+                "https://git.example.com/owner/gitignore/raw/{commit}/Global/macOS.gitignore?host=git.example.com"
+            ))
+        );
+    }
+
+    #[test]
+    fn test_unknown_hosts_have_no_permalink() {
+        assert_eq!(
+            permalink("https://git.example.com/owner/gitignore.git", None),
+            None
+        );
+        assert_eq!(permalink("/srv/git/gitignore", None), None);
+        assert_eq!(
+            permalink(
+                "https://git.example.com/owner/gitignore.git",
+                Some("no-such-preset")
+            ),
+            None
+        );
+    }
+}
@@ -0,0 +1,107 @@
+//! Progress of cloning and fetching the boilerplate repositories.
+//!
+//! Cloning a repository as large as github/gitignore takes a while, and a tool that sits there
+//! without a word for it looks hung. [`crate::Gixor::prepare`] and
+//! [`crate::RepositoryManager::add_repository`] therefore tell an [`Observer`] how far they got,
+//! repository by repository. The library renders nothing itself: what to show, and whether to
+//! show anything at all, is up to the observer, which [`crate::Gixor::set_observer`] installs.
+use std::fmt::Display;
+use std::sync::Arc;
+
+/// A stage of bringing the objects of a repository over, in the order they happen.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Phase {
+    /// The remote counts the objects it is going to send.
+    Counting,
+    /// The remote compresses the objects it is going to send.
+    Compressing,
+    /// The objects arrive.
+    Receiving,
+    /// The objects that arrived as deltas are resolved.
+    Resolving,
+    /// The files are written to the working tree.
+    CheckingOut,
+}
+
+impl Display for Phase {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        use Phase::*;
+        match self {
+            Counting => write!(f, "counting objects"),
+            Compressing => write!(f, "compressing objects"),
+            Receiving => write!(f, "receiving objects"),
+            Resolving => write!(f, "resolving deltas"),
+            CheckingOut => write!(f, "checking out files"),
+        }
+    }
+}
+
+/// What an [`Observer`] is told about a repository.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Event {
+    /// Work on the repository began.
+    Started,
+    /// A phase got further. `done` and `total` count objects, or files for
+    /// [`Phase::CheckingOut`], and `total` is `None` while it is not known yet. `bytes` is how
+    /// much has been received so far, where that is known.
+    Progress {
+        phase: Phase,
+        done: u64,
+        total: Option<u64>,
+        bytes: Option<u64>,
+    },
+    /// Work on the repository ended, whether it went well or not; the
+    /// [`crate::repos::Report`] tells which.
+    Finished,
+}
+
+/// Receives the progress of the repositories being prepared.
+///
+/// The repositories are prepared concurrently, so the events of several repositories arrive
+/// interleaved and from several threads at once; `repository` is the name of the repository
+/// an event is about.
+pub trait Observer: Send + Sync {
+    fn on_event(&self, repository: &str, event: &Event);
+}
+
+/// The observer that ignores everything, which is what [`crate::Gixor`] starts with.
+pub struct Silent;
+
+impl Observer for Silent {
+    fn on_event(&self, _repository: &str, _event: &Event) {}
+}
+
+/// Reports the progress of one repository to an [`Observer`].
+///
+/// This is what the Git implementations are handed, so that they need to know neither which
+/// repository they work on nor who is listening.
+#[derive(Clone)]
+pub struct Reporter {
+    repository: String,
+    observer: Arc<dyn Observer>,
+}
+
+impl Reporter {
+    /// Creates a reporter telling `observer` about `repository`.
+    pub fn new<S: AsRef<str>>(repository: S, observer: Arc<dyn Observer>) -> Self {
+        Self {
+            repository: repository.as_ref().to_string(),
+            observer,
+        }
+    }
+
+    /// Creates a reporter nobody listens to.
+    pub fn silent() -> Self {
+        Self::new("", Arc::new(Silent))
+    }
+
+    /// Returns the name of the repository reported on.
+    pub fn repository(&self) -> &str {
+        &self.repository
+    }
+
+    /// Tells the observer about `event`.
+    pub fn report(&self, event: Event) {
+        self.observer.on_event(&self.repository, &event);
+    }
+}
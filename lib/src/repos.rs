@@ -1,15 +1,17 @@
 //! Module for managing repositories and boilerplates.
 //! This module provides functionality to define repositories, find boilerplates,
 //! and prepare repositories by cloning or pulling from remote sources.
-use std::{
-    fmt::Write,
-    path::{Path, PathBuf},
-};
+use std::path::{Path, PathBuf};
 
 use serde::{Deserialize, Serialize};
 
+use crate::progress::Reporter;
 use crate::{Error, Name, Result};
 
+mod url;
+
+pub use url::RepoUrl;
+
 /// Represents a boilerplate file.
 pub struct Boilerplate<'a> {
     /// The boilerplate name. It is the stem of the boilerplate file.
@@ -20,10 +22,16 @@ pub struct Boilerplate<'a> {
     path: PathBuf,
     /// The repository of this boilerplate.
     repo: &'a Repository,
+    /// The revision to take the boilerplate at, from the [`Name`] it was found by.
+    revision: Option<String>,
 }
 
 impl<'a> Boilerplate<'a> {
-    pub(crate) fn new<P: AsRef<Path>>(name: String, path: P, repo: &'a Repository) -> Boilerplate<'a> {
+    pub(crate) fn new<P: AsRef<Path>>(
+        name: String,
+        path: P,
+        repo: &'a Repository,
+    ) -> Boilerplate<'a> {
         log::debug!(
             "Boilerplate::new(name: {name}, path: {:?}, repo: {:?})",
             path.as_ref(),
@@ -33,12 +41,31 @@ impl<'a> Boilerplate<'a> {
             name,
             path: path.as_ref().to_path_buf(),
             repo,
+            revision: None,
+        }
+    }
+
+    /// Takes this boilerplate at `revision`, if it is given, and as it was taken otherwise.
+    pub(crate) fn at(self, revision: Option<String>) -> Self {
+        match revision {
+            Some(revision) => Self {
+                revision: Some(revision),
+                ..self
+            },
+            None => self,
         }
     }
 
-    /// Returns the name of this boilerplate as [Name].
+    /// Returns the revision this boilerplate is taken at, as its [`Name`] gave it, if any.
+    pub fn revision(&self) -> Option<&str> {
+        self.revision.as_deref()
+    }
+
+    /// Returns the name of this boilerplate as [Name], qualified with the repository and
+    /// spelling out the path of the file, such as `default/Global/macOS`, which names it
+    /// however many files of the repository share its stem.
     pub fn name(&self) -> Name {
-        Name::new(self.repository_name(), self.boilerplate_name())
+        Name::new(self.repository_name().to_string(), self.path_name())
     }
 
     /// Returns the file path of the boilerplate from the base path.
@@ -72,20 +99,53 @@ impl<'a> Boilerplate<'a> {
         self.name.as_ref()
     }
 
+    /// Returns the path of the boilerplate file from the repository root without the extension,
+    /// separated by `/`, such as `Global/macOS` or `Rust`.
+    pub fn path_name(&self) -> String {
+        self.path
+            .with_extension("")
+            .to_string_lossy()
+            .replace('\\', "/")
+    }
+
     /// Returns the repository name of this instance.
     pub fn repository_name(&self) -> &str {
         &self.repo.name
     }
 
+    /// Returns the repository of this instance.
+    pub fn repository(&self) -> &'a Repository {
+        self.repo
+    }
+
     /// Returns `true` if the given name and this instance are matched.
+    ///
+    /// A boilerplate is called by its stem, such as `macOS`, or by its path, such as
+    /// `Global/macOS`, either of which may be qualified with the repository, such as
+    /// `default/macOS` or `default/Global/macOS`. Since [`Name::parse`] cannot tell a
+    /// repository from a directory, the first part of `Global/macOS` is tried as both.
     pub fn matches(&self, name: &Name) -> bool {
-        name.boilerplate_name.to_lowercase() == self.boilerplate_name().to_lowercase()
-            && match &name.repository_name {
-                Some(repo_name) => {
-                    repo_name.to_lowercase() == self.repository_name().to_lowercase()
-                }
-                None => true,
+        match &name.repository_name {
+            Some(repo_name) if same(repo_name, self.repository_name()) => {
+                self.is_called(&name.boilerplate_name)
             }
+            Some(dir) => same(
+                &format!("{dir}/{}", name.boilerplate_name),
+                &self.path_name(),
+            ),
+            None => self.is_called(&name.boilerplate_name),
+        }
+    }
+
+    /// Returns `true` if `name` spells out the path of this boilerplate, rather than only
+    /// its stem.
+    pub(crate) fn is_at(&self, name: &Name) -> bool {
+        let path = self.path_name();
+        same(&name.boilerplate_name, &path) || same(&name.to_string(), &path)
+    }
+
+    fn is_called(&self, name: &str) -> bool {
+        same(name, self.boilerplate_name()) || same(name, &self.path_name())
     }
 
     /// Returns the latest commit hash (as bytes) of this boilerplate in the repository located at base_path.
@@ -97,65 +157,103 @@ impl<'a> Boilerplate<'a> {
         crate::source::hash(self, base_path)
     }
 
-    /// Returns the content URL of the boilerplate file.
-    pub fn content_url<P: AsRef<Path>>(&self, base_path: P) -> Result<String> {
+    /// Returns the commit (as bytes) [`Boilerplate::dump`] takes the boilerplate at: the one its
+    /// revision resolves to, or the latest one otherwise, as [`Boilerplate::hash`] finds.
+    pub fn commit<P: AsRef<Path>>(&self, base_path: P) -> Result<Vec<u8>> {
+        match &self.revision {
+            Some(rev) => {
+                let revision = crate::source::resolve(self.repo, rev, base_path.as_ref())?;
+                log::info!("{}: taken at {revision}", self.name());
+                Ok(revision.commit)
+            }
+            None => self.hash(base_path),
+        }
+    }
+
+    /// Returns the permalink of the boilerplate file, the URL of its content at the commit that
+    /// last changed it, or `None` when there is no telling how the host of the repository lays
+    /// such URLs out.
+    ///
+    /// The layout is the `permalink-template` of the repository, or, without one, the preset
+    /// of its host where gixor knows the host.
+    pub fn permalink<P: AsRef<Path>>(&self, base_path: P) -> Result<Option<String>> {
         let base_path = base_path.as_ref();
         log::info!(
-            "boilerplate content_url(name: {}, path: {:?}, repo: {:?}, base_path: {:?})",
+            "boilerplate permalink(name: {}, path: {:?}, repo: {:?}, base_path: {:?})",
             self.name,
             self.path,
             self.repo.path,
             base_path
         );
+        let Some(template) = crate::permalink::template_of(self.repo) else {
+            log::info!("{}: no permalink-template for the host", self.repo.url);
+            return Ok(None);
+        };
         let hash = self.hash(base_path)?;
         log::trace!("hash: {hash:02x?}");
-        let hash_string = hash.iter().fold(String::new(), |mut output, b| {
-            let _ = write!(output, "{b:02X}");
-            output
-        });
-        let url = &self.repo.url;
-        if url.contains("github.com") {
-            Ok(format!(
-                "https://raw.github.com/{0}/{1}/{2}/{3}",
-                self.repo.owner,
-                self.repo.repo_name,
-                hash_string,
-                self.path.to_string_lossy()
-            ))
-        } else if url.contains("gitlab.com") {
-            Ok(format!(
-                "https://gitlab.com/{0}/{1}/-/raw/{2}/{3}",
-                self.repo.owner,
-                self.repo.repo_name,
-                hash_string,
-                self.path.to_string_lossy()
-            ))
-        } else if url.contains("bitbucket.org") {
-            Ok(format!(
-                "https://bitbucket.org/{0}/{1}/raw/{2}/{3}",
-                self.repo.owner,
-                self.repo.repo_name,
-                hash_string,
-                self.path.to_string_lossy()
-            ))
-        } else {
-            Err(Error::Fatal(format!("{url}: Unsupported repository host")))
-        }
+        Ok(Some(crate::permalink::render(
+            template, self.repo, &hash, &self.path,
+        )))
+    }
+
+    /// Returns the content URL of the boilerplate file.
+    /// Unlike [`Boilerplate::permalink`], a repository on a host with no permalink is an error.
+    pub fn content_url<P: AsRef<Path>>(&self, base_path: P) -> Result<String> {
+        self.permalink(base_path)?
+            .ok_or_else(|| Error::Fatal(format!("{}: Unsupported repository host", self.repo.url)))
     }
 
     /// Returns the content of the boilerplate file.
+    ///
+    /// The header names the permalink of the boilerplate, or its path in the repository when it
+    /// has none, which still tells where it came from without failing the whole dump.
+    /// The end marker after the content tells where the block ends, so that whatever the user
+    /// writes below it is theirs and survives the next dump. The checksum of the content tells
+    /// whether the user edited the block itself.
+    ///
+    /// A boilerplate taken at a revision is dumped as it was at the commit the revision
+    /// resolves to, as [`Boilerplate::dump_at`] does.
     pub fn dump<P: AsRef<Path>>(&self, base_path: P) -> Result<String> {
+        if self.revision.is_some() {
+            let commit = self.commit(&base_path)?;
+            return self.dump_at(base_path, &commit);
+        }
         let content = crate::source::read(self, &base_path)?;
-        Ok(format!(
-            r#"### Generated by Gixor (https://github.com/tamada/gixor) ({}/{})
-### {}
-{}
+        let permalink = self.permalink(base_path.as_ref())?;
+        Ok(self.render(content, permalink))
+    }
+
+    /// Returns the content of the boilerplate file as it was at `commit` (as bytes), rendered as
+    /// [`Boilerplate::dump`] renders it, with the permalink naming `commit`.
+    ///
+    /// The content is read from the history of the clone rather than from its working tree, so
+    /// the clone stays where it is.
+    pub fn dump_at<P: AsRef<Path>>(&self, base_path: P, commit: &[u8]) -> Result<String> {
+        let content = crate::source::read_at(self, commit, base_path)?;
+        let permalink = crate::permalink::template_of(self.repo)
+            .map(|template| crate::permalink::render(template, self.repo, commit, &self.path));
+        Ok(self.render(content, permalink))
+    }
+
+    fn render(&self, mut content: String, permalink: Option<String>) -> String {
+        if !content.ends_with('\n') {
+            content.push('\n');
+        }
+        let source = match permalink {
+            Some(url) => url,
+            None => self.path.to_string_lossy().replace('\\', "/"),
+        };
+        format!(
+            r#"### Generated by Gixor (https://github.com/tamada/gixor) ({name})
+### {source}
+{}{}
+{content}{} ({name})
 "#,
-            self.repository_name(),
-            self.name,
-            self.content_url(base_path.as_ref())?,
-            content
-        ))
+            crate::document::CHECKSUM,
+            crate::document::checksum(&content),
+            crate::document::END,
+            name = self.name(),
+        )
     }
 }
 
@@ -176,6 +274,95 @@ pub struct Repository {
     pub repo_name: String,
     /// The path of the repository from the base path.
     pub path: PathBuf,
+    /// The name of the remote the repository is fetched from. `origin` when it is not given.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub remote: Option<String>,
+    /// The branch to follow. When it is not given, the clone follows the branch the remote
+    /// `HEAD` points at, which [`crate::RepositoryManager::add_repository`] records here.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub branch: Option<String>,
+    /// The revision to pin the repository to instead of following the branch: a tag, a
+    /// commit id, or a semver range over the tags such as `^2.1`. See [`Repository::resolve`].
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub rev: Option<String>,
+    /// How the permalinks of the boilerplates are laid out on the host, as a template such as
+    /// `{base}/{owner}/{repo}/raw/commit/{commit}/{path}` or the name of a preset such as
+    /// `gitea`. When it is not given, the preset of the host is used where gixor knows the host.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub permalink_template: Option<String>,
+    /// The repositories with the higher priority are searched first for a boilerplate, and
+    /// those of the same priority in the order of the configuration. `0` when it is not given.
+    #[serde(default, skip_serializing_if = "is_zero")]
+    pub priority: i32,
+    /// A disabled repository is neither prepared nor searched, while its clone is kept for it
+    /// to be enabled again. `true` when it is not given.
+    #[serde(default = "enabled_by_default", skip_serializing_if = "is_enabled")]
+    pub enabled: bool,
+}
+
+/// Compares two names of boilerplates, which are case-insensitive.
+fn same(a: &str, b: &str) -> bool {
+    a.to_lowercase() == b.to_lowercase()
+}
+
+fn is_zero(priority: &i32) -> bool {
+    *priority == 0
+}
+
+fn enabled_by_default() -> bool {
+    true
+}
+
+fn is_enabled(enabled: &bool) -> bool {
+    *enabled
+}
+
+/// The commit a pinned repository is checked out at, as [`Repository::resolve`] finds it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Revision {
+    /// The tag the revision was resolved through, or `None` when it was given as a commit.
+    pub tag: Option<String>,
+    /// The commit hash (as bytes).
+    pub commit: Vec<u8>,
+}
+
+impl std::fmt::Display for Revision {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let commit = hex::encode(&self.commit);
+        match &self.tag {
+            Some(tag) => write!(f, "{tag} ({commit})"),
+            None => write!(f, "{commit}"),
+        }
+    }
+}
+
+/// A commit of a repository, as `git log --oneline` shows it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Commit {
+    /// The commit hash (as bytes).
+    pub id: Vec<u8>,
+    /// The first line of the commit message.
+    pub summary: String,
+}
+
+impl Serialize for Commit {
+    fn serialize<S>(&self, serializer: S) -> core::result::Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        use serde::ser::SerializeStruct;
+        let mut commit = serializer.serialize_struct("Commit", 2)?;
+        commit.serialize_field("id", &hex::encode(&self.id))?;
+        commit.serialize_field("summary", &self.summary)?;
+        commit.end()
+    }
+}
+
+impl std::fmt::Display for Commit {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let id = hex::encode(&self.id);
+        write!(f, "{} {}", &id[..id.len().min(7)], self.summary)
+    }
 }
 
 impl Default for Repository {
@@ -188,6 +375,12 @@ impl Default for Repository {
             repo_name: "gitignore".to_string(),
             owner: "github".to_string(),
             path: PathBuf::from("default"),
+            remote: None,
+            branch: None,
+            rev: None,
+            permalink_template: None,
+            priority: 0,
+            enabled: true,
         }
     }
 }
@@ -195,19 +388,26 @@ impl Default for Repository {
 impl Repository {
     /// Creates an instance of Repository with the given url.
     /// The name of the repository is owner name which is extracted from the url.
+    /// See [`RepoUrl::owner`].
     pub fn new<S: AsRef<str>>(url: S) -> Self {
         let url = url.as_ref();
-        let (owner, repo_name) = url_to_owner_and_repo_name(url);
-        let path = PathBuf::from(&owner);
+        let (name, owner, repo_name) = identity_of(url);
+        let path = PathBuf::from(&name);
         log::debug!(
             "Repository::new(url: {url}) -> owner: {owner}, repo_name: {repo_name}, path: {path:?}"
         );
         Self {
-            name: owner.clone(),
+            name,
             url: url.to_string(),
-            owner: owner.clone(),
+            owner,
             repo_name,
             path,
+            remote: None,
+            branch: None,
+            rev: None,
+            permalink_template: None,
+            priority: 0,
+            enabled: true,
         }
     }
 
@@ -215,14 +415,22 @@ impl Repository {
     pub fn new_with<S: AsRef<str>>(name: S, url: S) -> Self {
         let url = url.as_ref();
         let name = name.as_ref();
-        let (owner, repo_name) = url_to_owner_and_repo_name(url);
-        log::debug!("Repository::new_with(name: {name}, url: {url}) -> owner: {owner}, repo_name: {repo_name}");
+        let (_, owner, repo_name) = identity_of(url);
+        log::debug!(
+            "Repository::new_with(name: {name}, url: {url}) -> owner: {owner}, repo_name: {repo_name}"
+        );
         Self {
             name: name.to_string(),
             url: url.to_string(),
             owner,
             repo_name,
             path: PathBuf::from(name),
+            remote: None,
+            branch: None,
+            rev: None,
+            permalink_template: None,
+            priority: 0,
+            enabled: true,
         }
     }
 
@@ -235,9 +443,60 @@ impl Repository {
         }
     }
 
+    /// Returns the name of the remote this repository is fetched from.
+    pub fn remote_name(&self) -> &str {
+        self.remote.as_deref().unwrap_or("origin")
+    }
+
+    /// Returns the branch this repository follows: the configured one, or else the branch
+    /// the clone under the base path has checked out. `None` means there is no clone to ask,
+    /// which is always the case for the embedded snapshot.
+    pub fn branch_at<P: AsRef<Path>>(&self, base_path: P) -> Option<String> {
+        self.branch
+            .clone()
+            .or_else(|| crate::source::branch(self, base_path))
+    }
+
+    /// Resolves the revision this repository is pinned to against the clone under the base
+    /// path, and returns `None` for a repository that follows its branch.
+    ///
+    /// A tag is looked up first, then a commit id, and last a semver range, which picks the
+    /// highest tagged version it matches; a leading `v` of a tag is not part of the version.
+    /// A range therefore resolves anew every time the repository is prepared, and moves on as
+    /// the provider tags new releases.
+    pub fn resolve<P: AsRef<Path>>(&self, base_path: P) -> Result<Option<Revision>> {
+        match &self.rev {
+            Some(rev) => crate::source::resolve(self, rev, base_path).map(Some),
+            None => Ok(None),
+        }
+    }
+
+    /// Returns when the clone under the base path was last cloned, pulled or fetched, or `None`
+    /// if that is not known, as for a clone made before gixor started to record it.
+    pub fn last_fetched<P: AsRef<Path>>(&self, base_path: P) -> Option<std::time::SystemTime> {
+        crate::source::last_fetched(self, base_path)
+    }
+
+    /// Returns `true` if the clone under the base path was fetched within `interval`, so that
+    /// preparing it again can wait.
+    pub fn is_fresh<P: AsRef<Path>>(&self, base_path: P, interval: std::time::Duration) -> bool {
+        self.last_fetched(base_path)
+            .and_then(|at| at.elapsed().ok())
+            .is_some_and(|elapsed| elapsed < interval)
+    }
+
     /// Finds the boilerplate by the name.
+    ///
+    /// When several files of the repository share the stem of `name`, the one whose path
+    /// `name` spells out wins, such as `Go.gitignore` at the root for `Go`; otherwise the
+    /// first one found is taken.
     pub fn find<P: AsRef<Path>>(&self, name: &Name, base_path: P) -> Option<Boilerplate<'_>> {
-        self.iter(base_path).find(|b| name.matches(b))
+        let mut found = self
+            .iter(base_path)
+            .filter(|b| name.matches(b))
+            .collect::<Vec<_>>();
+        let index = found.iter().position(|b| b.is_at(name)).unwrap_or(0);
+        (!found.is_empty()).then(|| found.swap_remove(index))
     }
 
     /// Iterates the boilerplates in the repository.
@@ -246,14 +505,28 @@ impl Repository {
     }
 
     /// Prepare the repository by cloning or pulling the remote repository.
-    /// If the repository already exists, perform the equivalent routine of `git pull origin main`.
+    /// If the repository already exists, perform the equivalent routine of
+    /// `git pull {remote} {branch}`, where the branch is the one [`Repository::branch_at`] gives.
     /// Otherwise, execute `git clone` and store the repository into the `self.path(base_path)`.
+    /// A repository with a `rev` is fetched instead of pulled, and its clone is checked out at
+    /// the commit [`Repository::resolve`] finds.
     ///
     /// ### See also
     ///
     /// - [`Repository::path`]
-    pub fn prepare<P: AsRef<Path>>(&self, base_path: P) -> Result<()> {
-        crate::source::prepare(self, base_path)
+    /// - [`Repository::prepare_with`]
+    pub fn prepare<P: AsRef<Path>>(&self, base_path: P) -> Result<Outcome> {
+        self.prepare_with(base_path, &Reporter::silent())
+    }
+
+    /// Prepares the repository as [`Repository::prepare`] does, telling `progress` how the
+    /// clone or the fetch goes.
+    pub fn prepare_with<P: AsRef<Path>>(
+        &self,
+        base_path: P,
+        progress: &Reporter,
+    ) -> Result<Outcome> {
+        crate::source::prepare(self, base_path, progress)
     }
 
     /// Makes the boilerplates of this repository available, without updating them when they
@@ -263,48 +536,169 @@ impl Repository {
     /// ### See also
     ///
     /// - [`Repository::path`]
+    /// - [`Repository::clone_repo_to_with`]
     pub fn clone_repo_to<P: AsRef<Path>>(&self, base_path: P) -> Result<()> {
+        self.clone_repo_to_with(base_path, &Reporter::silent())
+    }
+
+    /// Makes the boilerplates of this repository available as [`Repository::clone_repo_to`]
+    /// does, telling `progress` how the clone goes.
+    pub fn clone_repo_to_with<P: AsRef<Path>>(
+        &self,
+        base_path: P,
+        progress: &Reporter,
+    ) -> Result<()> {
         let path = self.path(&base_path);
         if path.join(".git").exists() {
             log::info!("{}: repository exists", path.display());
             Ok(())
         } else {
-            crate::source::prepare(self, base_path)
+            crate::source::prepare(self, base_path, progress).map(|_| ())
         }
     }
 }
 
-fn url_to_owner_and_repo_name(url: &str) -> (String, String) {
-    let items = url.split('/').collect::<Vec<_>>();
-    let len = items.len();
-    match (len.checked_sub(2).and_then(|idx| items.get(idx)), items.last()) {
-        (Some(&owner), Some(&name)) => (to_owner(owner), strip_dot_git(name)),
-        (None, Some(&name)) => ("unknown".into(), strip_dot_git(name)),
-        (Some(&owner), None) => (to_owner(owner), "gitignore".into()),
-        _ => ("unknown".into(), "gitignore".into()),
+/// What preparing a repository did to its clone. The commits are hashes (as bytes).
+#[derive(Debug)]
+pub enum Outcome {
+    /// The repository was cloned, and checked out at `commit`.
+    Cloned { commit: Vec<u8> },
+    /// The branch moved forward along the remote from `from` to `to`.
+    FastForwarded { from: Vec<u8>, to: Vec<u8> },
+    /// The branch had diverged from the remote, and a merge commit `to` joined them.
+    Merged { from: Vec<u8>, to: Vec<u8> },
+    /// Another revision was checked out, as when the pinned revision or the branch to follow
+    /// changed, and `to` does not descend from `from`.
+    CheckedOut { from: Vec<u8>, to: Vec<u8> },
+    /// The repository was fetched and had nothing new; it stays at `commit`.
+    Unchanged { commit: Vec<u8> },
+    /// The repository was left alone: it was fetched recently, the network is not to be used,
+    /// or there is no clone to update, as with the embedded snapshot.
+    Skipped,
+    /// Updating the repository failed, and the clone it already had is used as it is, which
+    /// [`PreparePolicy::KeepStale`] allows.
+    Stale(Error),
+    /// Preparing the repository failed.
+    Failed(Error),
+}
+
+/// What [`crate::Gixor::prepare_with`] makes of a repository that fails to be prepared.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum PreparePolicy {
+    /// Every failure is a failure: the repository is reported as [`Outcome::Failed`].
+    #[default]
+    Strict,
+    /// A repository that fails to update but was cloned before is used as the clone stands,
+    /// with a warning, and is reported as [`Outcome::Stale`]. A repository that was never
+    /// cloned has nothing to fall back on, and still fails.
+    ///
+    /// This suits whatever only reads the boilerplates: one unreachable remote should not keep
+    /// the others from being used, and a clone from yesterday is usually what one wants anyway.
+    KeepStale,
+}
+
+impl Outcome {
+    /// Returns `true` unless preparing the repository failed.
+    pub fn is_success(&self) -> bool {
+        !matches!(self, Outcome::Failed(_))
     }
 }
 
-fn to_owner<S: AsRef<str>>(s: S) -> String {
-    let s = s.as_ref();
-    if s.contains(':') {
-        let items = s.split(':').collect::<Vec<_>>();
-        if let Some(&owner) = items.last() {
-            owner.to_string()
-        } else {
-            "unknown".to_string()
+impl std::fmt::Display for Outcome {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        use Outcome::*;
+        let short = |commit: &[u8]| hex::encode(commit).chars().take(7).collect::<String>();
+        match self {
+            Cloned { commit } => write!(f, "cloned at {}", short(commit)),
+            FastForwarded { from, to } => {
+                write!(f, "fast-forwarded {}..{}", short(from), short(to))
+            }
+            Merged { from, to } => write!(f, "merged {}..{}", short(from), short(to)),
+            CheckedOut { from, to } => write!(f, "checked out {} -> {}", short(from), short(to)),
+            Unchanged { commit } => write!(f, "unchanged at {}", short(commit)),
+            Skipped => write!(f, "skipped"),
+            Stale(e) => write!(f, "kept as it was, since the update failed: {e}"),
+            Failed(e) => write!(f, "failed: {e}"),
         }
-    } else {
-        s.to_string()
     }
 }
 
-fn strip_dot_git<S: AsRef<str>>(s: S) -> String {
-    let s = s.as_ref().to_string();
-    if let Some(name) = s.strip_suffix(".git") {
-        name.to_string()
-    } else {
-        s.to_string()
+/// The outcome of preparing one repository, under the name of the repository.
+#[derive(Debug)]
+pub struct Prepared {
+    pub name: String,
+    pub outcome: Outcome,
+}
+
+/// What [`crate::Gixor::prepare`] did, repository by repository, in the order of the
+/// configuration. A failure of one repository does not stop the others, so the report holds
+/// the failures alongside everything that went well; [`Report::into_result`] turns it into an
+/// error when anything failed.
+#[derive(Debug, Default)]
+pub struct Report {
+    pub repositories: Vec<Prepared>,
+}
+
+impl Report {
+    /// Iterates the outcomes of the repositories.
+    pub fn iter(&self) -> impl Iterator<Item = &Prepared> {
+        self.repositories.iter()
+    }
+
+    /// Returns `true` if no repository failed.
+    pub fn is_success(&self) -> bool {
+        self.repositories.iter().all(|p| p.outcome.is_success())
+    }
+
+    /// Returns the report itself when no repository failed, and the failures otherwise, an
+    /// [`Error::Array`] when there are several of them.
+    pub fn into_result(self) -> Result<Report> {
+        if self.is_success() {
+            return Ok(self);
+        }
+        let errs = self
+            .repositories
+            .into_iter()
+            .filter_map(|p| match p.outcome {
+                Outcome::Failed(e) => Some(e),
+                _ => None,
+            })
+            .collect();
+        Error::to_err(Report::default(), errs)
+    }
+}
+
+impl std::fmt::Display for Report {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        for (i, prepared) in self.repositories.iter().enumerate() {
+            if i > 0 {
+                writeln!(f)?;
+            }
+            write!(f, "{}: {}", prepared.name, prepared.outcome)?;
+        }
+        Ok(())
+    }
+}
+
+/// Returns the default name, the owner and the repository name of the repository at `url`.
+///
+/// The owner is the whole namespace, such as `group/sub` of a nested group of GitLab, since
+/// that is what a permalink needs, while the default name is its last segment.
+fn identity_of(url: &str) -> (String, String, String) {
+    match RepoUrl::parse(url) {
+        Ok(parsed) => {
+            let name = parsed.owner().to_string();
+            let owner = if parsed.namespace.is_empty() {
+                name.clone()
+            } else {
+                parsed.namespace
+            };
+            (name, owner, parsed.name)
+        }
+        Err(e) => {
+            log::warn!("{e}");
+            ("unknown".into(), "unknown".into(), "gitignore".into())
+        }
     }
 }
 
@@ -340,40 +734,107 @@ mod tests {
         }
     }
 
+    /// Files in subdirectories are found by their paths, and by their stems as a shortcut.
+    #[cfg(feature = "local")]
     #[test]
-    fn test_url_to_name() {
-        let url = "https://github.com/github/gitignore.git";
-        let (owner, name) = url_to_owner_and_repo_name(url);
-        assert_eq!(owner, "github");
-        assert_eq!(name, "gitignore");
-    }
-
-    #[test]
-    fn test_url_to_name2() {
-        let url = "git@github.com:tamada/gitignore.git";
-        let (owner, name) = url_to_owner_and_repo_name(url);
-        assert_eq!(owner, "tamada");
-        assert_eq!(name, "gitignore");
+    fn test_find_by_path() {
+        let dir = tempfile::tempdir().unwrap();
+        let repo = Repository::new_with("default", "https://github.com/github/gitignore.git");
+        let root = repo.path(dir.path());
+        for path in [
+            "Go.gitignore",
+            "Global/macOS.gitignore",
+            "community/Golang/Go.gitignore",
+            "community/Golang/Hugo.gitignore",
+        ] {
+            let path = root.join(path);
+            std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+            std::fs::write(path, "").unwrap();
+        }
+        let found = |name: &str| {
+            repo.find(&Name::parse(name), dir.path())
+                .map(|b| b.name().to_string())
+        };
+        let cases = [
+            ("macOS", Some("default/Global/macOS")),
+            ("Global/macOS", Some("default/Global/macOS")),
+            ("global/macos", Some("default/Global/macOS")),
+            ("default/Global/macOS", Some("default/Global/macOS")),
+            ("default/macOS", Some("default/Global/macOS")),
+            (
+                "community/Golang/Hugo",
+                Some("default/community/Golang/Hugo"),
+            ),
+            (
+                "default/community/Golang/Hugo",
+                Some("default/community/Golang/Hugo"),
+            ),
+            // the stem shared by two files names the one at the root
+            ("Go", Some("default/Go")),
+            ("community/Golang/Go", Some("default/community/Golang/Go")),
+            ("other/macOS", None),
+            ("Golang/Hugo", None),
+        ];
+        for (name, expected) in cases {
+            assert_eq!(found(name).as_deref(), expected, "{name}");
+        }
     }
 
     #[test]
-    fn test_url_parsing_edge_cases() {
-        // Test with unusual URLs
-        assert_eq!(url_to_owner_and_repo_name("simple-name"), ("unknown".to_string(), "simple-name".to_string()));
-        assert_eq!(url_to_owner_and_repo_name("owner/repo"), ("owner".to_string(), "repo".to_string()));
-        assert_eq!(url_to_owner_and_repo_name("https://host.com/repo.git"), ("host.com".to_string(), "repo".to_string()));
-        
-        // Test to_owner
-        assert_eq!(to_owner("user:name"), "name".to_string());
-        assert_eq!(to_owner("name"), "name".to_string());
-        assert_eq!(to_owner(":"), "".to_string()); // items.last() of ":" split is ""
+    fn test_new_takes_the_identity_from_the_url() {
+        let cases = [
+            (
+                "https://github.com/github/gitignore.git",
+                "github",
+                "github",
+                "gitignore",
+            ),
+            (
+                "git@github.com:tamada/gitignore.git",
+                "tamada",
+                "tamada",
+                "gitignore",
+            ),
+            (
+                "https://gitlab.com/group/sub/gitignore.git",
+                "sub",
+                "group/sub",
+                "gitignore",
+            ),
+            (
+                "ssh://git@git.example.com:2222/team/gitignore.git",
+                "team",
+                "team",
+                "gitignore",
+            ),
+            ("https://host.com/repo.git", "host.com", "host.com", "repo"),
+            ("simple-name", "unknown", "unknown", "simple-name"),
+        ];
+        for (url, name, owner, repo_name) in cases {
+            let repo = Repository::new(url);
+            assert_eq!(
+                (
+                    repo.name.as_str(),
+                    repo.owner.as_str(),
+                    repo.repo_name.as_str()
+                ),
+                (name, owner, repo_name),
+                "{url}"
+            );
+            assert_eq!(repo.path, PathBuf::from(name));
+        }
+        let repo = Repository::new_with("corp", "https://gitlab.com/group/sub/gitignore.git");
+        assert_eq!(
+            (repo.name.as_str(), repo.owner.as_str()),
+            ("corp", "group/sub")
+        );
     }
 
     #[test]
     fn test_boilerplate_content_url() {
         let repo = Repository::default();
         let b = Boilerplate::new("Rust".into(), "Rust.gitignore", &repo);
-        
+
         // This might fail if git command is not available or not in a git repo,
         // but it's worth testing the logic if possible.
         // We use a dummy base path.
@@ -384,4 +845,24 @@ mod tests {
             assert!(url.contains("raw.github.com/github/gitignore"));
         }
     }
+
+    /// A host with no permalink costs the header its URL, not the dump; reading the content
+    /// does not even need a clone, since no commit is looked up.
+    #[cfg(feature = "local")]
+    #[test]
+    fn test_dump_without_permalink() {
+        let dir = tempfile::tempdir().unwrap();
+        let repo = Repository::new_with("corp", "https://git.example.com/team/gitignore.git");
+        let path = repo.path(dir.path()).join("Global");
+        std::fs::create_dir_all(&path).unwrap();
+        std::fs::write(path.join("macOS.gitignore"), ".DS_Store\n").unwrap();
+
+        let b = Boilerplate::new("macOS".into(), "Global/macOS.gitignore", &repo);
+        assert!(b.permalink(dir.path()).unwrap().is_none());
+        assert!(b.content_url(dir.path()).is_err());
+        assert_eq!(
+            b.dump(dir.path()).unwrap(),
+            "### Generated by Gixor (https://github.com/tamada/gixor) (corp/Global/macOS)\n### Global/macOS.gitignore\n### Checksum: sha256:cf237c7aff44efbe6e502e645c3e06da03a69d7bdeb43392108ef3348143417e\n.DS_Store\n### End of Gixor (corp/Global/macOS)\n"
+        );
+    }
 }
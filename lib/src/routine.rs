@@ -1,9 +1,14 @@
 use std::{
-    io::{BufRead, BufReader, Write},
+    io::Write,
     path::{Path, PathBuf},
 };
 
-use crate::{Error, Gixor, RepositoryManager};
+use crate::document::{Block, EditPolicy, GitignoreDocument, RevisionPolicy};
+use crate::lock::{Lock, Locked};
+use crate::repos::Boilerplate;
+use crate::{
+    Dropped, DumpReport, Entry, Error, Freshness, Gixor, Lag, Name, RepositoryManager, Update,
+};
 
 use super::Result;
 
@@ -12,7 +17,7 @@ pub(super) fn find_target_repositories<S: AsRef<str>>(
     repository_names: Vec<S>,
 ) -> Result<Vec<&crate::repos::Repository>> {
     if repository_names.is_empty() {
-        Ok(gixor.repositories().collect::<Vec<_>>())
+        Ok(gixor.config.active().collect::<Vec<_>>())
     } else {
         let r = repository_names
             .iter()
@@ -51,44 +56,292 @@ pub(super) fn find_boilerplates(
     }
 }
 
+/// Finds the boilerplates of `names` to dump into `current`, with the names dropped on the way.
+///
+/// The names of the blocks `current` has come from the gitignore rather than from the user, so
+/// a boilerplate renamed or removed upstream is not the user's mistake: such a name is dropped
+/// rather than failing the dump. One that became ambiguous, because another repository gained
+/// a boilerplate of the same name, keeps the first candidate in the search order, which is the
+/// one it was dumped from unless the repositories were reordered since. A boilerplate named
+/// twice, such as `go` given again beside the `default/Go` read back, is dumped once, in its
+/// first place, and at the revision of the last name giving one, such as `go@v1.0`.
+///
+/// A boilerplate `lock` has is read from the repository and the path the lock records, at its
+/// locked commit, whatever became of it upstream since, and so is, under
+/// [`RevisionPolicy::Recorded`], a block recording its repository, path, and commit. A name of
+/// `current` held either way that does not find its boilerplate fails the call rather than
+/// dropping the block, which only [`crate::Gixor::upgrade`] does.
+///
+/// A block edited by hand is dropped only under [`EditPolicy::Overwrite`], since its edits
+/// would go with it: `policy` otherwise fails the call, with [`Error::Modified`], or with
+/// [`Error::Conflict`] under [`EditPolicy::Merge`], as there is no upstream to merge with.
+pub(super) fn resolve_boilerplates<'a>(
+    gixor: &'a Gixor,
+    names: Vec<Name>,
+    current: &GitignoreDocument,
+    lock: &Lock,
+    policy: EditPolicy,
+) -> Result<(Vec<Boilerplate<'a>>, Vec<Dropped>)> {
+    let recorded = |name: &Name| current.blocks().any(|block| same(&block.name(), name));
+    let mut boilerplates: Vec<Boilerplate> = vec![];
+    let mut dropped = vec![];
+    let mut errs = vec![];
+    let keep = gixor.revision_policy == RevisionPolicy::Recorded;
+    for name in names {
+        let locked = name.revision.is_none()
+            && lock
+                .boilerplates
+                .iter()
+                .any(|locked| same(&locked.name, &name));
+        let found = match held(gixor, &name, current, lock) {
+            Some(b) => b.map(|b| vec![b]),
+            None => gixor.find(name.clone()),
+        };
+        let found = match found {
+            Err(Error::Ambiguous(_, candidates)) if recorded(&name) => {
+                let kept = Name::parse(&candidates[0]);
+                log::warn!("{name}: ambiguous, keeping {kept}; qualify the name to choose");
+                gixor.find(kept)
+            }
+            Err(e) if recorded(&name) && !keep && !locked => {
+                let edited = current.blocks().find(|block| {
+                    same(&block.name(), &name) && (block.is_modified() || block.merged().is_some())
+                });
+                match edited {
+                    Some(block) if policy == EditPolicy::Merge => {
+                        errs.push(Error::Conflict(block.name().to_string()));
+                        continue;
+                    }
+                    Some(block) if policy == EditPolicy::Refuse => {
+                        errs.push(Error::Modified(vec![block.name().to_string()]));
+                        continue;
+                    }
+                    _ => {}
+                }
+                log::info!("{name}: dropped from the gitignore ({e})");
+                dropped.push(Dropped {
+                    name,
+                    reason: e.to_string(),
+                });
+                continue;
+            }
+            r => r,
+        };
+        match found {
+            Ok(found) => {
+                for b in found {
+                    match boilerplates
+                        .iter()
+                        .position(|other| same(&other.name(), &b.name()))
+                    {
+                        Some(i) if b.revision().is_some() => boilerplates[i] = b,
+                        Some(_) => {}
+                        None => boilerplates.push(b),
+                    }
+                }
+            }
+            Err(e) => errs.push(e),
+        }
+    }
+    Error::to_err(boilerplates, errs).map(|boilerplates| (boilerplates, dropped))
+}
+
+/// Returns the boilerplate `name` holds at a commit of its own, without asking the working
+/// tree of the clone, if it holds one: the one `lock` has, or under
+/// [`RevisionPolicy::Recorded`], the one at the path and the commit of the block of `current`.
+/// A name giving a revision holds nothing, since it moves the boilerplate.
+fn held<'a>(
+    gixor: &'a Gixor,
+    name: &Name,
+    current: &GitignoreDocument,
+    lock: &Lock,
+) -> Option<Result<Boilerplate<'a>>> {
+    if name.revision.is_some() {
+        return None;
+    }
+    if let Some(locked) = lock
+        .boilerplates
+        .iter()
+        .find(|locked| same(&locked.name, name))
+    {
+        let repo = gixor
+            .repositories()
+            .find(|repo| crate::lock::same_url(&repo.url, &locked.url))
+            .ok_or_else(|| Error::RepositoryNotFound(locked.url.clone()));
+        return Some(repo.map(|repo| at_commit(repo, &locked.path, &locked.commit)));
+    }
+    if gixor.revision_policy != RevisionPolicy::Recorded {
+        return None;
+    }
+    let block = current.blocks().find(|block| same(&block.name(), name))?;
+    let (Some(repository), Some(path), Some(commit)) =
+        (&block.repository, &block.path, &block.commit)
+    else {
+        return None;
+    };
+    Some(Ok(at_commit(gixor.repository(repository)?, path, commit)))
+}
+
+/// The boilerplate at `path` of `repo`, taken at `commit`, whether or not the clone still has it.
+fn at_commit<'a>(repo: &'a crate::repos::Repository, path: &str, commit: &str) -> Boilerplate<'a> {
+    let stem = Path::new(path)
+        .file_stem()
+        .unwrap_or_default()
+        .to_string_lossy()
+        .to_string();
+    Boilerplate::new(stem, path, repo).at(Some(commit.to_string()))
+}
+
+/// Tells what dumping `content` over `current` did to its blocks.
+pub(super) fn report(
+    path: PathBuf,
+    current: &GitignoreDocument,
+    content: &str,
+    dropped: Vec<Dropped>,
+) -> DumpReport {
+    let dumped = GitignoreDocument::parse(content);
+    let mut report = DumpReport {
+        path,
+        dropped,
+        ..Default::default()
+    };
+    for block in dumped.blocks() {
+        let name = block.name();
+        match current.blocks().find(|old| same(&old.name(), &name)) {
+            None => report.added.push(name),
+            Some(old) if old == block => report.unchanged.push(name),
+            Some(old) => report.updated.push(Update {
+                name,
+                from: old.commit.clone(),
+                to: block.commit.clone(),
+            }),
+        }
+    }
+    for old in current.blocks() {
+        let name = old.name();
+        let gone = !dumped.blocks().any(|block| same(&block.name(), &name));
+        if gone && !report.dropped.iter().any(|d| same(&d.name, &name)) {
+            report.removed.push(name);
+        }
+    }
+    report
+}
+
+/// Returns `true` if the two names are of the same boilerplate, as far as they tell: a name
+/// with no repository, such as a block written before the repository was recorded, is of any.
+fn same(a: &Name, b: &Name) -> bool {
+    let repository = match (&a.repository_name, &b.repository_name) {
+        (Some(a), Some(b)) => a.eq_ignore_ascii_case(b),
+        _ => true,
+    };
+    repository && a.boilerplate_name.eq_ignore_ascii_case(&b.boilerplate_name)
+}
+
 /// Finds the entries of `.gitignore` file in the given path.
 /// The given path should be a directory containing a `.gitignore` file or a `.gitignore` file directly.
 /// If the `.gitignore` file is not found, returns error.
-pub(super) fn entries<P: AsRef<Path>>(path: P) -> Result<Vec<String>> {
+pub(super) fn entries<P: AsRef<Path>>(path: P) -> Result<Vec<Entry>> {
     let gitignore_path = find_gitignore(path);
     if !gitignore_path.exists() {
         Err(super::Error::FileNotFound(gitignore_path))
     } else {
-        match std::fs::File::open(gitignore_path) {
-            Err(e) => Err(super::Error::IO(e)),
-            Ok(f) => {
-                let reader = BufReader::new(f);
-                let r = reader
-                    .lines()
-                    .map_while(|r| r.ok())
-                    .filter_map(map_to_boilerplate_name)
-                    .collect::<Vec<_>>();
-                Ok(r)
-            }
-        }
+        let bytes = std::fs::read(gitignore_path).map_err(super::Error::IO)?;
+        let document = GitignoreDocument::parse(&String::from_utf8_lossy(&bytes));
+        Ok(document
+            .blocks()
+            .map(|block| Entry {
+                name: block.name(),
+                commit: block.commit.clone(),
+            })
+            .collect())
     }
 }
 
-fn map_to_boilerplate_name(line: String) -> Option<String> {
-    if line.starts_with("### ") && line.ends_with(".gitignore") {
-        Some(strip_to_boilerplate_name(line))
-    } else {
-        None
+/// Reads the `.gitignore` file that `dest` denotes, as [`Gixor::check`] takes it, and returns
+/// its path with its content. A missing one is [`Error::FileNotFound`].
+pub(super) fn read_gitignore<P: AsRef<Path>>(dest: P) -> Result<(PathBuf, String)> {
+    let path = source_of(dest.as_ref());
+    match std::fs::read(&path) {
+        Ok(bytes) => Ok((path, String::from_utf8_lossy(&bytes).to_string())),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => Err(Error::FileNotFound(path)),
+        Err(e) => Err(Error::IO(e)),
+    }
+}
+
+/// Finds how the boilerplate of `block` stands against its repository. Whatever keeps that
+/// from being told, such as a repository no longer configured, is the reason it is unknown.
+pub(super) fn lag(gixor: &Gixor, block: &Block) -> Lag {
+    let freshness = freshness(gixor, block).unwrap_or_else(|e| Freshness::Unknown {
+        reason: e.to_string(),
+    });
+    Lag {
+        name: block.name(),
+        commit: block.commit.clone(),
+        freshness,
+    }
+}
+
+/// The boilerplate is looked for at the path the block records, in the repository it records,
+/// so that the name, which may find another file by the same stem, does not decide whether it
+/// is still there. A block recording no path has only its name to go by.
+fn freshness(gixor: &Gixor, block: &Block) -> Result<Freshness> {
+    let Some(commit) = &block.commit else {
+        return Ok(Freshness::Unknown {
+            reason: "no commit recorded".into(),
+        });
+    };
+    let since = hex::decode(commit)
+        .map_err(|e| Error::Fatal(format!("{commit}: not a commit hash: {e}")))?;
+    let base_path = gixor.base_path();
+    let behind = |boilerplate: &Boilerplate| {
+        let commits = crate::source::log(boilerplate, &since, base_path)?;
+        Ok(if commits.is_empty() {
+            Freshness::UpToDate
+        } else {
+            Freshness::Outdated { commits }
+        })
+    };
+    let Some(path) = block.path.as_deref().map(Path::new) else {
+        let found = gixor.find(block.name())?;
+        return match found.first() {
+            Some(boilerplate) => behind(boilerplate),
+            None => Err(Error::BoilerplateNotFound(block.name().to_string(), vec![])),
+        };
+    };
+    let repositories = match &block.repository {
+        Some(name) => vec![gixor
+            .repository(name)
+            .ok_or_else(|| Error::RepositoryNotFound(name.clone()))?],
+        None => gixor.config.active().collect(),
+    };
+    for repo in &repositories {
+        if let Some(boilerplate) = repo.iter(base_path).find(|b| b.path() == path) {
+            return behind(&boilerplate);
+        }
     }
+    // gone from every clone, so it is the history of the one it was dumped from that tells
+    let stem = path.file_stem().unwrap_or_default().to_string_lossy();
+    let (repo, gone) = repositories
+        .into_iter()
+        .map(|repo| (repo, Boilerplate::new(stem.to_string(), path, repo)))
+        .find(|(_, b)| crate::source::read_at(b, &since, base_path).is_ok())
+        .ok_or_else(|| Error::BoilerplateNotFound(block.name().to_string(), vec![]))?;
+    let renamed = crate::source::renamed(&gone, &since, base_path)?
+        .and_then(|to| repo.iter(base_path).find(|b| b.path() == to))
+        .map(|b| b.name());
+    Ok(match renamed {
+        Some(to) => Freshness::Renamed { to },
+        None => Freshness::Removed,
+    })
 }
 
-fn strip_to_boilerplate_name(line: String) -> String {
-    let line = line.strip_prefix("### ").unwrap_or(&line);
-    let items = line.rsplit("/").collect::<Vec<_>>();
-    if items.is_empty() {
-        "".to_string()
+/// Returns the gitignore a dump to `dest` carries over, which is `.gitignore` in the current
+/// directory for `"-"`.
+pub(super) fn source_of(dest: &Path) -> PathBuf {
+    if dest == Path::new("-") {
+        PathBuf::from(".gitignore")
     } else {
-        items[0].strip_suffix(".gitignore").unwrap().to_string()
+        find_gitignore(dest)
     }
 }
 
@@ -106,28 +359,304 @@ pub(super) fn find_gitignore<P: AsRef<Path>>(path: P) -> PathBuf {
 /// Builds the whole content to be written, without touching any file.
 ///
 /// Every boilerplate is rendered before anything is handed back to the caller, so a boilerplate
-/// that cannot be read makes the whole call fail with nothing written anywhere.
+/// that cannot be read makes the whole call fail with nothing written anywhere. So does a block
+/// edited by hand that `policy` does not let go.
+///
+/// The boilerplates are rendered at the revision the revision policy of `gixor` asks for.
 pub(super) fn build_content(
+    gixor: &Gixor,
     boilerplates: Vec<super::repos::Boilerplate>,
-    prologue: Vec<String>,
-    base_path: &Path,
+    current: GitignoreDocument,
+    policy: EditPolicy,
+) -> Result<String> {
+    let fresh = render(gixor, boilerplates, &current)?;
+    finish(gixor, fresh, current, policy)
+}
+
+/// Builds the content as [`build_content`] does, with the lock of the boilerplates it holds,
+/// in the order of the content.
+pub(super) fn build_locked(
+    gixor: &Gixor,
+    boilerplates: Vec<super::repos::Boilerplate>,
+    current: GitignoreDocument,
+    policy: EditPolicy,
+) -> Result<(String, Lock)> {
+    let fresh = render(gixor, boilerplates, &current)?;
+    let mut locked = Error::vec_result_to_result_vec(
+        fresh
+            .iter()
+            .map(|(b, block)| locked(gixor, b, block))
+            .collect(),
+    )?;
+    let content = finish(gixor, fresh, current, policy)?;
+    let document = GitignoreDocument::parse(&content);
+    for locked in &mut locked {
+        let block = document
+            .blocks()
+            .find(|block| same(&block.name(), &locked.name));
+        locked.merged = block.and_then(Block::merged).map(str::to_string);
+    }
+    locked.sort_by_key(|locked| {
+        document
+            .blocks()
+            .position(|block| same(&block.name(), &locked.name))
+    });
+    let lock = Lock {
+        boilerplates: locked,
+        ..Default::default()
+    };
+    Ok((content, lock))
+}
+
+/// The entry of the lock for `boilerplate`, rendered as `block`. The checksum is that of the
+/// content upstream, whatever becomes of the block once the edits by hand are resolved, and
+/// [`build_locked`] records the merge of them apart.
+fn locked(gixor: &Gixor, boilerplate: &Boilerplate, block: &Block) -> Result<Locked> {
+    let commit = match &block.commit {
+        Some(commit) => commit.clone(),
+        // no permalink records the commit in the header
+        None => hex::encode(boilerplate.commit(gixor.base_path())?),
+    };
+    Ok(Locked {
+        name: boilerplate.name(),
+        url: boilerplate.repository().url.clone(),
+        path: crate::lock::portable(boilerplate.path()),
+        commit,
+        checksum: block
+            .checksum
+            .clone()
+            .unwrap_or_else(|| crate::document::checksum(&block.body)),
+        merged: None,
+    })
+}
+
+/// Builds the content the lock records into `current`, as [`crate::Gixor::sync`] does. Every
+/// boilerplate is taken at its locked commit, from the repository of its locked URL, and a
+/// repository whose clone lacks the commit is fetched once.
+///
+/// A boilerplate whose content at the commit is not what the lock records, such as one whose
+/// history was rewritten upstream, fails the call with [`Error::LockMismatch`], and so does a
+/// block the lock records edits merged into, when `current` no longer has them to carry over.
+/// [`EditPolicy::Overwrite`] drops the merged edits instead.
+pub(super) fn build_synced(
+    gixor: &Gixor,
+    lock: &Lock,
+    current: GitignoreDocument,
+    policy: EditPolicy,
 ) -> Result<String> {
+    let base_path = gixor.base_path();
+    let mut fetched: Vec<&str> = vec![];
+    let mut boilerplates = vec![];
+    for locked in &lock.boilerplates {
+        let repo = gixor
+            .repositories()
+            .find(|repo| crate::lock::same_url(&repo.url, &locked.url))
+            .ok_or_else(|| Error::RepositoryNotFound(locked.url.clone()))?;
+        if crate::source::resolve(repo, &locked.commit, base_path).is_err()
+            && !fetched.contains(&repo.name.as_str())
+        {
+            log::info!("{}: fetching for {}", repo.name, locked.commit);
+            let progress = crate::progress::Reporter::new(&repo.name, gixor.observer.clone());
+            // a failure to fetch leaves the commit missing, which the read below tells
+            if let Err(e) = repo.prepare_with(base_path, &progress) {
+                log::warn!("{}: {e}", repo.name);
+            }
+            fetched.push(&repo.name);
+        }
+        boilerplates.push(at_commit(repo, &locked.path, &locked.commit));
+    }
+    let fresh = render(gixor, boilerplates, &current)?;
+    let mismatched = fresh
+        .iter()
+        .filter(|(b, block)| {
+            let path = crate::lock::portable(b.path());
+            !lock.boilerplates.iter().any(|locked| {
+                locked.path == path
+                    && crate::lock::same_url(&locked.url, &b.repository().url)
+                    && block.checksum.as_ref() == Some(&locked.checksum)
+            })
+        })
+        .map(|(b, _)| b.name().to_string())
+        .collect::<Vec<_>>();
+    if !mismatched.is_empty() {
+        return Err(Error::LockMismatch(mismatched));
+    }
+    let content = finish(gixor, fresh, current, policy)?;
+    if policy == EditPolicy::Overwrite {
+        return Ok(content);
+    }
+    // the merged edits live only in the gitignore, so a block that lost them cannot be rebuilt
+    let document = GitignoreDocument::parse(&content);
+    let unmerged = lock
+        .boilerplates
+        .iter()
+        .filter(|locked| locked.merged.is_some())
+        .filter(|locked| {
+            let block = document
+                .blocks()
+                .find(|block| same(&block.name(), &locked.name));
+            block.and_then(Block::merged) != locked.merged.as_deref()
+        })
+        .map(|locked| locked.name.to_string())
+        .collect::<Vec<_>>();
+    if !unmerged.is_empty() {
+        return Err(Error::LockMismatch(unmerged));
+    }
+    Ok(content)
+}
+
+/// Renders each of the boilerplates into a block, all of them or none.
+fn render<'a>(
+    gixor: &Gixor,
+    boilerplates: Vec<Boilerplate<'a>>,
+    current: &GitignoreDocument,
+) -> Result<Vec<(Boilerplate<'a>, Block)>> {
     log::info!(
         "dumping boilerplates {:?}",
         boilerplates.iter().map(|b| b.name()).collect::<Vec<_>>()
     );
+    let base_path = gixor.base_path();
     let contents = Error::vec_result_to_result_vec(
         boilerplates
-            .into_iter()
-            .map(|b| b.dump(base_path))
+            .iter()
+            .map(
+                |b| match recorded_commit(b, current, gixor.revision_policy) {
+                    Some(commit) => b.dump_at(base_path, &commit),
+                    None => b.dump(base_path),
+                },
+            )
             .collect::<Vec<_>>(),
     )?;
-    let mut result = String::new();
-    for block in prologue.iter().chain(contents.iter()) {
-        result.push_str(block);
-        result.push('\n');
+    Ok(boilerplates
+        .into_iter()
+        .zip(contents)
+        .filter_map(|(b, content)| Block::rendered(&content).map(|block| (b, block)))
+        .collect())
+}
+
+/// Puts the rendered blocks in their places in `current`, resolving the edits by hand to them.
+fn finish(
+    gixor: &Gixor,
+    fresh: Vec<(Boilerplate, Block)>,
+    current: GitignoreDocument,
+    policy: EditPolicy,
+) -> Result<String> {
+    let fresh = resolve_edits(fresh, &current, policy, gixor.base_path())?;
+    let document = current.rewrite(fresh, |b, block| b.matches(&block.name()));
+    Ok(document.to_string())
+}
+
+/// The commit the block of `boilerplate` in `current` records, when `policy` asks for it. A
+/// boilerplate named with a revision is taken at that one instead, and one that the block
+/// records at another path, since the file moved upstream, is the latest.
+fn recorded_commit(
+    boilerplate: &super::repos::Boilerplate,
+    current: &GitignoreDocument,
+    policy: RevisionPolicy,
+) -> Option<Vec<u8>> {
+    if policy != RevisionPolicy::Recorded || boilerplate.revision().is_some() {
+        return None;
     }
-    Ok(result)
+    let block = current
+        .blocks()
+        .find(|block| boilerplate.matches(&block.name()))?;
+    let path = boilerplate.path().to_string_lossy().replace('\\', "/");
+    if block
+        .path
+        .as_ref()
+        .is_some_and(|recorded| *recorded != path)
+    {
+        log::info!(
+            "{}: moved to {path} since, dumping the latest",
+            block.name()
+        );
+        return None;
+    }
+    let commit = block.commit.as_deref().and_then(|c| hex::decode(c).ok());
+    if commit.is_none() {
+        log::info!("{}: no commit recorded, dumping the latest", block.name());
+    }
+    commit
+}
+
+/// Applies `policy` to the blocks of `current` edited by hand that `fresh` is about to rewrite,
+/// and carries over the edits merged into a block before, unless `policy` overwrites them.
+///
+/// A block is rewritten by the first of `fresh` it matches, as [`GitignoreDocument::rewrite`]
+/// finds, and a merge goes into the body of that one.
+fn resolve_edits<'a>(
+    mut fresh: Vec<(super::repos::Boilerplate<'a>, Block)>,
+    current: &GitignoreDocument,
+    policy: EditPolicy,
+    base_path: &Path,
+) -> Result<Vec<(super::repos::Boilerplate<'a>, Block)>> {
+    let (edited, carried): (Vec<_>, Vec<_>) = current
+        .blocks()
+        .filter(|block| block.is_modified() || block.merged().is_some())
+        .filter_map(|block| {
+            fresh
+                .iter()
+                .position(|(b, _)| b.matches(&block.name()))
+                .map(|i| (i, block))
+        })
+        .partition(|(_, block)| block.is_modified());
+    // the edits merged by an earlier dump are carried over unless they are to be dropped
+    let to_merge = match policy {
+        EditPolicy::Refuse if !edited.is_empty() => {
+            return Err(Error::Modified(
+                edited
+                    .iter()
+                    .map(|(_, block)| block.name().to_string())
+                    .collect(),
+            ));
+        }
+        EditPolicy::Refuse => carried,
+        EditPolicy::Overwrite => {
+            for (_, block) in edited.iter().chain(&carried) {
+                log::info!("{}: overwriting the edits by hand", block.name());
+            }
+            vec![]
+        }
+        EditPolicy::Merge => edited.into_iter().chain(carried).collect(),
+    };
+    let merged = to_merge
+        .into_iter()
+        .map(|(i, block)| merge(&fresh[i], block, base_path).map(|body| (i, body)))
+        .collect::<Vec<_>>();
+    for (i, body) in Error::vec_result_to_result_vec(merged)? {
+        fresh[i].1.merge_into(body);
+    }
+    Ok(fresh)
+}
+
+/// Merges the edits of `edited` into the new content of the boilerplate, from the content at
+/// the commit the block records.
+fn merge(
+    (boilerplate, fresh): &(super::repos::Boilerplate, Block),
+    edited: &Block,
+    base_path: &Path,
+) -> Result<String> {
+    // nothing changed upstream, so the merge is the edits themselves
+    if fresh.checksum.is_some() && fresh.checksum == edited.checksum {
+        return Ok(edited.body.clone());
+    }
+    let name = edited.name().to_string();
+    let commit = edited
+        .commit
+        .as_deref()
+        .and_then(|commit| hex::decode(commit).ok())
+        .ok_or_else(|| {
+            Error::Fatal(format!(
+                "{name}: cannot merge the edits, since the block records no commit they were made on"
+            ))
+        })?;
+    let mut body =
+        crate::source::merge(boilerplate, &commit, &edited.body, &fresh.body, base_path)?
+            .ok_or(Error::Conflict(name))?;
+    if !body.ends_with('\n') {
+        body.push('\n');
+    }
+    Ok(body)
 }
 
 /// Replaces `dest` with `content` so that a failure never leaves a half-written file behind.
@@ -169,42 +698,22 @@ fn write_temp_then_rename(temp: &Path, dest: &Path, content: &str) -> std::io::R
     std::fs::rename(temp, dest)
 }
 
-/// Reads the part of `path` that precedes the first boilerplate, known as the prologue.
-/// A missing or unreadable file simply has no prologue.
-pub(super) fn load_prologue(path: &Path) -> Vec<String> {
-    match std::fs::File::open(path) {
-        Ok(f) => {
-            log::info!("loading prologue from {}", path.display());
-            let reader = BufReader::new(f);
-            take_prologue(reader.lines().map_while(|r| r.ok()))
+/// Reads the gitignore at `path` as a [`GitignoreDocument`].
+/// A missing or unreadable file is simply an empty one.
+pub(super) fn load_document(path: &Path) -> GitignoreDocument {
+    match std::fs::read(path) {
+        Ok(bytes) => {
+            log::info!("loading the gitignore from {}", path.display());
+            GitignoreDocument::parse(&String::from_utf8_lossy(&bytes))
         }
-        Err(_) => vec![],
+        Err(_) => GitignoreDocument::default(),
     }
 }
 
-/// The prologue of a gitignore already held in memory, for callers that have the text rather
-/// than a path to it.
-pub(super) fn prologue_of(content: &str) -> Vec<String> {
-    take_prologue(content.lines().map(str::to_string))
-}
-
-/// The lines up to the first boilerplate. Everything from `### ` onwards was written by gixor
-/// and is about to be written again.
-fn take_prologue(lines: impl Iterator<Item = String>) -> Vec<String> {
-    lines.take_while(|line| !line.starts_with("### ")).collect()
-}
-
 #[cfg(test)]
 mod tests {
     use super::*;
 
-    #[test]
-    fn test_map_to_boilerplate_name() {
-        assert_eq!(map_to_boilerplate_name("### Rust.gitignore".into()), Some("Rust".into()));
-        assert_eq!(map_to_boilerplate_name("### path/to/Rust.gitignore".into()), Some("Rust".into()));
-        assert_eq!(map_to_boilerplate_name("Not a boilerplate".into()), None);
-    }
-
     #[test]
     fn test_find_gitignore() {
         let temp_dir = tempfile::tempdir().unwrap();
@@ -225,14 +734,17 @@ mod tests {
     }
 
     #[test]
-    fn test_load_prologue_stops_at_the_first_boilerplate() {
+    fn test_load_document_stops_the_prologue_at_the_first_boilerplate() {
         let temp_dir = tempfile::tempdir().unwrap();
         let path = temp_dir.path().join(".gitignore");
         std::fs::write(&path, "# mine\n*.local\n### Rust.gitignore\ntarget\n").unwrap();
-        assert_eq!(load_prologue(&path), vec!["# mine", "*.local"]);
+        assert_eq!(load_document(&path).prologue, "# mine\n*.local\n");
 
         // a missing file simply has no prologue
-        assert!(load_prologue(&temp_dir.path().join("absent")).is_empty());
+        assert_eq!(
+            load_document(&temp_dir.path().join("absent")),
+            GitignoreDocument::default()
+        );
     }
 
     #[test]
@@ -272,9 +784,11 @@ mod tests {
 
     #[test]
     fn test_build_content_joins_the_prologue_and_the_blocks() {
-        let prologue = vec!["# mine".to_string(), "*.local".to_string()];
+        let temp_dir = tempfile::tempdir().unwrap();
+        let gixor = crate::GixorFactory::new_at(temp_dir.path().join("config.json"));
+        let current = GitignoreDocument::parse("# mine\n*.local");
         // no boilerplate is needed to pin the prologue handling down
-        let r = build_content(vec![], prologue, Path::new(".")).unwrap();
+        let r = build_content(&gixor, vec![], current, EditPolicy::Refuse).unwrap();
         assert_eq!(r, "# mine\n*.local\n");
     }
 }
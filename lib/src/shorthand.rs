@@ -0,0 +1,177 @@
+//! The shorthands of the repository URLs, such as `tamada` or `gl:group/gitignore`.
+//!
+//! A shorthand is expanded to the URL it stands for before anything else sees it:
+//!
+//! - `owner` is the repository `gitignore` of `owner` on GitHub, such as `github` for
+//!   `https://github.com/github/gitignore.git`,
+//! - `owner/repo` is that repository on GitHub, and
+//! - `prefix:owner/repo`, or `prefix:owner` for `owner/gitignore`, is that repository on the
+//!   forge of the prefix, where the owner may be a nested group such as `group/sub`: `gh:` for
+//!   GitHub, `gl:` for GitLab, `bb:` for Bitbucket and `codeberg:` for Codeberg, plus those the
+//!   `shorthands` of the configuration declare.
+//!
+//! A prefix of the configuration maps to a URL template with the placeholder `{path}`, such
+//! as `"corp": "ssh://git@git.corp.example.com/{path}.git"`, or to the URL the path is
+//! appended to. It wins over a built-in prefix of the same name.
+//!
+//! Anything else is left as it is, and so is a path written as one, starting with `./`, `../`
+//! or `/`, which is how a local clone named like an owner is told from the owner.
+use std::collections::BTreeMap;
+
+use crate::{Error, Result};
+
+/// The prefixes every configuration knows.
+const BUILTINS: [(&str, &str); 4] = [
+    ("gh", "https://github.com/{path}.git"),
+    ("gl", "https://gitlab.com/{path}.git"),
+    ("bb", "https://bitbucket.org/{path}.git"),
+    ("codeberg", "https://codeberg.org/{path}.git"),
+];
+
+/// The repository an owner alone stands for.
+const DEFAULT_REPOSITORY: &str = "gitignore";
+
+/// Expands `spec` to the URL it stands for, with `custom` as the prefixes of the configuration.
+pub(crate) fn expand(spec: &str, custom: Option<&BTreeMap<String, String>>) -> Result<String> {
+    let spec = spec.trim();
+    if let Some((prefix, path)) = spec.split_once(':') {
+        let template = custom
+            .and_then(|custom| custom.get(prefix))
+            .map(String::as_str)
+            .or_else(|| builtin(prefix));
+        return match template {
+            Some(_) if !is_path(path, usize::MAX) => Err(Error::Fatal(format!(
+                "{spec}: expected {prefix}:OWNER or {prefix}:OWNER/REPO"
+            ))),
+            Some(template) => Ok(fill(template, &with_repository(path))),
+            None => Ok(spec.to_string()),
+        };
+    }
+    if is_path(spec, 2) && !is_local(spec) {
+        Ok(fill(builtin("gh").unwrap(), &with_repository(spec)))
+    } else {
+        Ok(spec.to_string())
+    }
+}
+
+/// Returns `true` if `spec` is written as a path of the file system. Whether the path exists is
+/// not asked, so that a shorthand means the same whatever directory gixor runs in.
+fn is_local(spec: &str) -> bool {
+    matches!(spec, "." | "..") || ["./", "../", "/"].iter().any(|p| spec.starts_with(p))
+}
+
+fn builtin(prefix: &str) -> Option<&'static str> {
+    BUILTINS
+        .iter()
+        .find(|(name, _)| *name == prefix)
+        .map(|(_, template)| *template)
+}
+
+/// Returns `true` if `s` is `owner`, `owner/repo` or deeper, up to `max` segments in all, and
+/// nothing more.
+fn is_path(s: &str, max: usize) -> bool {
+    let segments = s.split('/').collect::<Vec<_>>();
+    segments.len() <= max
+        && segments.iter().all(|segment| {
+            !segment.is_empty()
+                && segment
+                    .chars()
+                    .all(|c| c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '.'))
+        })
+}
+
+fn with_repository(path: &str) -> String {
+    let path = path.strip_suffix(".git").unwrap_or(path);
+    if path.contains('/') {
+        path.to_string()
+    } else {
+        format!("{path}/{DEFAULT_REPOSITORY}")
+    }
+}
+
+fn fill(template: &str, path: &str) -> String {
+    if template.contains("{path}") {
+        template.replace("{path}", path)
+    } else {
+        format!("{}/{path}", template.trim_end_matches('/'))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_expand() {
+        let custom = BTreeMap::from([
+            (
+                "corp".to_string(),
+                "ssh://git@git.corp.example.com/{path}.git".to_string(),
+            ),
+            ("gl".to_string(), "https://gitlab.example.com/".to_string()),
+        ]);
+        let cases = [
+            ("github", "https://github.com/github/gitignore.git"),
+            (
+                "tamada/gitignore",
+                "https://github.com/tamada/gitignore.git",
+            ),
+            (
+                "tamada/gitignore.git",
+                "https://github.com/tamada/gitignore.git",
+            ),
+            ("gh:tamada", "https://github.com/tamada/gitignore.git"),
+            (
+                "bb:owner/boilerplates",
+                "https://bitbucket.org/owner/boilerplates.git",
+            ),
+            (
+                "codeberg:owner/gitignore",
+                "https://codeberg.org/owner/gitignore.git",
+            ),
+            (
+                "corp:team/gitignore",
+                "ssh://git@git.corp.example.com/team/gitignore.git",
+            ),
+            // the configuration wins over the built-in prefix
+            (
+                "gl:group/gitignore",
+                "https://gitlab.example.com/group/gitignore",
+            ),
+            (
+                "gl:group/sub/gitignore",
+                "https://gitlab.example.com/group/sub/gitignore",
+            ),
+            // URLs and paths are not shorthands
+            (
+                "https://github.com/github/gitignore.git",
+                "https://github.com/github/gitignore.git",
+            ),
+            (
+                "git@github.com:tamada/gitignore.git",
+                "git@github.com:tamada/gitignore.git",
+            ),
+            ("../mirrors/gitignore", "../mirrors/gitignore"),
+            ("../gitignore", "../gitignore"),
+            ("./tamada", "./tamada"),
+            ("/srv/git/gitignore", "/srv/git/gitignore"),
+            // a name is an owner even where a directory of that name is at hand
+            (".", "."),
+            ("src", "https://github.com/src/gitignore.git"),
+        ];
+        for (spec, expected) in cases {
+            assert_eq!(expand(spec, Some(&custom)).unwrap(), expected, "{spec}");
+        }
+        assert_eq!(
+            expand("gl:group/gitignore", None).unwrap(),
+            "https://gitlab.com/group/gitignore.git"
+        );
+    }
+
+    #[test]
+    fn test_expand_errors() {
+        for spec in ["gh:", "gh:owner//gitignore", "codeberg:owner/"] {
+            assert!(expand(spec, None).is_err(), "{spec}");
+        }
+    }
+}
@@ -1,13 +1,15 @@
 //! Where the boilerplates come from.
 //!
-//! Two implementations answer the same four questions, and the feature flags pick one of them,
+//! Two implementations answer the same questions, and the feature flags pick one of them,
 //! the same way [`crate::gitbridge`] picks a Git implementation. `local` keeps clones on the
 //! file system and asks Git about them; `embedded` reads a snapshot compiled into the binary,
 //! which is what lets the library be built for a target that has neither.
-use std::path::Path;
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
 
-use crate::repos::{Boilerplate, Repository};
 use crate::Result;
+use crate::progress::Reporter;
+use crate::repos::{Boilerplate, Commit, Outcome, Repository, Revision};
 
 #[cfg(feature = "embedded")]
 #[path = "source/embedded.rs"]
@@ -27,14 +29,95 @@ pub(crate) fn read<P: AsRef<Path>>(boilerplate: &Boilerplate, base_path: P) -> R
     imp::read(boilerplate, base_path.as_ref())
 }
 
+/// Returns the content of the boilerplate file as it was at `commit` (as bytes).
+pub(crate) fn read_at<P: AsRef<Path>>(
+    boilerplate: &Boilerplate,
+    commit: &[u8],
+    base_path: P,
+) -> Result<String> {
+    imp::read_at(boilerplate, commit, base_path.as_ref())
+}
+
 /// Returns the commit (as bytes) the content of the boilerplate is to be attributed to.
 pub(crate) fn hash<P: AsRef<Path>>(boilerplate: &Boilerplate, base_path: P) -> Result<Vec<u8>> {
     imp::hash(boilerplate, base_path.as_ref())
 }
 
-/// Makes the boilerplates of the repository available.
-pub(crate) fn prepare<P: AsRef<Path>>(repo: &Repository, base_path: P) -> Result<()> {
-    imp::prepare(repo, base_path.as_ref())
+/// Returns the commits since `since` (as bytes) that changed the boilerplate, the newest first.
+pub(crate) fn log<P: AsRef<Path>>(
+    boilerplate: &Boilerplate,
+    since: &[u8],
+    base_path: P,
+) -> Result<Vec<Commit>> {
+    imp::log(boilerplate, since, base_path.as_ref())
+}
+
+/// Returns the path from the repository root that the boilerplate was renamed to since `since`
+/// (as bytes), for a boilerplate that is gone, or `None` when it was removed instead.
+pub(crate) fn renamed<P: AsRef<Path>>(
+    boilerplate: &Boilerplate,
+    since: &[u8],
+    base_path: P,
+) -> Result<Option<PathBuf>> {
+    imp::renamed(boilerplate, since, base_path.as_ref())
+}
+
+/// Merges `edited`, the boilerplate as the user edited it, with `upstream`, its content now,
+/// taking the content at `commit` (as bytes) as what both started from. Returns `None` when
+/// the two conflict.
+pub(crate) fn merge<P: AsRef<Path>>(
+    boilerplate: &Boilerplate,
+    commit: &[u8],
+    edited: &str,
+    upstream: &str,
+    base_path: P,
+) -> Result<Option<String>> {
+    imp::merge(boilerplate, commit, edited, upstream, base_path.as_ref())
+}
+
+/// Makes the boilerplates of the repository available, and tells what that took. How the
+/// clone or the fetch goes is reported to `progress`.
+pub(crate) fn prepare<P: AsRef<Path>>(
+    repo: &Repository,
+    base_path: P,
+    progress: &Reporter,
+) -> Result<Outcome> {
+    imp::prepare(repo, base_path.as_ref(), progress)
+}
+
+/// The branch the clone of the repository has checked out, if there is a clone to ask.
+pub(crate) fn branch<P: AsRef<Path>>(repo: &Repository, base_path: P) -> Option<String> {
+    imp::branch(repo, base_path.as_ref())
+}
+
+/// When the clone of the repository was last fetched, if it ever was.
+pub(crate) fn last_fetched<P: AsRef<Path>>(repo: &Repository, base_path: P) -> Option<SystemTime> {
+    imp::last_fetched(repo, base_path.as_ref())
+}
+
+/// The commit `rev` names in the repository, for a repository pinned to a revision.
+pub(crate) fn resolve<P: AsRef<Path>>(
+    repo: &Repository,
+    rev: &str,
+    base_path: P,
+) -> Result<Revision> {
+    imp::resolve(repo, rev, base_path.as_ref())
+}
+
+/// Moves what the repository `from` keeps on the file system to where `to` keeps it, for a
+/// repository that was renamed.
+pub(crate) fn relocate<P: AsRef<Path>>(
+    from: &Repository,
+    to: &Repository,
+    base_path: P,
+) -> Result<()> {
+    imp::relocate(from, to, base_path.as_ref())
+}
+
+/// Makes the clone of the repository fetch from the URL it has now, for a repository whose URL
+/// was changed.
+pub(crate) fn set_url<P: AsRef<Path>>(repo: &Repository, base_path: P) -> Result<()> {
+    imp::set_url(repo, base_path.as_ref())
 }
 
 /// The repositories the boilerplates were taken from, for the configuration to start from.
@@ -4,8 +4,10 @@
 //! target that offers neither. The snapshot cannot be updated at run time by design: `prepare`
 //! has nothing to do, and the permalinks point at the commit the snapshot was taken from.
 use std::path::{Path, PathBuf};
+use std::time::SystemTime;
 
-use crate::repos::{Boilerplate, Repository};
+use crate::progress::Reporter;
+use crate::repos::{Boilerplate, Commit, Outcome, Repository, Revision};
 use crate::{Error, Result};
 
 include!(concat!(env!("OUT_DIR"), "/embedded.rs"));
@@ -31,7 +33,7 @@ pub(super) fn read(boilerplate: &Boilerplate, _base_path: &Path) -> Result<Strin
         .iter()
         .find(|(name, path, _)| *name == boilerplate.repository_name() && *path == wanted)
         .map(|(_, _, content)| content.to_string())
-        .ok_or_else(|| Error::BoilerplateNotFound(boilerplate.name().to_string()))
+        .ok_or_else(|| Error::BoilerplateNotFound(boilerplate.name().to_string(), vec![]))
 }
 
 /// The commit the snapshot was taken from, which is what the permalinks have to name. Every
@@ -46,9 +48,113 @@ pub(super) fn hash(boilerplate: &Boilerplate, _base_path: &Path) -> Result<Vec<u
     hex::decode(commit).map_err(|e| Error::Git(format!("{commit}: not a commit hash: {e}")))
 }
 
+/// The snapshot holds a single revision, so only the commit it was taken at can be read.
+pub(super) fn read_at(
+    boilerplate: &Boilerplate,
+    commit: &[u8],
+    base_path: &Path,
+) -> Result<String> {
+    if hash(boilerplate, base_path)? == commit {
+        read(boilerplate, base_path)
+    } else {
+        Err(no_history(boilerplate, commit))
+    }
+}
+
+/// The snapshot holds a single revision, so a boilerplate taken at it has not changed since,
+/// and there is no telling what changed since any other.
+pub(super) fn log(
+    boilerplate: &Boilerplate,
+    since: &[u8],
+    base_path: &Path,
+) -> Result<Vec<Commit>> {
+    if hash(boilerplate, base_path)? == since {
+        Ok(vec![])
+    } else {
+        Err(no_history(boilerplate, since))
+    }
+}
+
+/// The snapshot holds a single revision, so a boilerplate taken at it that is not there was
+/// not renamed since.
+pub(super) fn renamed(
+    boilerplate: &Boilerplate,
+    since: &[u8],
+    base_path: &Path,
+) -> Result<Option<PathBuf>> {
+    if hash(boilerplate, base_path)? == since {
+        Ok(None)
+    } else {
+        Err(no_history(boilerplate, since))
+    }
+}
+
+fn no_history(boilerplate: &Boilerplate, commit: &[u8]) -> Error {
+    Error::Fatal(format!(
+        "{}: the embedded snapshot has no history to find {} in",
+        boilerplate.name(),
+        hex::encode(commit)
+    ))
+}
+
+/// The snapshot holds a single revision with no history around it, so there is no telling
+/// what the edits were made on.
+pub(super) fn merge(
+    boilerplate: &Boilerplate,
+    commit: &[u8],
+    _edited: &str,
+    _upstream: &str,
+    _base_path: &Path,
+) -> Result<Option<String>> {
+    Err(Error::Fatal(format!(
+        "{}: cannot merge the edits, since the embedded snapshot has no history to find {} in",
+        boilerplate.name(),
+        hex::encode(commit)
+    )))
+}
+
 /// The snapshot is whatever it was when it was built, so there is nothing to fetch.
-pub(super) fn prepare(_repo: &Repository, _base_path: &Path) -> Result<()> {
-    Ok(())
+pub(super) fn prepare(
+    _repo: &Repository,
+    _base_path: &Path,
+    _progress: &Reporter,
+) -> Result<Outcome> {
+    Ok(Outcome::Skipped)
+}
+
+/// The snapshot was taken at a commit rather than from a branch, so there is none to report.
+pub(super) fn branch(_repo: &Repository, _base_path: &Path) -> Option<String> {
+    None
+}
+
+/// The snapshot is never fetched.
+pub(super) fn last_fetched(_repo: &Repository, _base_path: &Path) -> Option<SystemTime> {
+    None
+}
+
+/// The snapshot holds a single revision with no history around it, so there is nothing to
+/// pin to.
+pub(super) fn resolve(repo: &Repository, rev: &str, _base_path: &Path) -> Result<Revision> {
+    Err(Error::Git(format!(
+        "{}: cannot pin the embedded snapshot to {rev}",
+        repo.name
+    )))
+}
+
+/// The snapshot knows its repositories by the names they were taken under.
+pub(super) fn relocate(from: &Repository, _to: &Repository, _base_path: &Path) -> Result<()> {
+    Err(Error::Fatal(format!(
+        "{}: cannot rename a repository of the embedded snapshot",
+        from.name
+    )))
+}
+
+/// The snapshot is never fetched, from this URL or any other.
+pub(super) fn set_url(repo: &Repository, _base_path: &Path) -> Result<()> {
+    Err(Error::Fatal(format!(
+        "{}: cannot change the URL of a repository of the embedded snapshot",
+        repo.name
+    )))
 }
 
 /// The repositories the snapshot was taken from, for a configuration to start from.
@@ -61,6 +167,12 @@ pub(super) fn repositories() -> Vec<Repository> {
             owner: owner.to_string(),
             repo_name: repo_name.to_string(),
             path: PathBuf::from(name),
+            remote: None,
+            branch: None,
+            rev: None,
+            permalink_template: None,
+            priority: 0,
+            enabled: true,
         })
         .collect()
 }
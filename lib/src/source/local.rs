@@ -1,8 +1,10 @@
 //! Boilerplates read from clones on the file system, kept up to date through Git.
-use std::path::Path;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
 
 use crate::gitbridge;
-use crate::repos::{Boilerplate, Repository};
+use crate::progress::{Event, Reporter};
+use crate::repos::{Boilerplate, Commit, Outcome, Repository, Revision};
 use crate::{Error, Result};
 
 pub(super) fn list<'a>(repo: &'a Repository, base_path: &Path) -> Vec<Boilerplate<'a>> {
@@ -32,15 +34,204 @@ pub(super) fn hash(boilerplate: &Boilerplate, base_path: &Path) -> Result<Vec<u8
     gitbridge::hash(boilerplate, base_path)
 }
 
+/// Reads the object database of the clone, leaving its working tree alone.
+pub(super) fn read_at(
+    boilerplate: &Boilerplate,
+    commit: &[u8],
+    base_path: &Path,
+) -> Result<String> {
+    let repo_path = boilerplate.repo_path(base_path);
+    match gitbridge::read_at(&repo_path, commit, boilerplate.path())? {
+        Some(content) => Ok(String::from_utf8_lossy(&content).to_string()),
+        None => Err(Error::Git(format!(
+            "{}: not found at {}",
+            boilerplate.path().display(),
+            hex::encode(commit)
+        ))),
+    }
+}
+
+pub(super) fn log(
+    boilerplate: &Boilerplate,
+    since: &[u8],
+    base_path: &Path,
+) -> Result<Vec<Commit>> {
+    gitbridge::log(&boilerplate.repo_path(base_path), since, boilerplate.path())
+}
+
+pub(super) fn renamed(
+    boilerplate: &Boilerplate,
+    since: &[u8],
+    base_path: &Path,
+) -> Result<Option<PathBuf>> {
+    gitbridge::renamed(&boilerplate.repo_path(base_path), since, boilerplate.path())
+}
+
+/// Reads what both sides started from out of the history of the clone, and merges them the way
+/// `git merge-file` does.
+pub(super) fn merge(
+    boilerplate: &Boilerplate,
+    commit: &[u8],
+    edited: &str,
+    upstream: &str,
+    base_path: &Path,
+) -> Result<Option<String>> {
+    let ancestor = read_at(boilerplate, commit, base_path)?;
+    gitbridge::merge(&ancestor, edited, upstream)
+}
+
 /// Clones the repository, or pulls it when it is already there.
-pub(super) fn prepare(repo: &Repository, base_path: &Path) -> Result<()> {
+///
+/// A repository with no branch configured follows whatever the clone has checked out, which
+/// `clone` took from the remote `HEAD`. Assuming `main` would break every repository whose
+/// default branch is called something else.
+///
+/// A repository pinned to a revision is fetched rather than pulled, since there is no branch
+/// to move along, and then checked out at whatever the revision resolves to now.
+///
+/// A repository that was not there before and fails to be prepared is not left half-done: the
+/// clone is removed again, such as when the revision it is pinned to turns out not to exist.
+pub(super) fn prepare(repo: &Repository, base_path: &Path, progress: &Reporter) -> Result<Outcome> {
     let path = repo.path(base_path);
-    if path.join(".git").exists() {
-        log::info!("Pulling {} to {}", repo.url, path.display());
-        gitbridge::pull(&path, "origin", "main")
+    let existed = path.exists();
+    let from = if path.join(".git").exists() {
+        Some(gitbridge::head(&path)?)
+    } else {
+        None
+    };
+    progress.report(Event::Started);
+    let upstream = update(repo, base_path, progress);
+    progress.report(Event::Finished);
+    if upstream.is_err() && !existed {
+        log::info!("{}: removing the unfinished clone", path.display());
+        if let Err(e) = gitbridge::remove_dir(&path) {
+            log::warn!("{}: failed to remove: {e}", path.display());
+        }
+    }
+    let upstream = upstream?;
+    stamp(&path);
+    let to = gitbridge::head(&path)?;
+    Ok(match from {
+        None => Outcome::Cloned { commit: to },
+        Some(from) if from == to => Outcome::Unchanged { commit: to },
+        // Every way of updating lands on the commit it was brought to, save a merge, which
+        // makes a commit of its own on top.
+        Some(from) if to != upstream => Outcome::Merged { from, to },
+        Some(from) if gitbridge::is_ancestor(&path, &from, &to)? => {
+            Outcome::FastForwarded { from, to }
+        }
+        Some(from) => Outcome::CheckedOut { from, to },
+    })
+}
+
+/// Brings the clone up to date, and returns the commit it was brought to: the revision it is
+/// pinned to, or the remote tracking branch it follows.
+fn update(repo: &Repository, base_path: &Path, progress: &Reporter) -> Result<Vec<u8>> {
+    let path = repo.path(base_path);
+    let remote = repo.remote_name();
+    if let Some(rev) = &repo.rev {
+        if path.join(".git").exists() {
+            log::info!("Fetching {} ({remote}) to {}", repo.url, path.display());
+            gitbridge::fetch(&path, remote, progress)?;
+        } else {
+            log::info!("Cloning {} to {}", repo.url, path.display());
+            gitbridge::clone(&repo.url, &path, remote, repo.branch.as_deref(), progress)?;
+        }
+        let revision = gitbridge::resolve(&path, rev)?;
+        log::info!("{}: pinned to {revision}", repo.name);
+        gitbridge::checkout(&path, &revision.commit)?;
+        Ok(revision.commit)
+    } else if path.join(".git").exists() {
+        let branch = match &repo.branch {
+            Some(branch) => branch.clone(),
+            None => gitbridge::current_branch(&path)?,
+        };
+        log::info!(
+            "Pulling {} ({remote}/{branch}) to {}",
+            repo.url,
+            path.display()
+        );
+        gitbridge::pull(&path, remote, &branch, progress)?;
+        let tracking = format!("refs/remotes/{remote}/{branch}");
+        gitbridge::find_commit(&path, &tracking)?
+            .ok_or_else(|| Error::Git(format!("{tracking}: not found after the pull")))
     } else {
         log::info!("Cloning {} to {}", repo.url, path.display());
-        gitbridge::clone(&repo.url, &path)
+        let branch = gitbridge::clone(&repo.url, &path, remote, repo.branch.as_deref(), progress)?;
+        log::info!("{}: following {remote}/{branch}", repo.name);
+        gitbridge::head(&path)
+    }
+}
+
+/// The file in the Git directory of the clone that records when it was last fetched, in
+/// seconds since the Unix epoch.
+///
+/// Neither Git implementation leaves a trace of its own that would do: gix writes no
+/// `FETCH_HEAD`, and the modification time of the references does not move when a fetch finds
+/// nothing new. Keeping it beside the clone rather than in the configuration means that a
+/// command which only reads does not have to rewrite the configuration to remember it.
+const LAST_FETCHED: &str = ".git/gixor-last-fetched";
+
+fn stamp(path: &Path) {
+    let now = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_secs();
+    if let Err(e) = std::fs::write(path.join(LAST_FETCHED), now.to_string()) {
+        log::warn!("{}: failed to record the fetch: {e}", path.display());
+    }
+}
+
+pub(super) fn last_fetched(repo: &Repository, base_path: &Path) -> Option<SystemTime> {
+    let content = std::fs::read_to_string(repo.path(base_path).join(LAST_FETCHED)).ok()?;
+    let secs = content.trim().parse::<u64>().ok()?;
+    Some(UNIX_EPOCH + Duration::from_secs(secs))
+}
+
+pub(super) fn resolve(repo: &Repository, rev: &str, base_path: &Path) -> Result<Revision> {
+    gitbridge::resolve(&repo.path(base_path), rev)
+}
+
+pub(super) fn branch(repo: &Repository, base_path: &Path) -> Option<String> {
+    let path = repo.path(base_path);
+    if path.join(".git").exists() {
+        gitbridge::current_branch(&path)
+            .inspect_err(|e| log::warn!("{}: {e}", path.display()))
+            .ok()
+    } else {
+        None
+    }
+}
+
+/// A repository that was never cloned has nothing to move. One whose directory did not change,
+/// because it is configured apart from the name, has nothing to move either.
+pub(super) fn relocate(from: &Repository, to: &Repository, base_path: &Path) -> Result<()> {
+    let (source, dest) = (from.path(base_path), to.path(base_path));
+    if source == dest || !source.exists() {
+        return Ok(());
+    }
+    if dest.exists() {
+        return Err(Error::Fatal(format!(
+            "{}: already exists, cannot move {} there",
+            dest.display(),
+            from.name
+        )));
+    }
+    log::info!("Moving {} to {}", source.display(), dest.display());
+    if let Some(parent) = dest.parent() {
+        std::fs::create_dir_all(parent).map_err(Error::IO)?;
+    }
+    std::fs::rename(&source, &dest).map_err(Error::IO)
+}
+
+/// A repository that was never cloned will be cloned from the new URL anyway.
+pub(super) fn set_url(repo: &Repository, base_path: &Path) -> Result<()> {
+    let path = repo.path(base_path);
+    if path.join(".git").exists() {
+        log::info!("{}: fetching from {} from now on", repo.name, repo.url);
+        gitbridge::set_remote_url(&path, repo.remote_name(), &repo.url)
+    } else {
+        Ok(())
     }
 }
 
@@ -0,0 +1,92 @@
+//! The close matches of a name that was not found, for an error to suggest instead of leaving
+//! the user at a dead end after a typo such as `pyhton`.
+//!
+//! Names are compared folded: in lowercase and without the separators `-`, `_`, `.` and spaces,
+//! so that `visual-studio-code` finds `VisualStudioCode` as if typed right. What remains is
+//! measured by the edit distance, and a candidate is close when it takes no more than a third
+//! of the edits its length would allow, and at least one.
+
+/// How many suggestions an error carries at most.
+const LIMIT: usize = 5;
+
+/// Returns the candidates close to `wanted`, the closest first, and those equally close in
+/// alphabetical order.
+pub(crate) fn close_matches<I, S>(wanted: &str, candidates: I) -> Vec<String>
+where
+    I: IntoIterator<Item = S>,
+    S: AsRef<str>,
+{
+    let folded = fold(wanted);
+    let mut matches = candidates
+        .into_iter()
+        .filter_map(|candidate| {
+            let candidate = candidate.as_ref();
+            let other = fold(candidate);
+            let distance = distance(&folded, &other);
+            let allowed = (folded.len().max(other.len()) / 3).max(1);
+            (distance <= allowed).then(|| (distance, candidate.to_string()))
+        })
+        .collect::<Vec<_>>();
+    matches.sort();
+    matches.dedup_by(|(_, a), (_, b)| a == b);
+    matches
+        .into_iter()
+        .take(LIMIT)
+        .map(|(_, candidate)| candidate)
+        .collect()
+}
+
+fn fold(name: &str) -> Vec<char> {
+    name.chars()
+        .filter(|c| !matches!(c, '-' | '_' | '.' | ' '))
+        .flat_map(char::to_lowercase)
+        .collect()
+}
+
+/// The Levenshtein distance of `a` and `b`, computed row by row.
+fn distance(a: &[char], b: &[char]) -> usize {
+    let mut row = (0..=b.len()).collect::<Vec<_>>();
+    for (i, ca) in a.iter().enumerate() {
+        let mut diagonal = row[0];
+        row[0] = i + 1;
+        for (j, cb) in b.iter().enumerate() {
+            let above = row[j + 1];
+            row[j + 1] = if ca == cb {
+                diagonal
+            } else {
+                1 + diagonal.min(above).min(row[j])
+            };
+            diagonal = above;
+        }
+    }
+    row[b.len()]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_close_matches() {
+        let candidates = [
+            "Python",
+            "Jython",
+            "VisualStudioCode",
+            "VisualStudio",
+            "Rust",
+            "Ruby",
+            "macOS",
+        ];
+        let cases: [(&str, &[&str]); 6] = [
+            ("pyhton", &["Python"]),
+            ("visual-studio-code", &["VisualStudioCode", "VisualStudio"]),
+            ("visual_studio", &["VisualStudio", "VisualStudioCode"]),
+            ("rst", &["Rust"]),
+            ("MacOs", &["macOS"]),
+            ("haskell", &[]),
+        ];
+        for (wanted, expected) in cases {
+            assert_eq!(close_matches(wanted, candidates), expected, "{wanted}");
+        }
+    }
+}
@@ -13,7 +13,7 @@ async fn test_clone_and_find() -> Result<()> {
         }
     };
     assert_eq!(gixor.len(), 1);
-    gixor.prepare(false)?; // clone all repositories
+    gixor.prepare(false).into_result()?; // clone all repositories
 
     let result = gixor.find(Name::parse("rust")).unwrap();
     assert_eq!(result.len(), 1);
@@ -31,7 +31,7 @@ async fn test_clone_and_find() -> Result<()> {
 fn test_find() {
     let gixor = GixorFactory::load(common::config_path()).unwrap();
     // the fixtures have to be on disk before a name resolves to a boilerplate
-    gixor.prepare(false).unwrap();
+    gixor.prepare(false).into_result().unwrap();
     let results = gixor.find(Name::from("devcontainer")).unwrap();
     assert_eq!(results.len(), 1);
     let result = results.first().unwrap();
@@ -41,7 +41,7 @@ pub fn setup() -> Result<Gixor> {
     // The tests of a binary run in parallel and share this one clone directory, so the
     // repositories are fetched once instead of once per test racing for the same path.
     static PREPARED: OnceLock<Result<()>> = OnceLock::new();
-    match PREPARED.get_or_init(|| gixor.prepare(false)) {
+    match PREPARED.get_or_init(|| gixor.prepare(false).into_result().map(|_| ())) {
         Ok(_) => Ok(gixor),
         Err(e) => Err(Error::Fatal(format!("failed to prepare the repositories: {e}"))),
     }
@@ -1,5 +1,13 @@
 mod common;
 
+fn names(dest: &std::path::Path) -> Vec<String> {
+    gixor::entries(dest)
+        .unwrap()
+        .iter()
+        .map(ToString::to_string)
+        .collect()
+}
+
 #[test]
 fn test_dump() {
     let gixor = match common::setup() {
@@ -27,9 +35,10 @@ fn test_dump() {
     assert!(r.is_ok());
     let entries = r.unwrap();
     assert_eq!(entries.len(), 3);
-    assert_eq!(entries[0], "Rust".to_string());
-    assert_eq!(entries[1], "Python".to_string());
-    assert_eq!(entries[2], "C".to_string());
+    assert_eq!(entries[0].to_string(), "default/Rust");
+    assert_eq!(entries[1].to_string(), "default/Python");
+    assert_eq!(entries[2].to_string(), "default/C");
+    assert!(entries.iter().all(|e| e.commit.is_some()));
 }
 
 #[test]
@@ -46,7 +55,7 @@ fn test_dump_failed() {
     let r = gixor.dump_to(vec![gixor::Name::parse("unknown")], &dest_path, false);
     assert!(r.is_err());
     let e = r.unwrap_err();
-    assert!(matches!(e, gixor::Error::BoilerplateNotFound(_)))
+    assert!(matches!(e, gixor::Error::BoilerplateNotFound(..)))
 }
 
 /// A gitignore holds hand-written rules before the first boilerplate, and dumping must carry
@@ -64,7 +73,125 @@ fn test_dump_keeps_the_prologue() {
 
     let content = std::fs::read_to_string(&dest).unwrap();
     assert!(content.starts_with("# my own rules\n*.local\n"), "{content}");
-    assert_eq!(gixor::entries(&dest).unwrap(), vec!["Rust".to_string()]);
+    assert_eq!(names(&dest), vec!["default/Rust"]);
+}
+
+/// Rules written after the end marker of a boilerplate are the user's too, and stay where they
+/// are when the gitignore is dumped again.
+#[test]
+fn test_dump_keeps_the_rules_after_the_boilerplates() {
+    let gixor = common::setup().expect("Failed to initialize Gixor");
+    let dir = tempfile::tempdir().unwrap();
+    let dest = dir.path().join(".gitignore");
+    gixor
+        .dump_to(gixor::Name::parse_all(vec!["rust", "c"]), &dest, false)
+        .unwrap();
+    let content = std::fs::read_to_string(&dest).unwrap();
+    let content = content.replacen(
+        "### Generated by Gixor (https://github.com/tamada/gixor) (default/C)",
+        "# between\n### Generated by Gixor (https://github.com/tamada/gixor) (default/C)",
+        1,
+    );
+    std::fs::write(&dest, format!("{content}# after all\n*.bak\n")).unwrap();
+
+    gixor
+        .dump_to(
+            gixor::Name::parse_all(vec!["rust", "c", "python"]),
+            &dest,
+            false,
+        )
+        .unwrap();
+
+    let content = std::fs::read_to_string(&dest).unwrap();
+    let at = |text: &str| {
+        content
+            .find(text)
+            .unwrap_or_else(|| panic!("{text}: {content}"))
+    };
+    assert!(at("(default/Rust)") < at("# between"));
+    assert!(at("# between") < at("(default/C)"));
+    assert!(at("(default/C)") < at("(default/Python)"));
+    assert!(content.ends_with("# after all\n*.bak\n"), "{content}");
+    assert_eq!(
+        names(&dest),
+        vec!["default/Rust", "default/C", "default/Python"]
+    );
+}
+
+/// A line the user added inside a boilerplate is not lost to the next dump: it is refused, and
+/// then merged or overwritten as asked.
+#[test]
+fn test_dump_keeps_the_edits_inside_a_boilerplate() {
+    let mut gixor = common::setup().expect("Failed to initialize Gixor");
+    let dir = tempfile::tempdir().unwrap();
+    let dest = dir.path().join(".gitignore");
+    let names = || gixor::Name::parse_all(vec!["rust"]);
+    gixor.dump_to(names(), &dest, false).unwrap();
+    let edited = std::fs::read_to_string(&dest).unwrap().replacen(
+        "### End of Gixor (default/Rust)",
+        "!target/keep\n### End of Gixor (default/Rust)",
+        1,
+    );
+    std::fs::write(&dest, &edited).unwrap();
+
+    match gixor.dump_to(names(), &dest, false) {
+        Err(gixor::Error::Modified(names)) => assert_eq!(names, vec!["default/Rust"]),
+        r => panic!("expected Modified, got {r:?}"),
+    }
+    assert_eq!(std::fs::read_to_string(&dest).unwrap(), edited);
+
+    // upstream has not moved since, so the merge is the edit itself
+    gixor.set_edit_policy(gixor::document::EditPolicy::Merge);
+    gixor.dump_to(names(), &dest, false).unwrap();
+    let merged = std::fs::read_to_string(&dest).unwrap();
+    assert!(merged.contains("\n### Merged: sha256:"), "{merged}");
+    let unmarked = merged
+        .lines()
+        .filter(|line| !line.starts_with("### Merged: "))
+        .map(|line| format!("{line}\n"))
+        .collect::<String>();
+    assert_eq!(unmarked, edited);
+
+    // the merged block is no longer taken as edited, and keeps its edits
+    gixor.set_edit_policy(gixor::document::EditPolicy::Refuse);
+    let report = gixor.dump_to(names(), &dest, false).unwrap();
+    assert!(report.is_unchanged(), "{report}");
+    assert_eq!(std::fs::read_to_string(&dest).unwrap(), merged);
+
+    gixor.set_edit_policy(gixor::document::EditPolicy::Overwrite);
+    gixor.dump_to(names(), &dest, false).unwrap();
+    let content = std::fs::read_to_string(&dest).unwrap();
+    assert!(!content.contains("!target/keep"), "{content}");
+}
+
+/// A gitignore just dumped is what dumping makes of it, and stops being so once edited.
+#[test]
+fn test_check_finds_the_differences() {
+    let gixor = common::setup().expect("Failed to initialize Gixor");
+    let dir = tempfile::tempdir().unwrap();
+    let dest = dir.path().join(".gitignore");
+    let names = || gixor::Name::parse_all(vec!["rust"]);
+    gixor.dump_to(names(), &dest, false).unwrap();
+    let check = gixor.check(names(), &dest).unwrap();
+    assert!(check.is_up_to_date());
+    assert_eq!(check.diff(), "");
+
+    let content = std::fs::read_to_string(&dest).unwrap();
+    std::fs::write(&dest, content.replacen("target", "target-edited", 1)).unwrap();
+    let check = gixor.check(names(), &dest).unwrap();
+    assert!(!check.is_up_to_date());
+    assert_eq!(check.hunks().len(), 1);
+    assert!(check.diff().contains("+target"), "{}", check.diff());
+    assert!(matches!(
+        check.into_result(),
+        Err(gixor::Error::Mismatch(_))
+    ));
+
+    let missing = dir.path().join("absent");
+    assert!(matches!(
+        gixor.check(names(), &missing),
+        Err(gixor::Error::FileNotFound(_))
+    ));
 }
 
 /// Dropping the prologue keeps the boilerplates and nothing else.
@@ -81,7 +208,7 @@ fn test_dump_can_clear_the_prologue() {
 
     let content = std::fs::read_to_string(&dest).unwrap();
     assert!(!content.contains("my own rules"), "{content}");
-    assert_eq!(gixor::entries(&dest).unwrap(), vec!["Rust".to_string()]);
+    assert_eq!(names(&dest), vec!["default/Rust"]);
 }
 
 /// A failing dump must leave the destination byte for byte as it was, which is what makes a
@@ -112,7 +239,7 @@ fn test_dump_creates_a_missing_gitignore() {
         .dump_to(vec![gixor::Name::parse("rust")], &dest, false)
         .unwrap();
 
-    assert_eq!(gixor::entries(&dest).unwrap(), vec!["Rust".to_string()]);
+    assert_eq!(names(&dest), vec!["default/Rust"]);
 }
 
 /// `build_gitignore` backs `--dry-run`, so it must produce what would be written while leaving
@@ -144,3 +271,48 @@ fn test_list_entries_not_found() {
     let e = r.unwrap_err();
     assert!(matches!(e, gixor::Error::FileNotFound(_)));
 }
+
+/// The report of a dump tells each block apart, and a block whose boilerplate is gone upstream
+/// is dropped from the gitignore rather than failing the dump.
+#[test]
+fn test_dump_reports_what_became_of_the_blocks() {
+    let gixor = common::setup().expect("Failed to initialize Gixor");
+    let dir = tempfile::tempdir().unwrap();
+    let dest = dir.path().join(".gitignore");
+    let report = gixor
+        .dump_to(gixor::Name::parse_all(vec!["rust", "c"]), &dest, false)
+        .unwrap();
+    let listed = |names: &[gixor::Name]| names.iter().map(ToString::to_string).collect::<Vec<_>>();
+    assert_eq!(listed(&report.added), vec!["default/Rust", "default/C"]);
+
+    let mut content = std::fs::read_to_string(&dest).unwrap();
+    content.push_str(
+        "### Generated by Gixor (https://github.com/tamada/gixor) (default/Gone)\n### Gone.gitignore\ngone/\n### End of Gixor (default/Gone)\n",
+    );
+    std::fs::write(&dest, content).unwrap();
+    let report = gixor
+        .dump_to(
+            gixor::Name::parse_all(vec!["default/Rust", "default/Gone", "python", "rust"]),
+            &dest,
+            false,
+        )
+        .unwrap();
+    assert_eq!(listed(&report.added), vec!["default/Python"]);
+    assert_eq!(listed(&report.unchanged), vec!["default/Rust"]);
+    assert_eq!(listed(&report.removed), vec!["default/C"]);
+    assert_eq!(report.dropped.len(), 1);
+    assert_eq!(report.dropped[0].name.to_string(), "default/Gone");
+    assert!(report.updated.is_empty());
+    assert!(!report.is_unchanged());
+    assert_eq!(
+        report.to_string().lines().next(),
+        Some(
+            format!(
+                "{}: 1 added, 1 unchanged, 1 removed, 1 dropped",
+                dest.display()
+            )
+            .as_str()
+        )
+    );
+    assert_eq!(names(&dest), vec!["default/Rust", "default/Python"]);
+}
@@ -41,7 +41,7 @@ fn the_snapshot_repositories_are_configured() {
 #[test]
 fn preparing_has_nothing_to_do() {
     // No clone, no network, and no error either: the snapshot is already whatever it will be.
-    assert!(GixorFactory::embedded().prepare(false).is_ok());
+    assert!(GixorFactory::embedded().prepare(false).is_success());
 }
 
 #[test]
@@ -63,7 +63,10 @@ fn the_content_matches_the_snapshot() {
 
     let dumped = rust.dump("").unwrap();
     let expected = std::fs::read_to_string(snapshot_dir().join("Rust.gitignore")).unwrap();
-    assert!(dumped.ends_with(&format!("{expected}\n")), "{dumped}");
+    assert!(
+        dumped.ends_with(&format!("{expected}### End of Gixor (default/Rust)\n")),
+        "{dumped}"
+    );
 }
 
 /// The permalink cannot be resolved through Git here, so it names the commit the snapshot was
@@ -97,7 +100,47 @@ fn building_a_gitignore_keeps_the_given_prologue() {
 fn a_name_that_is_not_in_the_snapshot_is_an_error() {
     let gixor = GixorFactory::embedded();
     let r = gixor.find(Name::parse("no-such-boilerplate"));
-    assert!(matches!(r, Err(gixor::Error::BoilerplateNotFound(_))));
+    assert!(matches!(r, Err(gixor::Error::BoilerplateNotFound(..))));
+}
+
+#[test]
+fn a_misspelled_name_suggests_the_boilerplates_close_to_it() {
+    let gixor = GixorFactory::embedded();
+    match gixor.find(Name::parse("pyhton")) {
+        Err(gixor::Error::BoilerplateNotFound(name, suggestions)) => {
+            assert_eq!(name, "pyhton");
+            assert!(
+                suggestions.contains(&"Python".to_string()),
+                "{suggestions:?}"
+            );
+        }
+        _ => panic!("expected BoilerplateNotFound"),
+    }
+    match gixor.find(Name::parse("default/visual-studio-code")) {
+        Err(gixor::Error::BoilerplateNotFound(_, suggestions)) => {
+            assert_eq!(
+                suggestions.first().map(String::as_str),
+                Some("default/VisualStudioCode")
+            );
+        }
+        _ => panic!("expected BoilerplateNotFound"),
+    }
+    // a name qualified with a path is compared with the paths
+    for (typed, expected) in [
+        ("Global/macOz", "Global/macOS"),
+        ("default/Global/macOz", "default/Global/macOS"),
+    ] {
+        match gixor.find(Name::parse(typed)) {
+            Err(gixor::Error::BoilerplateNotFound(_, suggestions)) => {
+                assert_eq!(
+                    suggestions.first().map(String::as_str),
+                    Some(expected),
+                    "{typed}"
+                );
+            }
+            _ => panic!("expected BoilerplateNotFound"),
+        }
+    }
 }
 
 fn walk(dir: &std::path::Path) -> usize {
@@ -3,6 +3,7 @@
 //! The boilerplates are compiled in, so nothing here reaches for a file system, a clone or the
 //! network. The page hands over the `.gitignore` it already has and gets the new one back; what
 //! to do with it afterwards is the page's business.
+use gixor::document::{EditPolicy, GitignoreDocument};
 use gixor::{GixorFactory, Name, RepositoryManager};
 use wasm_bindgen::prelude::*;
 
@@ -38,19 +39,35 @@ pub fn list_boilerplates() -> Vec<String> {
 /// Builds a `.gitignore` from the named boilerplates.
 ///
 /// `current` is the content of the gitignore as it stands. Whatever precedes the first
-/// boilerplate in it is the reader's own and is carried over; the rest is written again from the
-/// boilerplates named here. Pass an empty string to start from nothing.
+/// boilerplate in it, or follows the end marker of one, is the reader's own and is carried over
+/// in place; the boilerplates are written again from the ones named here. Pass an empty string
+/// to start from nothing.
 ///
 /// A name that no build carries is an error rather than a silent omission: a gitignore missing
-/// the rules someone asked for looks finished and is not.
+/// the rules someone asked for looks finished and is not. So is a boilerplate of `current`
+/// edited by hand since it was generated, unless `force` is `true` to overwrite the edit. There
+/// is no history here to merge it with.
 #[wasm_bindgen]
-pub fn generate(names: Vec<String>, current: &str) -> Result<String, JsError> {
-    let gixor = GixorFactory::embedded();
+pub fn generate(names: Vec<String>, current: &str, force: Option<bool>) -> Result<String, JsError> {
+    let mut gixor = GixorFactory::embedded();
+    if force == Some(true) {
+        gixor.set_edit_policy(EditPolicy::Overwrite);
+    }
     gixor
         .build_gitignore_with(Name::parse_all(names), current)
         .map_err(|e| JsError::new(&e.to_string()))
 }
 
+/// The names of the boilerplates `current` lists, such as `default/Rust`, for a picker to
+/// start from what the gitignore already has.
+#[wasm_bindgen]
+pub fn entries(current: &str) -> Vec<String> {
+    GitignoreDocument::parse(current)
+        .blocks()
+        .map(|block| block.name().to_string())
+        .collect()
+}
+
 /// The commit of the boilerplate repository this build carries, so a page can say how old the
 /// boilerplates it is offering are.
 #[wasm_bindgen]